@@ -0,0 +1,87 @@
+//! Compares the payload construction of `POGRLogger::log` with the former JSON string round-trip,
+//! where `structured_log!` serialized its fields into the message and the logger parsed them back.
+//!
+//! Run with `cargo bench --bench structured_log`.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use log::kv::Value as KvValue;
+use log::{Level, Record};
+use pogr_log_rs::{LogConfig, LoggerConfig, POGRLogger};
+use reqwest::Client;
+use serde_json::{json, Value};
+
+fn logger() -> POGRLogger {
+    let logger_config = LoggerConfig::new("bench_service", "bench");
+    POGRLogger::new(
+        Client::new(),
+        Some("http://localhost/v1/intake/logs".to_string()),
+        LogConfig::AccessKeys {
+            access_key: "access".to_string(),
+            secret_key: "secret".to_string(),
+            logger_config: logger_config.clone(),
+        },
+        logger_config,
+    )
+}
+
+/// What `structured_log!` and `POGRLogger::log` used to do for every structured record.
+fn round_trip(logger: &POGRLogger, level: Level, msg: &str, log_type: &str, data: &Value, tags: &Value) -> Value {
+    let message = json!({"log": msg, "type": log_type, "data": data, "tags": tags}).to_string();
+    let mut payload = logger.build_payload(&Record::builder().args(format_args!("")).level(level).build());
+    match serde_json::from_str::<Value>(&message) {
+        Ok(Value::Object(fields)) => {
+            for (key, value) in fields {
+                payload[&key] = value;
+            }
+        },
+        _ => payload["log"] = Value::String(message),
+    }
+    payload
+}
+
+fn bench_structured(c: &mut Criterion) {
+    let logger = logger();
+    let data = json!({"user_id": 123, "match": {"map": "dust", "players": [1, 2, 3, 4, 5, 6, 7, 8, 9, 10]}});
+    let tags = json!({"env": "production", "platform": "web"});
+
+    let mut group = c.benchmark_group("structured_record");
+    group.bench_function("json_round_trip", |b| {
+        b.iter(|| round_trip(&logger, Level::Info, "User logged in", "login", black_box(&data), black_box(&tags)))
+    });
+    group.bench_function("key_values", |b| {
+        b.iter(|| {
+            let kvs: [(&str, KvValue); 3] = [
+                ("pogr.type", KvValue::from("login")),
+                ("pogr.data", KvValue::from_serde(black_box(&data))),
+                ("pogr.tags", KvValue::from_serde(black_box(&tags))),
+            ];
+            logger.build_payload(&Record::builder()
+                .args(format_args!("{}", "User logged in"))
+                .level(Level::Info)
+                .key_values(&kvs)
+                .build())
+        })
+    });
+    group.finish();
+}
+
+fn bench_plain(c: &mut Criterion) {
+    let logger = logger();
+    let message = "Player 42 joined lobby 7 after 1.3s in queue";
+
+    let mut group = c.benchmark_group("plain_record");
+    group.bench_function("parse_attempt", |b| {
+        b.iter(|| {
+            // Every plain message used to be tried as JSON first.
+            let _ = serde_json::from_str::<Value>(black_box(message));
+            logger.build_payload(&Record::builder().args(format_args!("{}", message)).level(Level::Info).build())
+        })
+    });
+    group.bench_function("no_parse", |b| {
+        b.iter(|| logger.build_payload(&Record::builder().args(format_args!("{}", black_box(message))).level(Level::Info).build()))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_structured, bench_plain);
+criterion_main!(benches);
@@ -0,0 +1,103 @@
+//! `#[derive(PogrEvent)]` for typed POGR log events.
+//!
+//! This crate is re-exported by `pogr_log_rs` behind its `derive` feature; use it from there.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, DeriveInput, LitStr, Path};
+
+/// Implements `pogr_log_rs::PogrEvent` for a struct or enum that also implements `serde::Serialize`.
+///
+/// # Attributes
+/// - `type = "..."` (required): the `type` every event of this kind is logged with.
+/// - `level = "..."`: one of `error`, `warn`, `info`, `debug`, `trace`; defaults to `info`.
+/// - `message = "..."`: the log message; defaults to the type.
+/// - `tags(key = "value", ...)`: a fixed tag set sent with every event.
+/// - `crate = "..."`: the path to `pogr_log_rs`, for crates that rename the dependency or use it
+///   through a re-export; defaults to `::pogr_log_rs`.
+///
+/// Events are logged with the path of the module the derive is in as their target.
+///
+/// # Examples
+/// ```ignore
+/// #[derive(Serialize, PogrEvent)]
+/// #[pogr(type = "login", level = "info", message = "User logged in", tags(env = "production"))]
+/// struct Login {
+///     user_id: u64,
+/// }
+///
+/// Login { user_id: 123 }.emit();
+/// ```
+#[proc_macro_derive(PogrEvent, attributes(pogr))]
+pub fn derive_pogr_event(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let mut log_type: Option<LitStr> = None;
+    let mut level = quote!(Info);
+    let mut message: Option<LitStr> = None;
+    let mut tags: Vec<(LitStr, LitStr)> = Vec::new();
+    let mut krate: Path = syn::parse_quote!(::pogr_log_rs);
+
+    for attr in input.attrs.iter().filter(|attr| attr.path().is_ident("pogr")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("type") {
+                log_type = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("level") {
+                let lit: LitStr = meta.value()?.parse()?;
+                level = match lit.value().to_lowercase().as_str() {
+                    "error" => quote!(Error),
+                    "warn" => quote!(Warn),
+                    "info" => quote!(Info),
+                    "debug" => quote!(Debug),
+                    "trace" => quote!(Trace),
+                    _ => return Err(syn::Error::new(lit.span(), "expected one of `error`, `warn`, `info`, `debug`, `trace`")),
+                };
+            } else if meta.path.is_ident("message") {
+                message = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("tags") {
+                meta.parse_nested_meta(|tag| {
+                    let key = tag.path.get_ident()
+                        .ok_or_else(|| tag.error("expected a tag name"))?;
+                    let key = LitStr::new(&key.to_string(), key.span());
+                    tags.push((key, tag.value()?.parse()?));
+                    Ok(())
+                })?;
+            } else if meta.path.is_ident("crate") {
+                let lit: LitStr = meta.value()?.parse()?;
+                krate = lit.parse()?;
+            } else {
+                return Err(meta.error("expected `type`, `level`, `message`, `tags` or `crate`"));
+            }
+            Ok(())
+        })?;
+    }
+
+    let log_type = log_type.ok_or_else(|| {
+        syn::Error::new_spanned(&input.ident, "missing `#[pogr(type = \"...\")]` attribute")
+    })?;
+    let message = message.unwrap_or_else(|| log_type.clone());
+    let tag_keys = tags.iter().map(|(key, _)| key);
+    let tag_values = tags.iter().map(|(_, value)| value);
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #krate::PogrEvent for #name #ty_generics #where_clause {
+            const TYPE: &'static str = #log_type;
+            const LEVEL: #krate::__private::Level = #krate::__private::Level::#level;
+            const MESSAGE: &'static str = #message;
+            const TARGET: &'static str = ::core::module_path!();
+
+            fn tags(&self) -> #krate::__private::Value {
+                #krate::__private::json!({ #( #tag_keys: #tag_values ),* })
+            }
+        }
+    })
+}
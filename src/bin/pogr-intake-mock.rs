@@ -0,0 +1,104 @@
+//! Runs a local mock of the POGR intake, see `pogr_log_rs::mock_intake`.
+
+use pogr_log_rs::mock_intake::{MockIntake, MockIntakeConfig, FAULTS_PATH, LOGS_PATH};
+use pogr_log_rs::Credentials;
+use std::net::SocketAddr;
+use std::process::ExitCode;
+use std::time::Duration;
+
+const USAGE: &str = "\
+Usage: pogr-intake-mock [OPTIONS]
+
+Options:
+  --addr <ADDR>                 Address to listen on [default: 127.0.0.1:8089]
+  --access-key <KEY>            Accept this access key; requires --secret-key
+  --secret-key <SECRET>         Secret key for the preceding --access-key
+  --client-id <ID>              Accept this client id; requires --build-id
+  --build-id <ID>               Build id for the preceding --client-id
+  --latency-ms <MS>             Delay every intake answer
+  --token-lifetime-secs <SECS>  Lifetime of issued session tokens [default: 3600]
+  -h, --help                    Print this help
+
+Credential options may be repeated. Without any, all credentials are accepted.";
+
+struct Options {
+    config: MockIntakeConfig,
+    latency: Duration,
+}
+
+fn parse(mut args: impl Iterator<Item = String>) -> Result<Option<Options>, String> {
+    let mut config = MockIntakeConfig::new().bind(SocketAddr::from(([127, 0, 0, 1], 8089)));
+    let mut latency = Duration::ZERO;
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or_else(|| format!("{} needs a value", name));
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "--addr" => {
+                let addr = value("--addr")?;
+                config = config.bind(addr.parse().map_err(|_| format!("invalid address: {}", addr))?);
+            },
+            "--access-key" => {
+                let access_key = value("--access-key")?;
+                if args.next().as_deref() != Some("--secret-key") {
+                    return Err("--access-key must be followed by --secret-key".to_string());
+                }
+                let secret_key = args.next().ok_or("--secret-key needs a value")?;
+                config = config.accept(Credentials::AccessKeys { access_key, secret_key });
+            },
+            "--client-id" => {
+                let client_id = value("--client-id")?;
+                if args.next().as_deref() != Some("--build-id") {
+                    return Err("--client-id must be followed by --build-id".to_string());
+                }
+                let build_id = args.next().ok_or("--build-id needs a value")?;
+                config = config.accept(Credentials::ClientBuild { client_id, build_id });
+            },
+            "--latency-ms" => {
+                let ms = value("--latency-ms")?;
+                latency = Duration::from_millis(ms.parse().map_err(|_| format!("invalid --latency-ms: {}", ms))?);
+            },
+            "--token-lifetime-secs" => {
+                let secs = value("--token-lifetime-secs")?;
+                let secs = secs.parse().map_err(|_| format!("invalid --token-lifetime-secs: {}", secs))?;
+                config = config.token_lifetime(Duration::from_secs(secs));
+            },
+            other => return Err(format!("unexpected argument: {}", other)),
+        }
+    }
+    Ok(Some(Options { config, latency }))
+}
+
+fn main() -> ExitCode {
+    let options = match parse(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        },
+        Err(message) => {
+            eprintln!("error: {}\n\n{}", message, USAGE);
+            return ExitCode::from(2);
+        },
+    };
+    let runtime = match tokio::runtime::Builder::new_multi_thread().enable_all().build() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("error: cannot start runtime: {}", e);
+            return ExitCode::FAILURE;
+        },
+    };
+    runtime.block_on(async {
+        let intake = match MockIntake::start(options.config).await {
+            Ok(intake) => intake,
+            Err(e) => {
+                eprintln!("error: cannot listen: {}", e);
+                return ExitCode::FAILURE;
+            },
+        };
+        intake.set_latency(options.latency);
+        println!("Mock intake listening on {}", intake.url());
+        println!("Received logs: http://{}{}", intake.addr(), LOGS_PATH);
+        println!("Fault injection: http://{}{}", intake.addr(), FAULTS_PATH);
+        std::future::pending::<ExitCode>().await
+    })
+}
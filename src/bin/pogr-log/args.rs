@@ -0,0 +1,44 @@
+//! A small command-line argument reader.
+
+use std::collections::VecDeque;
+use std::str::FromStr;
+
+use crate::Failure;
+
+pub struct Args {
+    args: VecDeque<String>,
+}
+
+impl Args {
+    pub fn new(args: impl Iterator<Item = String>) -> Self {
+        Args { args: args.collect() }
+    }
+
+    pub fn next(&mut self) -> Option<String> {
+        self.args.pop_front()
+    }
+
+    pub fn wants_help(&self) -> bool {
+        self.args.iter().any(|arg| arg == "-h" || arg == "--help")
+    }
+
+    /// The value following the option `name`.
+    pub fn value(&mut self, name: &str) -> Result<String, Failure> {
+        self.next().ok_or_else(|| Failure::Usage(format!("{} needs a value", name)))
+    }
+
+    /// The value following the option `name`, parsed.
+    pub fn parse<T: FromStr>(&mut self, name: &str) -> Result<T, Failure> {
+        let value = self.value(name)?;
+        value.parse().map_err(|_| Failure::Usage(format!("invalid value for {}: {}", name, value)))
+    }
+
+    /// The value following the option `name`, as a JSON object.
+    pub fn object(&mut self, name: &str) -> Result<serde_json::Value, Failure> {
+        let value = self.value(name)?;
+        match serde_json::from_str(&value) {
+            Ok(object @ serde_json::Value::Object(_)) => Ok(object),
+            _ => Err(Failure::Usage(format!("{} must be a JSON object: {}", name, value))),
+        }
+    }
+}
@@ -0,0 +1,102 @@
+//! Options shared by every command: where to send records, as whom, and how long to wait.
+
+use pogr_log_rs::credentials::EnvCredentials;
+use pogr_log_rs::http::HttpConfig;
+use pogr_log_rs::{BlockingMode, CredentialProvider, Credentials, LogConfig, LoggerConfig, POGRLogger};
+use std::env;
+use std::time::Duration;
+
+use crate::args::Args;
+use crate::Failure;
+
+pub const USAGE: &str = "\
+Connection options:
+  --url <URL>              Intake URL [env: POGR_INTAKE_URL]
+  --service <NAME>         Service name [env: POGR_SERVICE] [default: pogr-log]
+  --environment <NAME>     Environment [env: POGR_ENVIRONMENT] [default: production]
+  --access-key <KEY>       Access key, with --secret-key [env: POGR_ACCESS_KEY]
+  --secret-key <SECRET>    Secret key, with --access-key [env: POGR_SECRET_KEY]
+  --client-id <ID>         Client id, with --build-id [env: POGR_CLIENT_ID]
+  --build-id <ID>          Build id, with --client-id [env: POGR_BUILD_ID]
+  --timeout-secs <SECS>    How long to wait for delivery [default: 30]
+
+HTTP settings are read from the POGR_HTTP_* environment variables.";
+
+#[derive(Default)]
+pub struct Connection {
+    url: Option<String>,
+    service: Option<String>,
+    environment: Option<String>,
+    access_key: Option<String>,
+    secret_key: Option<String>,
+    client_id: Option<String>,
+    build_id: Option<String>,
+    timeout: Option<Duration>,
+}
+
+impl Connection {
+    /// Takes `arg` and its value if it is a connection option; returns whether it was one.
+    pub fn parse(&mut self, arg: &str, args: &mut Args) -> Result<bool, Failure> {
+        match arg {
+            "--url" => self.url = Some(args.value(arg)?),
+            "--service" => self.service = Some(args.value(arg)?),
+            "--environment" => self.environment = Some(args.value(arg)?),
+            "--access-key" => self.access_key = Some(args.value(arg)?),
+            "--secret-key" => self.secret_key = Some(args.value(arg)?),
+            "--client-id" => self.client_id = Some(args.value(arg)?),
+            "--build-id" => self.build_id = Some(args.value(arg)?),
+            "--timeout-secs" => self.timeout = Some(Duration::from_secs(args.parse(arg)?)),
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    /// How long to wait for records to be delivered.
+    pub fn timeout(&self) -> Duration {
+        self.timeout.unwrap_or(Duration::from_secs(30))
+    }
+
+    /// A logger sending from a runtime of its own, so that the commands can stay synchronous.
+    pub fn connect(&self) -> Result<POGRLogger, Failure> {
+        let credentials = self.credentials()?;
+        let logger_config = LoggerConfig::new(
+            self.service.clone().or_else(|| env::var("POGR_SERVICE").ok()).unwrap_or_else(|| "pogr-log".to_string()),
+            self.environment.clone().or_else(|| env::var("POGR_ENVIRONMENT").ok()).unwrap_or_else(|| "production".to_string()),
+        );
+        let auth_config = match credentials {
+            Credentials::AccessKeys { access_key, secret_key } => LogConfig::AccessKeys {
+                access_key,
+                secret_key,
+                logger_config: logger_config.clone(),
+            },
+            Credentials::ClientBuild { client_id, build_id } => LogConfig::ClientBuild {
+                client_id,
+                build_id,
+                logger_config: logger_config.clone(),
+            },
+        };
+        let http = HttpConfig::from_env().map_err(|e| Failure::Config(e.to_string()))?;
+        let client = http.build_client(None).map_err(|e| Failure::Config(e.to_string()))?;
+        let mut logger = POGRLogger::new(client, self.url.clone(), auth_config, logger_config);
+        logger.use_blocking_transport(BlockingMode::Background)
+            .map_err(|e| Failure::Config(format!("cannot start the sending thread: {}", e)))?;
+        logger.on_delivery_error(|error, _| eprintln!("error: delivery failed: {}", error));
+        Ok(logger)
+    }
+
+    /// Credentials from the options, or else from the environment.
+    fn credentials(&self) -> Result<Credentials, Failure> {
+        let pair = |first: &Option<String>, second: &Option<String>, names: &str| match (first, second) {
+            (Some(first), Some(second)) => Ok(Some((first.clone(), second.clone()))),
+            (None, None) => Ok(None),
+            _ => Err(Failure::Usage(format!("{} must be given together", names))),
+        };
+        if let Some((access_key, secret_key)) = pair(&self.access_key, &self.secret_key, "--access-key and --secret-key")? {
+            return Ok(Credentials::AccessKeys { access_key, secret_key });
+        }
+        if let Some((client_id, build_id)) = pair(&self.client_id, &self.build_id, "--client-id and --build-id")? {
+            return Ok(Credentials::ClientBuild { client_id, build_id });
+        }
+        EnvCredentials::default().credentials().map_err(|e| Failure::Config(e.to_string()))
+    }
+}
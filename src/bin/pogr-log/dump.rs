@@ -0,0 +1,49 @@
+//! Reading NDJSON dumps, such as the files written by `pogr_log_rs::spool::Spool`.
+
+use serde_json::Value;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::PathBuf;
+
+use crate::Failure;
+
+/// The dumps to read, in order; stdin without any, or for `-`.
+pub struct Dumps {
+    paths: Vec<PathBuf>,
+}
+
+impl Dumps {
+    pub fn new(paths: Vec<PathBuf>) -> Self {
+        Dumps { paths }
+    }
+
+    /// Calls `each` with every record, in order. Lines that are not JSON objects are reported on
+    /// stderr and skipped; `each` returns `false` to stop early.
+    pub fn read(&self, mut each: impl FnMut(Value) -> bool) -> Result<(), Failure> {
+        let stdin = [PathBuf::from("-")];
+        let paths = if self.paths.is_empty() { &stdin[..] } else { &self.paths[..] };
+        for path in paths {
+            let reader: Box<dyn BufRead> = if path.as_os_str() == "-" {
+                Box::new(io::stdin().lock())
+            } else {
+                let file = File::open(path).map_err(|e| Failure::Config(format!("cannot open {}: {}", path.display(), e)))?;
+                Box::new(BufReader::new(file))
+            };
+            for (number, line) in reader.lines().enumerate() {
+                let line = line.map_err(|e| Failure::Config(format!("cannot read {}: {}", path.display(), e)))?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                match serde_json::from_str(&line) {
+                    Ok(record @ Value::Object(_)) => {
+                        if !each(record) {
+                            return Ok(());
+                        }
+                    },
+                    _ => eprintln!("warning: {}:{}: not a JSON object, skipped", path.display(), number + 1),
+                }
+            }
+        }
+        Ok(())
+    }
+}
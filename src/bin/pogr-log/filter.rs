@@ -0,0 +1,88 @@
+//! Selecting records from a dump by level, type, time and tags.
+
+use log::Level;
+use serde_json::Value;
+
+use crate::args::Args;
+use crate::record::parse_level;
+use crate::{time, Failure};
+
+pub const USAGE: &str = "\
+Filter options:
+  --level <LEVEL>        Only records at this level or more severe
+  --type <TYPE>          Only records of this type; may be repeated
+  --since <TIME>         Only records at or after TIME (RFC 3339, a date, or epoch milliseconds)
+  --until <TIME>         Only records before TIME
+  --tag <KEY=VALUE>      Only records with this tag; may be repeated";
+
+#[derive(Default)]
+pub struct Filter {
+    level: Option<Level>,
+    types: Vec<String>,
+    since: Option<i64>,
+    until: Option<i64>,
+    tags: Vec<(String, String)>,
+}
+
+impl Filter {
+    /// Takes `arg` and its value if it is a filter option; returns whether it was one.
+    pub fn parse(&mut self, arg: &str, args: &mut Args) -> Result<bool, Failure> {
+        match arg {
+            "--level" => {
+                let level = args.value(arg)?;
+                self.level = Some(parse_level(&level).ok_or_else(|| Failure::Usage(format!("invalid level: {}", level)))?);
+            },
+            "--type" => self.types.push(args.value(arg)?),
+            "--since" => self.since = Some(parse_time(arg, args)?),
+            "--until" => self.until = Some(parse_time(arg, args)?),
+            "--tag" => {
+                let tag = args.value(arg)?;
+                let (key, value) = tag.split_once('=')
+                    .ok_or_else(|| Failure::Usage(format!("--tag must look like KEY=VALUE: {}", tag)))?;
+                self.tags.push((key.to_string(), value.to_string()));
+            },
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    pub fn matches(&self, record: &Value) -> bool {
+        if let Some(level) = self.level {
+            match record["severity"].as_str().and_then(parse_level) {
+                Some(severity) if severity <= level => {},
+                _ => return false,
+            }
+        }
+        if !self.types.is_empty() && !self.types.iter().any(|log_type| record["type"] == log_type.as_str()) {
+            return false;
+        }
+        if self.since.is_some() || self.until.is_some() {
+            let timestamp = match timestamp(record) {
+                Some(timestamp) => timestamp,
+                None => return false,
+            };
+            if self.since.is_some_and(|since| timestamp < since) || self.until.is_some_and(|until| timestamp >= until) {
+                return false;
+            }
+        }
+        self.tags.iter().all(|(key, value)| match &record["tags"][key.as_str()] {
+            Value::String(tag) => tag == value,
+            Value::Null => false,
+            other => serde_json::from_str::<Value>(value).is_ok_and(|value| value == *other),
+        })
+    }
+}
+
+fn parse_time(arg: &str, args: &mut Args) -> Result<i64, Failure> {
+    let value = args.value(arg)?;
+    time::parse(&value).ok_or_else(|| Failure::Usage(format!("invalid time for {}: {}", arg, value)))
+}
+
+/// The record's `timestamp`, in epoch milliseconds or RFC 3339.
+pub fn timestamp(record: &Value) -> Option<i64> {
+    match &record["timestamp"] {
+        Value::Number(millis) => millis.as_i64(),
+        Value::String(text) => time::parse(text),
+        _ => None,
+    }
+}
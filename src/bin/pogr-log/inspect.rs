@@ -0,0 +1,99 @@
+//! `pogr-log inspect`: filters and prints the records of NDJSON dumps.
+
+use serde_json::Value;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use crate::args::Args;
+use crate::dump::Dumps;
+use crate::filter::{self, Filter};
+use crate::{time, Failure, Outcome};
+
+pub fn usage() -> String {
+    format!("\
+Usage: pogr-log inspect [OPTIONS] [FILE]...
+
+Prints the records of NDJSON dumps, such as spool files, that pass the filters. Reads stdin without
+FILE or for `-`.
+
+Options:
+  --output <FORMAT>    text: one line per record, with data and tags below it; json: pretty-printed
+                       JSON; ndjson: the matching lines, e.g. for `pogr-log replay` [default: text]
+  --limit <N>          Stop after N records
+
+{}", filter::USAGE)
+}
+
+enum Output {
+    Text,
+    Json,
+    Ndjson,
+}
+
+pub fn run(mut args: Args) -> Result<Outcome, Failure> {
+    let mut filter = Filter::default();
+    let mut output = Output::Text;
+    let mut limit = usize::MAX;
+    let mut paths = Vec::new();
+    while let Some(arg) = args.next() {
+        if filter.parse(&arg, &mut args)? {
+            continue;
+        }
+        match arg.as_str() {
+            "--output" => output = match args.value(&arg)?.as_str() {
+                "text" => Output::Text,
+                "json" => Output::Json,
+                "ndjson" => Output::Ndjson,
+                other => return Err(Failure::Usage(format!("unknown output format: {}", other))),
+            },
+            "--limit" => limit = args.parse(&arg)?,
+            path if path == "-" || !path.starts_with('-') => paths.push(PathBuf::from(path)),
+            other => return Err(Failure::Usage(format!("unexpected option: {}", other))),
+        }
+    }
+
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    let mut printed = 0;
+    Dumps::new(paths).read(|record| {
+        if printed >= limit {
+            return false;
+        }
+        if !filter.matches(&record) {
+            return true;
+        }
+        printed += 1;
+        let written = match output {
+            Output::Text => write_text(&mut out, &record),
+            Output::Json => serde_json::to_string_pretty(&record).map_err(io::Error::from)
+                .and_then(|json| writeln!(out, "{}", json)),
+            Output::Ndjson => writeln!(out, "{}", record),
+        };
+        // Stops quietly when the reader goes away, as with `| head`.
+        written.is_ok()
+    })?;
+    Ok(Outcome::Delivered)
+}
+
+/// `2026-10-18T12:30:00.250Z WARN  game-server/production deploy: Deploy started`, then data and tags.
+fn write_text(out: &mut impl Write, record: &Value) -> io::Result<()> {
+    let time = filter::timestamp(record).map(time::format).unwrap_or_else(|| format!("{:<24}", "-"));
+    let severity = record["severity"].as_str().unwrap_or("-").to_uppercase();
+    let text = |field: &str| record[field].as_str().unwrap_or("-").to_string();
+    let message = match &record["log"] {
+        Value::String(message) => message.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    };
+    write!(out, "{} {:<5} {}/{}", time, severity, text("service"), text("environment"))?;
+    match record["type"].as_str() {
+        Some(log_type) => writeln!(out, " {}: {}", log_type, message)?,
+        None => writeln!(out, " {}", message)?,
+    }
+    for field in ["data", "tags"] {
+        if !record[field].is_null() {
+            writeln!(out, "    {} {}", field, record[field])?;
+        }
+    }
+    Ok(())
+}
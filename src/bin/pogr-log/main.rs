@@ -0,0 +1,82 @@
+//! `pogr-log`: ships log records to POGR from shell scripts and legacy processes.
+//!
+//! Exit codes: 0 when every record was delivered, 1 when some were not (rejected, unreachable intake,
+//! or still unsent when the timeout ran out), 2 for usage and configuration errors.
+
+mod args;
+mod connection;
+mod dump;
+mod filter;
+mod inspect;
+mod record;
+mod replay;
+mod send;
+mod tail;
+mod time;
+
+use std::process::ExitCode;
+
+use args::Args;
+
+const USAGE: &str = "\
+Usage: pogr-log <COMMAND> [OPTIONS]
+
+Commands:
+  send     Send a single record
+  tail     Send each line of stdin or a file
+  inspect  Filter and print the records of NDJSON dumps
+  replay   Send the records of NDJSON dumps again
+
+Run `pogr-log <COMMAND> --help` for the options of a command.";
+
+/// How a command ended, mapped to the process exit code.
+pub enum Outcome {
+    Delivered,
+    Undelivered,
+}
+
+/// Why a command could not run.
+pub enum Failure {
+    /// Bad arguments; the usage text of the command is printed with the message.
+    Usage(String),
+    /// The arguments were fine, but the logger or the input could not be set up.
+    Config(String),
+}
+
+fn main() -> ExitCode {
+    let mut args = Args::new(std::env::args().skip(1));
+    let (command, usage) = match args.next().as_deref() {
+        Some("send") => (send::run as fn(Args) -> Result<Outcome, Failure>, send::usage()),
+        Some("tail") => (tail::run as fn(Args) -> Result<Outcome, Failure>, tail::usage()),
+        Some("inspect") => (inspect::run as fn(Args) -> Result<Outcome, Failure>, inspect::usage()),
+        Some("replay") => (replay::run as fn(Args) -> Result<Outcome, Failure>, replay::usage()),
+        Some("-h" | "--help") => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        },
+        Some(other) => {
+            eprintln!("error: unknown command: {}\n\n{}", other, USAGE);
+            return ExitCode::from(2);
+        },
+        None => {
+            eprintln!("{}", USAGE);
+            return ExitCode::from(2);
+        },
+    };
+    if args.wants_help() {
+        println!("{}", usage);
+        return ExitCode::SUCCESS;
+    }
+    match command(args) {
+        Ok(Outcome::Delivered) => ExitCode::SUCCESS,
+        Ok(Outcome::Undelivered) => ExitCode::from(1),
+        Err(Failure::Usage(message)) => {
+            eprintln!("error: {}\n\n{}", message, usage);
+            ExitCode::from(2)
+        },
+        Err(Failure::Config(message)) => {
+            eprintln!("error: {}", message);
+            ExitCode::from(2)
+        },
+    }
+}
@@ -0,0 +1,187 @@
+//! Records as the commands see them, and their delivery through a `POGRLogger`.
+
+use log::kv::Value as KvValue;
+use log::{Level, Log, Record};
+use pogr_log_rs::POGRLogger;
+use serde_json::{Map, Value};
+use std::time::Duration;
+
+use crate::args::Args;
+use crate::{Failure, Outcome};
+
+pub const USAGE: &str = "\
+Record options:
+  --level <LEVEL>    error, warn or info [default: info]
+  --type <TYPE>      Record type
+  --data <JSON>      JSON object sent as the record's data
+  --tags <JSON>      JSON object sent as the record's tags";
+
+/// One record to send.
+pub struct Entry {
+    pub level: Level,
+    pub message: String,
+    pub log_type: Option<String>,
+    pub data: Option<Value>,
+    pub tags: Option<Value>,
+}
+
+/// The record options, applied to every record a command sends.
+pub struct Defaults {
+    level: Level,
+    log_type: Option<String>,
+    data: Option<Value>,
+    tags: Option<Value>,
+}
+
+impl Default for Defaults {
+    fn default() -> Self {
+        Defaults { level: Level::Info, log_type: None, data: None, tags: None }
+    }
+}
+
+impl Defaults {
+    /// Takes `arg` and its value if it is a record option; returns whether it was one.
+    pub fn parse(&mut self, arg: &str, args: &mut Args) -> Result<bool, Failure> {
+        match arg {
+            "--level" => {
+                let level = args.value(arg)?;
+                self.level = parse_level(&level).filter(|level| *level <= Level::Info)
+                    .ok_or_else(|| Failure::Usage(format!("invalid level: {}", level)))?;
+            },
+            "--type" => self.log_type = Some(args.value(arg)?),
+            "--data" => self.data = Some(args.object(arg)?),
+            "--tags" => self.tags = Some(args.object(arg)?),
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    /// A record with `message` and the defaults.
+    pub fn text(&self, message: String) -> Entry {
+        Entry {
+            level: self.level,
+            message,
+            log_type: self.log_type.clone(),
+            data: self.data.clone(),
+            tags: self.tags.clone(),
+        }
+    }
+
+    /// A record from a JSON object, taking the message from `log`, `message` or `msg`, the level from
+    /// `severity` or `level`, and `type`, `data` and `tags` as they are; other fields are added to the
+    /// data. Fields missing from the line are taken from the defaults. Lines that are not JSON objects
+    /// are sent as text.
+    pub fn json(&self, line: &str) -> Entry {
+        let mut fields = match serde_json::from_str(line) {
+            Ok(Value::Object(fields)) => fields,
+            _ => return self.text(line.to_string()),
+        };
+        let message = ["log", "message", "msg"].iter().find_map(|key| fields.remove(*key))
+            .map(|message| match message {
+                Value::String(message) => message,
+                other => other.to_string(),
+            })
+            .unwrap_or_else(|| line.to_string());
+        let level = ["severity", "level"].iter().find_map(|key| fields.remove(*key))
+            .and_then(|level| level.as_str().and_then(parse_level))
+            .unwrap_or(self.level);
+        let log_type = match fields.remove("type") {
+            Some(Value::String(log_type)) => Some(log_type),
+            _ => self.log_type.clone(),
+        };
+        let tags = merge(self.tags.as_ref(), fields.remove("tags"));
+        let mut data = match fields.remove("data") {
+            Some(Value::Object(data)) => data,
+            _ => Map::new(),
+        };
+        data.extend(fields);
+        let data = merge(self.data.as_ref(), (!data.is_empty()).then_some(Value::Object(data)));
+        Entry { level, message, log_type, data, tags }
+    }
+}
+
+/// The default object with the line's fields on top.
+fn merge(default: Option<&Value>, line: Option<Value>) -> Option<Value> {
+    match (default, line) {
+        (Some(Value::Object(default)), Some(Value::Object(line))) => {
+            let mut merged = default.clone();
+            merged.extend(line);
+            Some(Value::Object(merged))
+        },
+        (_, Some(line @ Value::Object(_))) => Some(line),
+        (default, _) => default.cloned(),
+    }
+}
+
+pub fn parse_level(level: &str) -> Option<Level> {
+    match level.to_ascii_lowercase().as_str() {
+        "fatal" | "critical" | "error" => Some(Level::Error),
+        "warning" | "warn" => Some(Level::Warn),
+        "info" | "notice" => Some(Level::Info),
+        "debug" => Some(Level::Debug),
+        "trace" => Some(Level::Trace),
+        _ => None,
+    }
+}
+
+/// Hands records to a logger and keeps track of whether all of them were delivered.
+pub struct Sender {
+    logger: POGRLogger,
+    timeout: Duration,
+    timed_out: bool,
+}
+
+impl Sender {
+    pub fn new(logger: POGRLogger, timeout: Duration) -> Self {
+        Sender { logger, timeout, timed_out: false }
+    }
+
+    /// Starts sending a record in the background.
+    pub fn send(&self, entry: &Entry) {
+        let data = entry.data.as_ref().map(KvValue::from_serde);
+        let tags = entry.tags.as_ref().map(KvValue::from_serde);
+        let mut fields: Vec<(&str, KvValue)> = Vec::new();
+        if let Some(log_type) = &entry.log_type {
+            fields.push(("pogr.type", KvValue::from(log_type.as_str())));
+        }
+        if let Some(data) = data {
+            fields.push(("pogr.data", data));
+        }
+        if let Some(tags) = tags {
+            fields.push(("pogr.tags", tags));
+        }
+        self.logger.log(&Record::builder()
+            .args(format_args!("{}", entry.message))
+            .level(entry.level)
+            .target("pogr_log")
+            .key_values(&fields)
+            .build());
+    }
+
+    /// Waits for the records sent so far.
+    pub fn flush(&mut self) {
+        if !self.logger.flush_timeout(self.timeout) {
+            self.timed_out = true;
+        }
+    }
+
+    /// Waits for the remaining records and tells whether everything was delivered.
+    pub fn finish(mut self) -> Outcome {
+        self.flush();
+        let stats = self.logger.stats();
+        if stats.filtered > 0 {
+            eprintln!("warning: {} records below info level were not sent", stats.filtered);
+        }
+        if self.timed_out {
+            eprintln!("error: records were still being sent after {:?}", self.timeout);
+        }
+        if stats.failed > 0 {
+            eprintln!("error: {} of {} records were not delivered", stats.failed, stats.accepted);
+        }
+        if self.timed_out || stats.failed > 0 {
+            Outcome::Undelivered
+        } else {
+            Outcome::Delivered
+        }
+    }
+}
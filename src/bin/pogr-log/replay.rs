@@ -0,0 +1,81 @@
+//! `pogr-log replay`: sends the records of NDJSON dumps again, unchanged, at a limited rate.
+
+use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::args::Args;
+use crate::connection::{self, Connection};
+use crate::dump::Dumps;
+use crate::filter::{self, Filter};
+use crate::{Failure, Outcome};
+
+pub fn usage() -> String {
+    format!("\
+Usage: pogr-log replay [OPTIONS] [FILE]...
+
+Sends the records of NDJSON dumps, such as spool files, that pass the filters. Each record is sent
+exactly as it was dumped, timestamp included, one at a time. Reads stdin without FILE or for `-`.
+
+Options:
+  --rate <N>     At most N records per second; 0 for no limit [default: 50]
+  --dry-run      Only count the records that would be sent
+
+{}
+
+{}", filter::USAGE, connection::USAGE)
+}
+
+pub fn run(mut args: Args) -> Result<Outcome, Failure> {
+    let mut connection = Connection::default();
+    let mut filter = Filter::default();
+    let mut rate: u32 = 50;
+    let mut dry_run = false;
+    let mut paths = Vec::new();
+    while let Some(arg) = args.next() {
+        if connection.parse(&arg, &mut args)? || filter.parse(&arg, &mut args)? {
+            continue;
+        }
+        match arg.as_str() {
+            "--rate" => rate = args.parse(&arg)?,
+            "--dry-run" => dry_run = true,
+            path if path == "-" || !path.starts_with('-') => paths.push(PathBuf::from(path)),
+            other => return Err(Failure::Usage(format!("unexpected option: {}", other))),
+        }
+    }
+
+    let dumps = Dumps::new(paths);
+    if dry_run {
+        let mut matching = 0;
+        dumps.read(|record| {
+            if filter.matches(&record) {
+                matching += 1;
+            }
+            true
+        })?;
+        println!("{} records would be replayed", matching);
+        return Ok(Outcome::Delivered);
+    }
+
+    let logger = connection.connect()?;
+    let interval = (rate > 0).then(|| Duration::from_secs(1) / rate);
+    let started = Instant::now();
+    let (mut sent, mut failed) = (0u32, 0u32);
+    dumps.read(|record| {
+        if !filter.matches(&record) {
+            return true;
+        }
+        if let Some(interval) = interval {
+            let due = started + interval * (sent + failed);
+            thread::sleep(due.saturating_duration_since(Instant::now()));
+        }
+        // Failures are printed by the logger's delivery error hook.
+        match logger.replay(record, connection.timeout()) {
+            Ok(()) => sent += 1,
+            Err(_) => failed += 1,
+        }
+        true
+    })?;
+    eprintln!("replayed {} of {} records", sent, sent + failed);
+    Ok(if failed == 0 { Outcome::Delivered } else { Outcome::Undelivered })
+}
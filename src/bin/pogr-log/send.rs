@@ -0,0 +1,41 @@
+//! `pogr-log send`: sends one record and waits for it to be delivered.
+
+use crate::args::Args;
+use crate::connection::{self, Connection};
+use crate::record::{self, Defaults, Sender};
+use crate::{Failure, Outcome};
+
+pub fn usage() -> String {
+    format!("\
+Usage: pogr-log send [OPTIONS] <MESSAGE>...
+
+Sends one record; the words of MESSAGE are joined with spaces.
+
+Example: pogr-log send --level warn --type deploy --data '{{\"version\": \"1.4.2\"}}' Deploy started
+
+{}
+
+{}", record::USAGE, connection::USAGE)
+}
+
+pub fn run(mut args: Args) -> Result<Outcome, Failure> {
+    let mut connection = Connection::default();
+    let mut defaults = Defaults::default();
+    let mut words = Vec::new();
+    while let Some(arg) = args.next() {
+        if connection.parse(&arg, &mut args)? || defaults.parse(&arg, &mut args)? {
+            continue;
+        }
+        if arg.starts_with("--") {
+            return Err(Failure::Usage(format!("unexpected option: {}", arg)));
+        }
+        words.push(arg);
+    }
+    if words.is_empty() {
+        return Err(Failure::Usage("a message is required".to_string()));
+    }
+
+    let sender = Sender::new(connection.connect()?, connection.timeout());
+    sender.send(&defaults.text(words.join(" ")));
+    Ok(sender.finish())
+}
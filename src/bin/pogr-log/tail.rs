@@ -0,0 +1,188 @@
+//! `pogr-log tail`: sends each line of stdin or a file as a record, waiting for delivery every so
+//! many records so that a fast producer cannot pile up unsent ones.
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Seek, SeekFrom};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, RecvTimeoutError, SyncSender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::args::Args;
+use crate::connection::{self, Connection};
+use crate::record::{self, Defaults, Sender};
+use crate::{Failure, Outcome};
+
+/// How often a followed file is checked for new lines.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+pub fn usage() -> String {
+    format!("\
+Usage: pogr-log tail [OPTIONS] [FILE]
+
+Sends each line of FILE, or of stdin without FILE or with `-`, as a record of its own. Empty lines
+are skipped.
+
+Options:
+  --format <FORMAT>           text: each line is a message; json: each line is a JSON object with
+                              log/message, severity/level, type, data and tags fields, other fields
+                              going into data [default: text]
+  --flush-every <N>           Wait for delivery after this many records [default: 100]
+  --flush-interval-ms <MS>    ... or once the oldest unconfirmed record is this old [default: 1000]
+  -f, --follow                Keep reading lines appended to FILE until interrupted
+
+{}
+
+Record options apply to every line; fields of JSON lines take precedence.
+
+{}", record::USAGE, connection::USAGE)
+}
+
+enum Format {
+    Text,
+    Json,
+}
+
+pub fn run(mut args: Args) -> Result<Outcome, Failure> {
+    let mut connection = Connection::default();
+    let mut defaults = Defaults::default();
+    let mut format = Format::Text;
+    let mut flush_every: usize = 100;
+    let mut flush_interval = Duration::from_millis(1000);
+    let mut follow = false;
+    let mut path = None;
+    while let Some(arg) = args.next() {
+        if connection.parse(&arg, &mut args)? || defaults.parse(&arg, &mut args)? {
+            continue;
+        }
+        match arg.as_str() {
+            "--format" => format = match args.value(&arg)?.as_str() {
+                "text" => Format::Text,
+                "json" => Format::Json,
+                other => return Err(Failure::Usage(format!("unknown format: {}", other))),
+            },
+            "--flush-every" => flush_every = args.parse::<usize>(&arg)?.max(1),
+            "--flush-interval-ms" => flush_interval = Duration::from_millis(args.parse(&arg)?),
+            "-f" | "--follow" => follow = true,
+            "-" if path.is_none() => path = Some(None),
+            file if !file.starts_with('-') && path.is_none() => path = Some(Some(PathBuf::from(file))),
+            other => return Err(Failure::Usage(format!("unexpected argument: {}", other))),
+        }
+    }
+    let path = path.flatten();
+    if follow && path.is_none() {
+        return Err(Failure::Usage("--follow needs a FILE".to_string()));
+    }
+    let file = match &path {
+        Some(path) => Some(File::open(path)
+            .map_err(|e| Failure::Config(format!("cannot open {}: {}", path.display(), e)))?),
+        None => None,
+    };
+
+    let mut sender = Sender::new(connection.connect()?, connection.timeout());
+    let (lines_in, lines) = mpsc::sync_channel(flush_every);
+    thread::spawn(move || {
+        let result = match file {
+            Some(file) if follow => follow_file(file, &lines_in),
+            Some(file) => read_lines(BufReader::new(file), &lines_in),
+            None => read_lines(io::stdin().lock(), &lines_in),
+        };
+        if let Err(e) = result {
+            let _ = lines_in.send(Err(e));
+        }
+    });
+
+    // Records sent since the last flush, and when the first of them was.
+    let mut unflushed = 0;
+    let mut unflushed_since: Option<Instant> = None;
+    let mut read_error = None;
+    loop {
+        let received = match unflushed_since {
+            Some(since) => lines.recv_timeout(flush_interval.saturating_sub(since.elapsed())),
+            None => lines.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+        match received {
+            Ok(Ok(line)) => {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let entry = match format {
+                    Format::Text => defaults.text(line),
+                    Format::Json => defaults.json(&line),
+                };
+                sender.send(&entry);
+                unflushed += 1;
+                unflushed_since.get_or_insert_with(Instant::now);
+                if unflushed >= flush_every {
+                    sender.flush();
+                    (unflushed, unflushed_since) = (0, None);
+                }
+            },
+            Ok(Err(e)) => {
+                read_error = Some(e);
+                break;
+            },
+            Err(RecvTimeoutError::Timeout) => {
+                sender.flush();
+                (unflushed, unflushed_since) = (0, None);
+            },
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+
+    let outcome = sender.finish();
+    match read_error {
+        Some(e) => Err(Failure::Config(format!("cannot read input: {}", e))),
+        None => Ok(outcome),
+    }
+}
+
+/// Reads one line, without its line ending, replacing invalid UTF-8. `None` at the end of the input.
+fn read_line(reader: &mut impl BufRead, buffer: &mut Vec<u8>) -> io::Result<Option<String>> {
+    buffer.clear();
+    if reader.read_until(b'\n', buffer)? == 0 {
+        return Ok(None);
+    }
+    Ok(Some(line_text(buffer)))
+}
+
+fn line_text(bytes: &[u8]) -> String {
+    let bytes = bytes.strip_suffix(b"\n").unwrap_or(bytes);
+    let bytes = bytes.strip_suffix(b"\r").unwrap_or(bytes);
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+fn read_lines(mut reader: impl BufRead, lines: &SyncSender<io::Result<String>>) -> io::Result<()> {
+    let mut buffer = Vec::new();
+    while let Some(line) = read_line(&mut reader, &mut buffer)? {
+        if lines.send(Ok(line)).is_err() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Reads the file's lines, then waits for more. Starts over when the file is truncated, as log
+/// rotation by copy-and-truncate does.
+fn follow_file(file: File, lines: &SyncSender<io::Result<String>>) -> io::Result<()> {
+    let mut reader = BufReader::new(file);
+    // A line still being written is kept until its line ending arrives.
+    let mut partial = Vec::new();
+    loop {
+        let read = reader.read_until(b'\n', &mut partial)?;
+        if read > 0 && partial.ends_with(b"\n") {
+            if lines.send(Ok(line_text(&partial))).is_err() {
+                return Ok(());
+            }
+            partial.clear();
+            continue;
+        }
+        let position = reader.stream_position()?;
+        if reader.get_ref().metadata()?.len() < position {
+            reader.seek(SeekFrom::Start(0))?;
+            partial.clear();
+            continue;
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
@@ -0,0 +1,93 @@
+//! Conversions between RFC 3339 times and the milliseconds since the Unix epoch used in spool files.
+
+/// Parses `2026-10-18`, `2026-10-18T12:30:00Z`, `2026-10-18T12:30:00.250+02:00` or a number of
+/// milliseconds since the epoch.
+pub fn parse(text: &str) -> Option<i64> {
+    if let Ok(millis) = text.parse::<i64>() {
+        return Some(millis);
+    }
+    let (date, time) = match text.split_once(['T', 't', ' ']) {
+        Some((date, time)) => (date, Some(time)),
+        None => (text, None),
+    };
+    let mut parts = date.splitn(3, '-');
+    let year: i64 = parts.next()?.parse().ok()?;
+    let month: u32 = parts.next()?.parse().ok()?;
+    let day: u32 = parts.next()?.parse().ok()?;
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    let mut millis = days_from_civil(year, month, day) * 86_400_000;
+    if let Some(time) = time {
+        let (clock, offset) = split_offset(time)?;
+        let (hms, fraction) = clock.split_once('.').unwrap_or((clock, ""));
+        let mut fields = hms.splitn(3, ':');
+        let hours: i64 = fields.next()?.parse().ok()?;
+        let minutes: i64 = fields.next()?.parse().ok()?;
+        let seconds: i64 = fields.next().unwrap_or("0").parse().ok()?;
+        if hours > 23 || minutes > 59 || seconds > 60 {
+            return None;
+        }
+        let fraction_millis = match fraction {
+            "" => 0,
+            digits if digits.bytes().all(|b| b.is_ascii_digit()) => format!("{:0<3}", &digits[..digits.len().min(3)]).parse().ok()?,
+            _ => return None,
+        };
+        millis += ((hours * 60 + minutes) * 60 + seconds) * 1000 + fraction_millis - offset;
+    }
+    Some(millis)
+}
+
+/// Splits the UTC offset off a time of day, returning it in milliseconds. No offset means UTC.
+fn split_offset(time: &str) -> Option<(&str, i64)> {
+    if let Some(clock) = time.strip_suffix(['Z', 'z']) {
+        return Some((clock, 0));
+    }
+    match time.rfind(['+', '-']) {
+        Some(at) => {
+            let (clock, offset) = time.split_at(at);
+            let sign = if offset.starts_with('-') { -1 } else { 1 };
+            let (hours, minutes) = offset[1..].split_once(':').unwrap_or((&offset[1..], "0"));
+            let minutes = hours.parse::<i64>().ok()? * 60 + minutes.parse::<i64>().ok()?;
+            Some((clock, sign * minutes * 60_000))
+        },
+        None => Some((time, 0)),
+    }
+}
+
+/// Formats milliseconds since the epoch as `2026-10-18T12:30:00.250Z`.
+pub fn format(millis: i64) -> String {
+    let days = millis.div_euclid(86_400_000);
+    let of_day = millis.rem_euclid(86_400_000);
+    let (year, month, day) = civil_from_days(days);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year, month, day,
+        of_day / 3_600_000, of_day / 60_000 % 60, of_day / 1000 % 60, of_day % 1000,
+    )
+}
+
+// The two conversions below are Howard Hinnant's `days_from_civil` and `civil_from_days`.
+
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month = month as i64;
+    let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
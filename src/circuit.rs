@@ -0,0 +1,331 @@
+//! Circuit breaker around the intake endpoint.
+//!
+//! After `failure_threshold` consecutive failed sends (connection errors, timeouts, `429` and `5xx`
+//! answers), the circuit opens: records are kept in a bounded in-memory buffer instead of being sent,
+//! so an unreachable intake no longer costs a full request per record. After `open_duration`, the
+//! oldest buffered record (or the next one logged) is sent as a probe. If it is delivered, the
+//! circuit closes and the buffer is replayed; otherwise it opens again for another `open_duration`.
+//!
+//! Transitions are counted in `CircuitMetrics` and reported locally, never to the intake: to the
+//! logger's error callback as `LogError::Circuit`, or on stderr.
+//!
+//! # Examples
+//! ```
+//! use pogr_log_rs::circuit::CircuitBreakerConfig;
+//! use std::time::Duration;
+//!
+//! let config = CircuitBreakerConfig::new()
+//!     .failure_threshold(3)
+//!     .open_duration(Duration::from_secs(10))
+//!     .buffer_capacity(500);
+//! ```
+
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Settings of the circuit breaker, see `POGRLogger::set_circuit_breaker`.
+#[derive(Clone, Debug)]
+pub struct CircuitBreakerConfig {
+    failure_threshold: u32,
+    open_duration: Duration,
+    buffer_capacity: usize,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        CircuitBreakerConfig {
+            failure_threshold: 5,
+            open_duration: Duration::from_secs(30),
+            buffer_capacity: 1000,
+        }
+    }
+}
+
+impl CircuitBreakerConfig {
+    /// Opens after 5 consecutive failures, probes every 30 seconds and buffers up to 1000 records.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of consecutive failed sends that opens the circuit. At least 1.
+    pub fn failure_threshold(mut self, failures: u32) -> Self {
+        self.failure_threshold = failures.max(1);
+        self
+    }
+
+    /// How long the circuit stays open before a probe is sent.
+    pub fn open_duration(mut self, duration: Duration) -> Self {
+        self.open_duration = duration;
+        self
+    }
+
+    /// Maximum number of records kept while the circuit is open. When full, the oldest record is dropped.
+    pub fn buffer_capacity(mut self, records: usize) -> Self {
+        self.buffer_capacity = records;
+        self
+    }
+}
+
+/// State of the circuit breaker.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Records are sent as usual.
+    #[default]
+    Closed,
+    /// The intake is considered down; records are buffered.
+    Open,
+    /// A probe is being sent; other records are buffered until it completes.
+    HalfOpen,
+}
+
+impl fmt::Display for CircuitState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CircuitState::Closed => write!(f, "closed"),
+            CircuitState::Open => write!(f, "open"),
+            CircuitState::HalfOpen => write!(f, "half-open"),
+        }
+    }
+}
+
+/// A snapshot of the circuit breaker's state and counters, see `POGRLogger::circuit_metrics`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CircuitMetrics {
+    pub state: CircuitState,
+    /// Failed sends since the last successful one.
+    pub consecutive_failures: u32,
+    /// Times the circuit opened, including after failed probes.
+    pub opened: u64,
+    /// Probes sent.
+    pub half_opened: u64,
+    /// Times the circuit closed after a successful probe.
+    pub closed: u64,
+    /// Records currently buffered.
+    pub buffered: usize,
+    /// Records dropped because the buffer was full.
+    pub dropped: u64,
+    /// Buffered records sent again after the circuit closed.
+    pub replayed: u64,
+}
+
+/// A change of the circuit breaker's state, reported as `LogError::Circuit`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CircuitTransition {
+    pub from: CircuitState,
+    pub to: CircuitState,
+    /// Failed sends since the last successful one, at the time of the transition.
+    pub consecutive_failures: u32,
+    /// Records buffered at the time of the transition; when closing, the records about to be replayed.
+    pub buffered: usize,
+}
+
+impl fmt::Display for CircuitTransition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.to {
+            CircuitState::Open => write!(
+                f, "intake circuit breaker open after {} consecutive failures; buffering records ({} buffered)",
+                self.consecutive_failures, self.buffered
+            ),
+            CircuitState::HalfOpen => write!(f, "intake circuit breaker half-open; probing the intake"),
+            CircuitState::Closed => write!(
+                f, "intake circuit breaker closed; replaying {} buffered records", self.buffered
+            ),
+        }
+    }
+}
+
+/// Whether a record may be sent right away.
+pub(crate) enum Admission {
+    /// Send the record. `probe` is set for the single record sent while half-open.
+    Send { payload: Value, probe: bool, transition: Option<CircuitTransition> },
+    /// The record was buffered; `evicted` is the record dropped to make room, if the buffer was full.
+    Buffered { evicted: Option<Value> },
+}
+
+/// What became of a record whose send failed.
+pub(crate) enum Failure {
+    /// The record was buffered, to be sent again. `transition` is set if the circuit opened, and
+    /// `evicted` is the record dropped to make room, if the buffer was full.
+    Buffered { transition: Option<CircuitTransition>, evicted: Option<Value> },
+    /// The circuit is still closed, so the record is not kept.
+    Rejected { payload: Value },
+}
+
+struct Inner {
+    state: CircuitState,
+    opened_at: Instant,
+    /// Whether the probe of the current half-open period has been sent.
+    probing: bool,
+    buffer: VecDeque<Value>,
+    metrics: CircuitMetrics,
+}
+
+/// Tracks the intake's health and holds records back while it is down.
+pub(crate) struct CircuitBreaker {
+    config: CircuitBreakerConfig,
+    inner: Mutex<Inner>,
+}
+
+impl CircuitBreaker {
+    pub(crate) fn new(config: CircuitBreakerConfig) -> Self {
+        CircuitBreaker {
+            config,
+            inner: Mutex::new(Inner {
+                state: CircuitState::Closed,
+                opened_at: Instant::now(),
+                probing: false,
+                buffer: VecDeque::new(),
+                metrics: CircuitMetrics::default(),
+            }),
+        }
+    }
+
+    pub(crate) fn open_duration(&self) -> Duration {
+        self.config.open_duration
+    }
+
+    pub(crate) fn metrics(&self) -> CircuitMetrics {
+        let inner = self.inner.lock().unwrap();
+        CircuitMetrics {
+            state: inner.state,
+            buffered: inner.buffer.len(),
+            ..inner.metrics.clone()
+        }
+    }
+
+    /// Decides whether `payload` is sent or buffered. An open circuit whose `open_duration` has passed
+    /// turns half-open and lets this record through as the probe.
+    pub(crate) fn admit(&self, payload: Value) -> Admission {
+        let mut inner = self.inner.lock().unwrap();
+        match inner.state {
+            CircuitState::Closed => Admission::Send { payload, probe: false, transition: None },
+            CircuitState::Open if inner.opened_at.elapsed() >= self.config.open_duration => {
+                let transition = self.half_open(&mut inner);
+                inner.probing = true;
+                Admission::Send { payload, probe: true, transition: Some(transition) }
+            },
+            CircuitState::HalfOpen if !inner.probing => {
+                inner.probing = true;
+                Admission::Send { payload, probe: true, transition: None }
+            },
+            CircuitState::Open | CircuitState::HalfOpen => {
+                let evicted = self.buffer(&mut inner, payload, false);
+                Admission::Buffered { evicted }
+            },
+        }
+    }
+
+    /// Turns an open circuit whose `open_duration` has passed half-open, for the probe timer. Returns
+    /// the transition and the oldest buffered record to send as the probe; without buffered records,
+    /// the next record logged becomes the probe.
+    pub(crate) fn start_probe(&self) -> Option<(CircuitTransition, Option<Value>)> {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.state != CircuitState::Open || inner.opened_at.elapsed() < self.config.open_duration {
+            return None;
+        }
+        let transition = self.half_open(&mut inner);
+        let probe = inner.buffer.pop_front();
+        inner.probing = probe.is_some();
+        Some((transition, probe))
+    }
+
+    /// Records a delivered send. A successful probe closes the circuit; the returned records are to
+    /// be sent again.
+    pub(crate) fn on_success(&self, probe: bool) -> Option<(CircuitTransition, Vec<Value>)> {
+        let mut inner = self.inner.lock().unwrap();
+        match inner.state {
+            CircuitState::Closed => {
+                inner.metrics.consecutive_failures = 0;
+                None
+            },
+            CircuitState::HalfOpen if probe => {
+                let replay: Vec<Value> = inner.buffer.drain(..).collect();
+                let transition = CircuitTransition {
+                    from: CircuitState::HalfOpen,
+                    to: CircuitState::Closed,
+                    consecutive_failures: inner.metrics.consecutive_failures,
+                    buffered: replay.len(),
+                };
+                inner.state = CircuitState::Closed;
+                inner.probing = false;
+                inner.metrics.consecutive_failures = 0;
+                inner.metrics.closed += 1;
+                inner.metrics.replayed += replay.len() as u64;
+                Some((transition, replay))
+            },
+            // A send that started before the circuit opened; only the probe decides on closing.
+            CircuitState::Open | CircuitState::HalfOpen => None,
+        }
+    }
+
+    /// Records a send that failed because the intake could not be reached or answered with a server
+    /// error. The payload is buffered if the circuit is (or now becomes) open, and handed back otherwise.
+    pub(crate) fn on_failure(&self, payload: Value, probe: bool) -> Failure {
+        let mut inner = self.inner.lock().unwrap();
+        inner.metrics.consecutive_failures = inner.metrics.consecutive_failures.saturating_add(1);
+        let from = inner.state;
+        let opens = match from {
+            CircuitState::Closed => inner.metrics.consecutive_failures >= self.config.failure_threshold,
+            CircuitState::HalfOpen => probe,
+            CircuitState::Open => false,
+        };
+        if from == CircuitState::Closed && !opens {
+            return Failure::Rejected { payload };
+        }
+        // A failed probe goes back to the front so that records keep their order.
+        let evicted = self.buffer(&mut inner, payload, probe);
+        if !opens {
+            return Failure::Buffered { transition: None, evicted };
+        }
+        inner.state = CircuitState::Open;
+        inner.opened_at = Instant::now();
+        inner.probing = false;
+        inner.metrics.opened += 1;
+        let transition = CircuitTransition {
+            from,
+            to: CircuitState::Open,
+            consecutive_failures: inner.metrics.consecutive_failures,
+            buffered: inner.buffer.len(),
+        };
+        Failure::Buffered { transition: Some(transition), evicted }
+    }
+
+    /// Empties the buffer, for records that will not be sent anymore.
+    pub(crate) fn drain(&self) -> Vec<Value> {
+        self.inner.lock().unwrap().buffer.drain(..).collect()
+    }
+
+    fn half_open(&self, inner: &mut Inner) -> CircuitTransition {
+        inner.state = CircuitState::HalfOpen;
+        inner.metrics.half_opened += 1;
+        CircuitTransition {
+            from: CircuitState::Open,
+            to: CircuitState::HalfOpen,
+            consecutive_failures: inner.metrics.consecutive_failures,
+            buffered: inner.buffer.len(),
+        }
+    }
+
+    /// Buffers a record, returning the one dropped if the buffer was full.
+    fn buffer(&self, inner: &mut Inner, payload: Value, front: bool) -> Option<Value> {
+        if self.config.buffer_capacity == 0 {
+            inner.metrics.dropped += 1;
+            return Some(payload);
+        }
+        let evicted = if inner.buffer.len() >= self.config.buffer_capacity {
+            inner.metrics.dropped += 1;
+            inner.buffer.pop_front()
+        } else {
+            None
+        };
+        if front {
+            inner.buffer.push_front(payload);
+        } else {
+            inner.buffer.push_back(payload);
+        }
+        evicted
+    }
+}
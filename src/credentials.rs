@@ -0,0 +1,230 @@
+//! Credential providers consulted by the transport before every request to the intake.
+//!
+//! `LogConfig` carries credentials that are fixed for the life of the process. A `CredentialProvider`
+//! lets the credentials change underneath a running logger, which is needed for rotated secrets
+//! (e.g. Kubernetes secret mounts) or credentials fetched from a vault at runtime.
+
+use reqwest::RequestBuilder;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use crate::LogConfig;
+
+/// A set of credentials accepted by the POGR intake.
+///
+/// Mirrors the two authentication methods of `LogConfig`, without the logger configuration.
+/// When read from a file, either shape is accepted as a flat JSON object.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Credentials {
+    /// Client-based authentication, sent as `POGR_CLIENT` / `POGR_BUILD` headers.
+    ClientBuild { client_id: String, build_id: String },
+    /// API key-based authentication, sent as `POGR_ACCESS` / `POGR_SECRET` headers.
+    AccessKeys { access_key: String, secret_key: String },
+}
+
+impl Credentials {
+    /// Sets the authentication headers for these credentials on an outgoing request.
+    pub fn apply(&self, req: RequestBuilder) -> RequestBuilder {
+        match self {
+            Credentials::ClientBuild { client_id, build_id } => {
+                req.header("POGR_CLIENT", client_id)
+                    .header("POGR_BUILD", build_id)
+            },
+            Credentials::AccessKeys { access_key, secret_key } => {
+                req.header("POGR_ACCESS", access_key)
+                    .header("POGR_SECRET", secret_key)
+            },
+        }
+    }
+}
+
+impl From<&LogConfig> for Credentials {
+    fn from(config: &LogConfig) -> Self {
+        match config {
+            LogConfig::ClientBuild { client_id, build_id, .. } => Credentials::ClientBuild {
+                client_id: client_id.clone(),
+                build_id: build_id.clone(),
+            },
+            LogConfig::AccessKeys { access_key, secret_key, .. } => Credentials::AccessKeys {
+                access_key: access_key.clone(),
+                secret_key: secret_key.clone(),
+            },
+        }
+    }
+}
+
+/// Errors raised while resolving credentials.
+#[derive(Debug)]
+pub enum CredentialError {
+    /// No credentials are available from the provider (e.g. unset environment variables).
+    Missing(String),
+    /// The credentials file could not be read.
+    Io(std::io::Error),
+    /// The credentials file could not be parsed.
+    Parse(serde_json::Error),
+}
+
+impl fmt::Display for CredentialError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CredentialError::Missing(what) => write!(f, "credentials not available: {}", what),
+            CredentialError::Io(e) => write!(f, "failed to read credentials: {}", e),
+            CredentialError::Parse(e) => write!(f, "failed to parse credentials: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for CredentialError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CredentialError::Missing(_) => None,
+            CredentialError::Io(e) => Some(e),
+            CredentialError::Parse(e) => Some(e),
+        }
+    }
+}
+
+/// Supplies credentials to the transport.
+///
+/// `credentials` is called before every request, so implementations should be cheap or cache
+/// internally. `refresh` is called when the intake rejects a request with `401` or `403`; providers
+/// holding a cached value should drop it so that the retried request sees fresh credentials.
+pub trait CredentialProvider: Send + Sync {
+    /// Returns the credentials to use for the next request.
+    fn credentials(&self) -> Result<Credentials, CredentialError>;
+
+    /// Invalidates any cached credentials. The default implementation does nothing.
+    fn refresh(&self) {}
+}
+
+/// A provider that always returns the same credentials. This is what `LogConfig` maps to.
+pub struct StaticCredentials {
+    credentials: Credentials,
+}
+
+impl StaticCredentials {
+    pub fn new(credentials: Credentials) -> Self {
+        StaticCredentials { credentials }
+    }
+}
+
+impl CredentialProvider for StaticCredentials {
+    fn credentials(&self) -> Result<Credentials, CredentialError> {
+        Ok(self.credentials.clone())
+    }
+}
+
+/// A provider that reads credentials from environment variables on every request.
+///
+/// With the default prefix `POGR`, it looks for `POGR_ACCESS_KEY` and `POGR_SECRET_KEY` first,
+/// then falls back to `POGR_CLIENT_ID` and `POGR_BUILD_ID`.
+pub struct EnvCredentials {
+    prefix: String,
+}
+
+impl EnvCredentials {
+    /// Creates a provider reading `<prefix>_ACCESS_KEY`, `<prefix>_SECRET_KEY`,
+    /// `<prefix>_CLIENT_ID` and `<prefix>_BUILD_ID`.
+    pub fn new(prefix: &str) -> Self {
+        EnvCredentials { prefix: prefix.to_string() }
+    }
+
+    fn var(&self, name: &str) -> Option<String> {
+        env::var(format!("{}_{}", self.prefix, name)).ok()
+    }
+}
+
+impl Default for EnvCredentials {
+    fn default() -> Self {
+        EnvCredentials::new("POGR")
+    }
+}
+
+impl CredentialProvider for EnvCredentials {
+    fn credentials(&self) -> Result<Credentials, CredentialError> {
+        if let (Some(access_key), Some(secret_key)) = (self.var("ACCESS_KEY"), self.var("SECRET_KEY")) {
+            return Ok(Credentials::AccessKeys { access_key, secret_key });
+        }
+        if let (Some(client_id), Some(build_id)) = (self.var("CLIENT_ID"), self.var("BUILD_ID")) {
+            return Ok(Credentials::ClientBuild { client_id, build_id });
+        }
+        Err(CredentialError::Missing(format!(
+            "neither {0}_ACCESS_KEY/{0}_SECRET_KEY nor {0}_CLIENT_ID/{0}_BUILD_ID is set",
+            self.prefix
+        )))
+    }
+}
+
+/// A provider that reads credentials from a JSON file and re-reads it whenever it changes.
+///
+/// The file holds either `{"access_key": "...", "secret_key": "..."}` or
+/// `{"client_id": "...", "build_id": "..."}`. The modification time is checked on every request,
+/// so a rotated Kubernetes secret mount is picked up without restarting the process.
+pub struct FileCredentials {
+    path: PathBuf,
+    cached: Mutex<Option<(SystemTime, Credentials)>>,
+}
+
+impl FileCredentials {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        FileCredentials {
+            path: path.into(),
+            cached: Mutex::new(None),
+        }
+    }
+}
+
+impl CredentialProvider for FileCredentials {
+    fn credentials(&self) -> Result<Credentials, CredentialError> {
+        let modified = fs::metadata(&self.path)
+            .and_then(|meta| meta.modified())
+            .map_err(CredentialError::Io)?;
+
+        let mut cached = self.cached.lock().unwrap();
+        if let Some((seen, credentials)) = cached.as_ref() {
+            if *seen == modified {
+                return Ok(credentials.clone());
+            }
+        }
+
+        let contents = fs::read_to_string(&self.path).map_err(CredentialError::Io)?;
+        let credentials: Credentials = serde_json::from_str(&contents).map_err(CredentialError::Parse)?;
+        *cached = Some((modified, credentials.clone()));
+        Ok(credentials)
+    }
+
+    fn refresh(&self) {
+        // Forget the cached copy so that the next request re-reads the file even if the
+        // modification time has not moved (some filesystems only have second resolution).
+        *self.cached.lock().unwrap() = None;
+    }
+}
+
+/// A provider backed by a user-supplied closure, e.g. one that queries a secrets manager.
+pub struct CallbackCredentials<F> {
+    callback: F,
+}
+
+impl<F> CallbackCredentials<F>
+where
+    F: Fn() -> Result<Credentials, CredentialError> + Send + Sync,
+{
+    pub fn new(callback: F) -> Self {
+        CallbackCredentials { callback }
+    }
+}
+
+impl<F> CredentialProvider for CallbackCredentials<F>
+where
+    F: Fn() -> Result<Credentials, CredentialError> + Send + Sync,
+{
+    fn credentials(&self) -> Result<Credentials, CredentialError> {
+        (self.callback)()
+    }
+}
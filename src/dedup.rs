@@ -0,0 +1,167 @@
+//! Collapsing of repeated identical records into a single summary.
+//!
+//! Records are keyed on (level, target, message template, type). The first record for a key is sent
+//! right away and opens a window; repeats inside the window are only counted, and each one slides the
+//! end of the window to `window` after it. Once `window` passes without a repeat, or the logger is
+//! flushed, one summary record is sent, carrying `repeat_count` (repeats after the first record),
+//! `first_seen` and `last_seen` (milliseconds since the Unix epoch). The next repeat after that opens
+//! a new window.
+//!
+//! Closing a window takes a timer on a Tokio runtime. Records logged where there is none, neither the
+//! logger's own nor the caller's, are not deduplicated. The summary is sent on the runtime the timer
+//! was set on, so a flush can close windows from any thread.
+
+use log::Level;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::runtime::Handle;
+
+use crate::epoch_millis;
+use crate::transport::Transport;
+
+/// Identifies records considered identical.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct DedupKey {
+    pub(crate) level: Level,
+    pub(crate) target: String,
+    pub(crate) template: String,
+    pub(crate) log_type: Option<String>,
+}
+
+/// An open window for one key.
+struct Window {
+    /// Tells the timer of this window apart from one left over from an earlier window for the key.
+    id: u64,
+    last_repeat: Instant,
+    first_seen: u64,
+    last_seen: u64,
+    repeats: u64,
+    /// The first record's final payload, the transport it went through and the runtime the timer was
+    /// set on, once the timer is set.
+    summary: Option<(Value, Transport, Handle)>,
+}
+
+#[derive(Default)]
+struct Windows {
+    open: HashMap<DedupKey, Window>,
+    next_id: u64,
+}
+
+/// Tracks open windows and sends the summaries when they close.
+pub(crate) struct Deduplicator {
+    window: Duration,
+    windows: Arc<Mutex<Windows>>,
+}
+
+impl Deduplicator {
+    pub(crate) fn new(window: Duration) -> Self {
+        Deduplicator {
+            window,
+            windows: Arc::new(Mutex::new(Windows::default())),
+        }
+    }
+
+    /// Returns `true` if the record opens a new window and should be sent, or `false` if it is a
+    /// repeat that has been counted towards the window's summary.
+    pub(crate) fn check(&self, key: &DedupKey) -> bool {
+        let now = epoch_millis();
+        let mut windows = self.windows.lock().unwrap();
+        if let Some(window) = windows.open.get_mut(key) {
+            window.repeats += 1;
+            window.last_seen = now;
+            window.last_repeat = Instant::now();
+            return false;
+        }
+        windows.next_id += 1;
+        let id = windows.next_id;
+        windows.open.insert(key.clone(), Window {
+            id,
+            last_repeat: Instant::now(),
+            first_seen: now,
+            last_seen: now,
+            repeats: 0,
+            summary: None,
+        });
+        true
+    }
+
+    /// Closes the window without a summary, for first records that ended up not being sent.
+    pub(crate) fn forget(&self, key: &DedupKey) {
+        self.windows.lock().unwrap().open.remove(key);
+    }
+
+    /// Sets the timer that closes the window opened by `key`, sending a summary built from the first
+    /// record's final `payload` if any repeats were counted. Without a runtime for the timer, the
+    /// window is closed right away, so that repeats are sent rather than counted towards a summary
+    /// that would never go out.
+    pub(crate) fn schedule_summary(&self, key: DedupKey, payload: Value, transport: Transport) {
+        let runtime = match transport.runtime_handle() {
+            Some(runtime) => runtime,
+            None => return self.forget(&key),
+        };
+        let id = match self.windows.lock().unwrap().open.get_mut(&key) {
+            Some(window) => {
+                window.summary = Some((payload, transport, runtime.clone()));
+                window.id
+            },
+            None => return,
+        };
+
+        let windows = self.windows.clone();
+        let length = self.window;
+        runtime.spawn(async move {
+            let closed = loop {
+                let remaining = {
+                    let mut windows = windows.lock().unwrap();
+                    let remaining = match windows.open.get(&key) {
+                        Some(window) if window.id == id => length.saturating_sub(window.last_repeat.elapsed()),
+                        // Closed by a flush, possibly reopened since.
+                        _ => return,
+                    };
+                    if remaining.is_zero() {
+                        break windows.open.remove(&key);
+                    }
+                    remaining
+                };
+                tokio::time::sleep(remaining).await;
+            };
+            if let Some(window) = closed {
+                send_summary(window);
+            }
+        });
+    }
+
+    /// Closes every open window now, sending the summaries of those with repeats.
+    pub(crate) fn flush(&self) {
+        let closed: Vec<Window> = self.windows.lock().unwrap().open.drain().map(|(_, window)| window).collect();
+        for window in closed {
+            send_summary(window);
+        }
+    }
+}
+
+/// Sends the summary record for a closed window, if anything was repeated, from a task on the runtime
+/// the window's timer was set on.
+fn send_summary(window: Window) {
+    if let Some((payload, transport, runtime)) = &window.summary {
+        if let Some(summary) = summarize(payload.clone(), &window) {
+            transport.spawn_on(runtime, summary);
+        }
+    }
+}
+
+/// Builds the summary record for a closed window, or `None` if nothing was repeated.
+fn summarize(mut payload: Value, window: &Window) -> Option<Value> {
+    if window.repeats == 0 {
+        return None;
+    }
+    if let Value::Object(map) = &mut payload {
+        map.remove("_suppressed");
+        map.insert("repeat_count".to_string(), window.repeats.into());
+        map.insert("first_seen".to_string(), window.first_seen.into());
+        map.insert("last_seen".to_string(), window.last_seen.into());
+    }
+    Some(payload)
+}
@@ -0,0 +1,199 @@
+//! Several intake endpoints, with failover and routing.
+//!
+//! Records go to the first healthy endpoint of an ordered list. When an endpoint cannot be reached,
+//! or answers `429` or `5xx`, it is marked down and the record is sent to the next one. A down
+//! endpoint is skipped for `retry_after` and then tried again; if every endpoint is down, all of them
+//! are tried in order.
+//!
+//! Routes send records of a given `type` or environment to a specific endpoint, for example to keep
+//! records in a region. A routed record only goes to the route's endpoint, and is not delivered while
+//! that endpoint is down. Routes added with `route_type_with_fallback` and
+//! `route_environment_with_fallback` try the route's endpoint first and fall back to the list.
+//!
+//! # Examples
+//! ```
+//! use pogr_log_rs::endpoints::Endpoints;
+//!
+//! let endpoints = Endpoints::new([
+//!     "https://eu.intake.example.com/v1/intake/logs",
+//!     "https://us.intake.example.com/v1/intake/logs",
+//! ])
+//! .route_type("payment", "https://us.intake.example.com/v1/intake/logs")
+//! .route_environment("staging", "https://staging.intake.example.com/v1/intake/logs");
+//! ```
+
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// The intake used when no endpoint is configured.
+pub const DEFAULT_INTAKE_URL: &str = "https://api.pogr.io/v1/intake/logs";
+
+/// Which records a route applies to.
+#[derive(Clone, Debug)]
+enum RouteMatch {
+    Type(String),
+    Environment(String),
+}
+
+#[derive(Clone, Debug)]
+struct Route {
+    matches: RouteMatch,
+    url: String,
+    /// Whether the list is tried when the route's endpoint is down.
+    fallback: bool,
+}
+
+/// An ordered list of intake endpoints and routing rules, see `POGRLogger::set_endpoints`.
+#[derive(Clone, Debug)]
+pub struct Endpoints {
+    urls: Vec<String>,
+    routes: Vec<Route>,
+    retry_after: Duration,
+}
+
+impl Endpoints {
+    /// Endpoints in order of preference. An empty list means the default intake.
+    pub fn new<I, S>(urls: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut urls: Vec<String> = urls.into_iter().map(Into::into).collect();
+        if urls.is_empty() {
+            urls.push(DEFAULT_INTAKE_URL.to_string());
+        }
+        Endpoints {
+            urls,
+            routes: Vec::new(),
+            retry_after: Duration::from_secs(30),
+        }
+    }
+
+    /// Parses a comma-separated list of URLs, as accepted in `POGR_INTAKE_URL`.
+    pub fn parse_list(list: &str) -> Self {
+        Endpoints::new(list.split(',').map(str::trim).filter(|url| !url.is_empty()))
+    }
+
+    /// Sends records of type `log_type` to `url` only, never to another endpoint.
+    pub fn route_type(self, log_type: impl Into<String>, url: impl Into<String>) -> Self {
+        self.route(RouteMatch::Type(log_type.into()), url.into(), false)
+    }
+
+    /// Sends records of type `log_type` to `url` first, and to the list while `url` is down.
+    pub fn route_type_with_fallback(self, log_type: impl Into<String>, url: impl Into<String>) -> Self {
+        self.route(RouteMatch::Type(log_type.into()), url.into(), true)
+    }
+
+    /// Sends records of the given environment to `url` only, never to another endpoint.
+    pub fn route_environment(self, environment: impl Into<String>, url: impl Into<String>) -> Self {
+        self.route(RouteMatch::Environment(environment.into()), url.into(), false)
+    }
+
+    /// Sends records of the given environment to `url` first, and to the list while `url` is down.
+    pub fn route_environment_with_fallback(self, environment: impl Into<String>, url: impl Into<String>) -> Self {
+        self.route(RouteMatch::Environment(environment.into()), url.into(), true)
+    }
+
+    fn route(mut self, matches: RouteMatch, url: String, fallback: bool) -> Self {
+        self.routes.push(Route { matches, url, fallback });
+        self
+    }
+
+    /// How long an endpoint that failed is skipped. Defaults to 30 seconds.
+    pub fn retry_after(mut self, duration: Duration) -> Self {
+        self.retry_after = duration;
+        self
+    }
+
+    /// The endpoints in order of preference.
+    pub fn urls(&self) -> &[String] {
+        &self.urls
+    }
+
+    /// The first route matching the payload, if any.
+    fn route_for(&self, payload: &Value) -> Option<&Route> {
+        self.routes.iter().find(|route| {
+            let (field, expected) = match &route.matches {
+                RouteMatch::Type(log_type) => ("type", log_type),
+                RouteMatch::Environment(environment) => ("environment", environment),
+            };
+            payload.get(field).and_then(Value::as_str) == Some(expected.as_str())
+        })
+    }
+}
+
+/// Health of one endpoint, see `POGRLogger::endpoint_status`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EndpointStatus {
+    pub url: String,
+    /// `false` while the endpoint is skipped after a failure.
+    pub healthy: bool,
+}
+
+/// The configured endpoints and the time until which each failed one is skipped.
+pub(crate) struct EndpointSet {
+    endpoints: Endpoints,
+    down_until: Mutex<HashMap<String, Instant>>,
+}
+
+impl EndpointSet {
+    pub(crate) fn new(endpoints: Endpoints) -> Self {
+        EndpointSet {
+            endpoints,
+            down_until: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The endpoints to try for a payload, in order: the routed one, then the list unless the route
+    /// has no fallback, leaving out those that are down unless nothing else is left.
+    pub(crate) fn candidates(&self, payload: &Value) -> Vec<String> {
+        let route = self.endpoints.route_for(payload);
+        let mut all: Vec<&str> = route.map(|route| route.url.as_str()).into_iter().collect();
+        if route.is_none_or(|route| route.fallback) {
+            for url in &self.endpoints.urls {
+                if !all.contains(&url.as_str()) {
+                    all.push(url);
+                }
+            }
+        }
+
+        let now = Instant::now();
+        let down_until = self.down_until.lock().unwrap();
+        let healthy: Vec<String> = all.iter()
+            .filter(|url| down_until.get(**url).is_none_or(|until| *until <= now))
+            .map(|url| url.to_string())
+            .collect();
+        if healthy.is_empty() {
+            all.into_iter().map(str::to_string).collect()
+        } else {
+            healthy
+        }
+    }
+
+    pub(crate) fn mark_down(&self, url: &str) {
+        let until = Instant::now() + self.endpoints.retry_after;
+        self.down_until.lock().unwrap().insert(url.to_string(), until);
+    }
+
+    pub(crate) fn mark_up(&self, url: &str) {
+        self.down_until.lock().unwrap().remove(url);
+    }
+
+    /// Health of the listed endpoints, in order, followed by route endpoints not in the list.
+    pub(crate) fn status(&self) -> Vec<EndpointStatus> {
+        let now = Instant::now();
+        let down_until = self.down_until.lock().unwrap();
+        let mut urls: Vec<&String> = self.endpoints.urls.iter().collect();
+        for route in &self.endpoints.routes {
+            if !urls.contains(&&route.url) {
+                urls.push(&route.url);
+            }
+        }
+        urls.into_iter().map(|url| EndpointStatus {
+            url: url.clone(),
+            healthy: down_until.get(url).is_none_or(|until| *until <= now),
+        }).collect()
+    }
+}
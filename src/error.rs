@@ -0,0 +1,41 @@
+//! Problems with individual records, and transport diagnostics, reported to the host application.
+
+use std::fmt;
+
+use crate::circuit::CircuitTransition;
+use crate::limits::OversizedRecord;
+
+/// A problem with a record that the logger handled without failing the caller.
+///
+/// Passed to the callback installed with `POGRLogger::set_error_callback`; without a callback,
+/// these are printed to stderr. Features can add variants, so matches need a wildcard arm.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum LogError {
+    /// The record exceeded the payload limits. It was truncated, or dropped if truncation could
+    /// not bring it under the total size limit.
+    Oversized(OversizedRecord),
+    /// The intake circuit breaker changed state. Only reported locally, never sent to the intake.
+    Circuit(CircuitTransition),
+    /// A delivered record could not be exported to the OpenTelemetry collector, see `otlp`.
+    #[cfg(feature = "otlp")]
+    Otlp(String),
+}
+
+impl fmt::Display for LogError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LogError::Oversized(record) if record.dropped => write!(
+                f, "dropped oversized log record ({} bytes)", record.original_bytes
+            ),
+            LogError::Oversized(record) => write!(
+                f, "truncated oversized log record ({} bytes): {}", record.original_bytes, record.truncated.join(", ")
+            ),
+            LogError::Circuit(transition) => write!(f, "{}", transition),
+            #[cfg(feature = "otlp")]
+            LogError::Otlp(e) => write!(f, "failed to export log record over OTLP: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for LogError {}
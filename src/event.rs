@@ -0,0 +1,51 @@
+//! Typed events: structs with a fixed `type`, level and tag set, logged through `structured_log!`.
+
+use log::Level;
+use serde::Serialize;
+use serde_json::Value;
+
+/// A typed log event.
+///
+/// Usually implemented with `#[derive(PogrEvent)]` (behind the `derive` feature), which takes the type,
+/// level, message and tags from a `#[pogr(...)]` attribute. The event's own fields, serialized with
+/// serde, become the record's `data`, so field names are checked at compile time.
+///
+/// # Examples
+/// ```
+/// # #[cfg(feature = "derive")] {
+/// use pogr_log_rs::PogrEvent;
+/// use serde::Serialize;
+///
+/// #[derive(Serialize, PogrEvent)]
+/// #[pogr(type = "login", level = "info", message = "User logged in", tags(env = "production"))]
+/// struct Login {
+///     user_id: u64,
+/// }
+///
+/// Login { user_id: 123 }.emit();
+/// # }
+/// ```
+pub trait PogrEvent: Serialize {
+    /// The `type` every event of this kind is logged with.
+    const TYPE: &'static str;
+    /// The level events of this kind are logged at.
+    const LEVEL: Level;
+    /// The log message.
+    const MESSAGE: &'static str;
+    /// The target events of this kind are logged with. The derive uses the module path it is
+    /// invoked in, so sampling, deduplication and `log` filters by target see the event's own module.
+    const TARGET: &'static str;
+
+    /// The tags sent with the event.
+    fn tags(&self) -> Value;
+
+    /// The event's `data`: its serde serialization, or `null` if serialization fails.
+    fn data(&self) -> Value {
+        serde_json::to_value(self).unwrap_or(Value::Null)
+    }
+
+    /// Logs the event through `structured_log!`.
+    fn emit(&self) {
+        crate::structured_log!(target: Self::TARGET, Self::LEVEL, Self::MESSAGE, Self::TYPE, self.data(), self.tags());
+    }
+}
@@ -0,0 +1,74 @@
+//! Hooks through which the host application learns about records that were not delivered.
+//!
+//! Installed with `POGRLogger::on_delivery_error`, `on_drop` and `on_auth_failure`. Hooks receive
+//! the JSON payloads the records were turned into, since `log::Record`s only live for the duration
+//! of the `log` call. They run on the logging thread (drops) or on the send task (delivery and auth
+//! failures), and should return quickly; a typical hook flips an "offline telemetry" indicator or
+//! hands the payload to a fallback sink.
+
+use serde_json::Value;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::transport::DeliveryError;
+
+/// Why a record was dropped before delivery.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DropReason {
+    /// Dropped by the sampler.
+    Sampled,
+    /// A repeat collapsed into a dedup summary.
+    Deduplicated,
+    /// Over the total size limit, even after truncation.
+    Oversized,
+    /// Evicted from the circuit breaker's buffer while it was full.
+    BufferFull,
+}
+
+impl fmt::Display for DropReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DropReason::Sampled => write!(f, "sampled"),
+            DropReason::Deduplicated => write!(f, "deduplicated"),
+            DropReason::Oversized => write!(f, "oversized"),
+            DropReason::BufferFull => write!(f, "buffer_full"),
+        }
+    }
+}
+
+pub(crate) type DeliveryErrorHook = dyn Fn(&DeliveryError, &[Value]) + Send + Sync;
+pub(crate) type DropHook = dyn Fn(DropReason, &Value) + Send + Sync;
+pub(crate) type AuthFailureHook = dyn Fn(&DeliveryError) + Send + Sync;
+
+/// The installed hooks, cloned into every send task.
+#[derive(Clone, Default)]
+pub(crate) struct Hooks {
+    pub(crate) delivery_error: Option<Arc<DeliveryErrorHook>>,
+    pub(crate) drop: Option<Arc<DropHook>>,
+    pub(crate) auth_failure: Option<Arc<AuthFailureHook>>,
+}
+
+impl Hooks {
+    /// Reports a failed delivery, and an authentication failure if that is what it was.
+    pub(crate) fn delivery_failed(&self, error: &DeliveryError, payloads: &[Value]) {
+        if let Some(hook) = &self.delivery_error {
+            hook(error, payloads);
+        }
+        if error.is_auth_failure() {
+            if let Some(hook) = &self.auth_failure {
+                hook(error);
+            }
+        }
+    }
+
+    pub(crate) fn dropped(&self, reason: DropReason, payload: &Value) {
+        if let Some(hook) = &self.drop {
+            hook(reason, payload);
+        }
+    }
+
+    /// Whether delivery errors go to a hook rather than stderr.
+    pub(crate) fn handles_delivery_errors(&self) -> bool {
+        self.delivery_error.is_some()
+    }
+}
@@ -0,0 +1,223 @@
+//! Connection settings for the client the logger uses to reach the intake: timeouts, proxy,
+//! connection pool, HTTP/2 and TCP keepalive.
+//!
+//! `HttpConfig` can be built in code, read from `POGR_HTTP_*` environment variables with `from_env`,
+//! or deserialized from a configuration file. Durations are given in milliseconds there, `null`
+//! disables a timeout, and missing fields keep their defaults, e.g.
+//! `{"connect_timeout_ms": 2000, "proxy": "socks5://10.0.0.1:1080"}`.
+
+use reqwest::{Client, ClientBuilder, NoProxy, Proxy};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fmt;
+use std::time::Duration;
+
+use crate::tls::{TlsConfig, TlsError};
+
+/// Errors raised while building the HTTP client or reading its settings.
+#[derive(Debug)]
+pub enum ClientConfigError {
+    /// An environment variable holds a value that cannot be parsed.
+    Env { var: String, value: String },
+    /// The proxy URL is not valid, or uses a scheme this build does not support.
+    Proxy(reqwest::Error),
+    /// The TLS settings could not be applied.
+    Tls(TlsError),
+    /// The client could not be built.
+    Build(reqwest::Error),
+}
+
+impl fmt::Display for ClientConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientConfigError::Env { var, value } => write!(f, "invalid value for {}: {:?}", var, value),
+            ClientConfigError::Proxy(e) => write!(f, "invalid proxy: {}", e),
+            ClientConfigError::Tls(e) => write!(f, "{}", e),
+            ClientConfigError::Build(e) => write!(f, "failed to build HTTP client: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ClientConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ClientConfigError::Env { .. } => None,
+            ClientConfigError::Proxy(e) => Some(e),
+            ClientConfigError::Tls(e) => Some(e),
+            ClientConfigError::Build(e) => Some(e),
+        }
+    }
+}
+
+impl From<TlsError> for ClientConfigError {
+    fn from(e: TlsError) -> Self {
+        ClientConfigError::Tls(e)
+    }
+}
+
+/// Connection settings for the intake client. `None` leaves reqwest's default in place, which for
+/// the timeouts means no limit at all.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HttpConfig {
+    /// Limit for establishing a connection, including the TLS handshake.
+    #[serde(rename = "connect_timeout_ms", with = "millis")]
+    pub connect_timeout: Option<Duration>,
+    /// Limit for a whole request, from connecting until the response has been read.
+    #[serde(rename = "request_timeout_ms", with = "millis")]
+    pub request_timeout: Option<Duration>,
+    /// Proxy for all intake traffic: `http://`, `https://` or (with the `socks` feature) `socks5://`.
+    /// Credentials can be given in the URL.
+    pub proxy: Option<String>,
+    /// Hosts that bypass `proxy`, in the `NO_PROXY` format (e.g. `localhost,.internal`).
+    pub no_proxy: Option<String>,
+    /// Whether `HTTP_PROXY`, `HTTPS_PROXY`, `ALL_PROXY` and `NO_PROXY` are honored when no `proxy` is set.
+    pub system_proxy: bool,
+    /// How long an idle pooled connection is kept open.
+    #[serde(rename = "pool_idle_timeout_ms", with = "millis")]
+    pub pool_idle_timeout: Option<Duration>,
+    /// Maximum number of idle connections kept per host.
+    pub pool_max_idle_per_host: Option<usize>,
+    /// Speak HTTP/2 without negotiating it first, for intakes (or proxies) that only accept HTTP/2.
+    pub http2_prior_knowledge: bool,
+    /// Interval of TCP keepalive probes on idle connections.
+    #[serde(rename = "tcp_keepalive_ms", with = "millis")]
+    pub tcp_keepalive: Option<Duration>,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        HttpConfig {
+            connect_timeout: Some(Duration::from_secs(10)),
+            request_timeout: Some(Duration::from_secs(30)),
+            proxy: None,
+            no_proxy: None,
+            system_proxy: true,
+            pool_idle_timeout: None,
+            pool_max_idle_per_host: None,
+            http2_prior_knowledge: false,
+            tcp_keepalive: Some(Duration::from_secs(60)),
+        }
+    }
+}
+
+impl HttpConfig {
+    /// The defaults, overridden by any of these environment variables that are set:
+    ///
+    /// | Variable | Setting |
+    /// | --- | --- |
+    /// | `POGR_HTTP_CONNECT_TIMEOUT_MS` | `connect_timeout` |
+    /// | `POGR_HTTP_REQUEST_TIMEOUT_MS` | `request_timeout` |
+    /// | `POGR_HTTP_PROXY` | `proxy` |
+    /// | `POGR_HTTP_NO_PROXY` | `no_proxy` |
+    /// | `POGR_HTTP_SYSTEM_PROXY` | `system_proxy` (`true`/`false`) |
+    /// | `POGR_HTTP_POOL_IDLE_TIMEOUT_MS` | `pool_idle_timeout` |
+    /// | `POGR_HTTP_POOL_MAX_IDLE_PER_HOST` | `pool_max_idle_per_host` |
+    /// | `POGR_HTTP2_PRIOR_KNOWLEDGE` | `http2_prior_knowledge` (`true`/`false`) |
+    /// | `POGR_HTTP_TCP_KEEPALIVE_MS` | `tcp_keepalive` |
+    ///
+    /// A duration of `0` unsets the corresponding setting.
+    pub fn from_env() -> Result<Self, ClientConfigError> {
+        let mut config = HttpConfig::default();
+        if let Some(ms) = env_parse::<u64>("POGR_HTTP_CONNECT_TIMEOUT_MS")? {
+            config.connect_timeout = non_zero_millis(ms);
+        }
+        if let Some(ms) = env_parse::<u64>("POGR_HTTP_REQUEST_TIMEOUT_MS")? {
+            config.request_timeout = non_zero_millis(ms);
+        }
+        if let Ok(proxy) = env::var("POGR_HTTP_PROXY") {
+            config.proxy = Some(proxy);
+        }
+        if let Ok(no_proxy) = env::var("POGR_HTTP_NO_PROXY") {
+            config.no_proxy = Some(no_proxy);
+        }
+        if let Some(system_proxy) = env_parse::<bool>("POGR_HTTP_SYSTEM_PROXY")? {
+            config.system_proxy = system_proxy;
+        }
+        if let Some(ms) = env_parse::<u64>("POGR_HTTP_POOL_IDLE_TIMEOUT_MS")? {
+            config.pool_idle_timeout = non_zero_millis(ms);
+        }
+        if let Some(max) = env_parse::<usize>("POGR_HTTP_POOL_MAX_IDLE_PER_HOST")? {
+            config.pool_max_idle_per_host = Some(max);
+        }
+        if let Some(prior_knowledge) = env_parse::<bool>("POGR_HTTP2_PRIOR_KNOWLEDGE")? {
+            config.http2_prior_knowledge = prior_knowledge;
+        }
+        if let Some(ms) = env_parse::<u64>("POGR_HTTP_TCP_KEEPALIVE_MS")? {
+            config.tcp_keepalive = non_zero_millis(ms);
+        }
+        Ok(config)
+    }
+
+    /// Applies these settings to a client builder.
+    pub fn configure(&self, mut builder: ClientBuilder) -> Result<ClientBuilder, ClientConfigError> {
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(timeout) = self.request_timeout {
+            builder = builder.timeout(timeout);
+        }
+        match &self.proxy {
+            Some(url) => {
+                let proxy = Proxy::all(url.as_str()).map_err(ClientConfigError::Proxy)?;
+                let no_proxy = self.no_proxy.as_deref().and_then(NoProxy::from_string);
+                builder = builder.proxy(proxy.no_proxy(no_proxy));
+            },
+            None if !self.system_proxy => builder = builder.no_proxy(),
+            None => {},
+        }
+        if let Some(timeout) = self.pool_idle_timeout {
+            builder = builder.pool_idle_timeout(timeout);
+        }
+        if let Some(max) = self.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max);
+        }
+        if self.http2_prior_knowledge {
+            builder = builder.http2_prior_knowledge();
+        }
+        if let Some(interval) = self.tcp_keepalive {
+            builder = builder.tcp_keepalive(interval);
+        }
+        Ok(builder)
+    }
+
+    /// Builds a client with these settings and, if given, the TLS settings.
+    pub fn build_client(&self, tls: Option<&TlsConfig>) -> Result<Client, ClientConfigError> {
+        let mut builder = self.configure(Client::builder())?;
+        if let Some(tls) = tls {
+            builder = tls.configure(builder)?;
+        }
+        builder.build().map_err(ClientConfigError::Build)
+    }
+}
+
+fn env_parse<T: std::str::FromStr>(var: &str) -> Result<Option<T>, ClientConfigError> {
+    match env::var(var) {
+        Ok(value) => value.trim().parse().map(Some).map_err(|_| ClientConfigError::Env {
+            var: var.to_string(),
+            value,
+        }),
+        Err(_) => Ok(None),
+    }
+}
+
+fn non_zero_millis(ms: u64) -> Option<Duration> {
+    (ms > 0).then(|| Duration::from_millis(ms))
+}
+
+/// (De)serializes an optional duration as a number of milliseconds.
+mod millis {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(duration: &Option<Duration>, serializer: S) -> Result<S::Ok, S::Error> {
+        match duration {
+            Some(duration) => serializer.serialize_some(&(duration.as_millis() as u64)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Duration>, D::Error> {
+        Ok(Option::<u64>::deserialize(deserializer)?.map(Duration::from_millis))
+    }
+}
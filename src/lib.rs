@@ -6,51 +6,297 @@ use reqwest::Client;
 use serde::{Serialize, Deserialize};
 use std::env;
 use serde_json::Value;
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use once_cell::sync::OnceCell;
 
+#[cfg(not(any(feature = "async-tokio", feature = "blocking")))]
+compile_error!("pogr_log_rs needs a way to run its sends: enable the `async-tokio` or the `blocking` feature");
+
+pub mod circuit;
+pub mod credentials;
+mod dedup;
+pub mod endpoints;
+mod error;
+mod event;
+pub mod hooks;
+pub mod http;
+pub mod limits;
+#[cfg(feature = "mock-intake")]
+pub mod mock_intake;
+#[cfg(feature = "otlp")]
+pub mod otlp;
+mod panic;
+pub mod redaction;
+pub mod report;
+pub mod sampling;
+mod session;
+#[cfg(feature = "spool")]
+pub mod spool;
+pub mod stats;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod tls;
+#[cfg(feature = "tracing")]
+pub mod tracing_layer;
+mod transport;
+
+pub use credentials::{CredentialProvider, Credentials, CredentialError};
+pub use error::LogError;
+pub use event::PogrEvent;
+#[cfg(feature = "derive")]
+pub use pogr_log_derive::PogrEvent;
+pub use limits::PayloadLimits;
+pub use panic::{install_panic_hook, install_panic_hook_with_timeout};
+pub use session::SessionTokenConfig;
+pub use transport::DeliveryError;
+#[cfg(feature = "blocking")]
+pub use transport::BlockingMode;
+
+/// Paths used by this crate's macros and by `#[derive(PogrEvent)]`; not part of the public API.
+#[doc(hidden)]
+pub mod __private {
+    pub use log;
+    pub use log::Level;
+    pub use serde_json::{json, Value};
+}
+
+use circuit::{CircuitBreaker, CircuitBreakerConfig, CircuitMetrics};
+use credentials::StaticCredentials;
+use endpoints::{EndpointSet, EndpointStatus, Endpoints};
+use hooks::{DropReason, Hooks};
+use dedup::{DedupKey, Deduplicator};
+use redaction::Redactor;
+use report::{AsDynError, ErrorReport};
+use sampling::{Decision, SampleInput, Sampler};
+use session::SessionTokens;
+use stats::{LoggerStats, StatsCollector};
+use http::{ClientConfigError, HttpConfig};
+use tls::TlsConfig;
+use transport::{InFlight, LoggerRuntime, Transport};
+
 /// Structured logging macro for easy logging of structured data.
 ///
 /// # Parameters
+/// - `target: $target` (optional): The record's target; defaults to the calling module's path.
 /// - `$level`: The log level (e.g., `log::Level::Info`).
 /// - `$msg`: The log message as a string.
 /// - `$log_type`: A string representing the type of log (e.g., "error", "request").
-/// - `$data`: JSON serializable data associated with the log.
-/// - `$tags`: JSON serializable tags for categorizing the log.
+/// - `$data`: JSON serializable data associated with the log, or an inline `{...}` object literal.
+/// - `$tags`: JSON serializable tags for categorizing the log, or an inline `{...}` object literal.
+///
+/// Given positionally, `structured_log!(level, msg, type, data, tags)`, all four are required and the
+/// message is sent as it is, braces included.
+///
+/// To format the message, name the structured parts before it instead: `type: ..`, `data: ..` and
+/// `tags: ..`, in any order, with at least one of them given. The format string and its arguments
+/// follow, like in `format!`. The `pogr_*!` shorthands take the same form, with every part optional.
+///
+/// None of the arguments are evaluated unless the level is enabled. Levels above the `max_level_*` /
+/// `release_max_level_*` cargo features compile to nothing at all.
 ///
 /// # Examples
 /// ```
-/// structured_log!(log::Level::Info, "User logged in", "login", {"user_id": 123}, {"env": "production"});
+/// use pogr_log_rs::structured_log;
+/// use serde_json::json;
+///
+/// let user_id = 123;
+/// structured_log!(log::Level::Info, "User logged in", "login", json!({"user_id": user_id}), json!({"env": "production"}));
+/// structured_log!(log::Level::Info, "User logged in", "login", {"user_id": user_id}, {"env": "production"});
+/// structured_log!(target: "auth", log::Level::Info, type: "login", data: {"user_id": user_id}, "User {} logged in", user_id);
+/// structured_log!(log::Level::Info, type: "login", "{} logged in from {} on {}", user_id, "web", "eu-west");
 /// ```
 #[macro_export]
 macro_rules! structured_log {
-    // Define the macro with parameters for log level, message, log type, data, and tags.
-    ($level:expr, $msg:expr, $log_type:expr, $data:expr, $tags:expr) => {{
-        // Use `serde_json::json!` macro to encode the structured message as a JSON object,
-        // including the log message, type, data, and tags.
-        let structured_message = serde_json::json!({
-            "log": $msg,
-            "type": $log_type,
-            "data": $data,
-            "tags": $tags,
-        }).to_string(); // Convert the JSON object to a string for logging.
-
-        // Match the provided log level and call the corresponding logging function from the `log` crate.
-        // The `log` crate provides a macro for each log level (error, warn, info, debug, trace).
-        // The structured message is logged as a JSON-encoded string.
-        match $level {
-            log::Level::Error => log::error!("{}", structured_message),
-            log::Level::Warn => log::warn!("{}", structured_message),
-            log::Level::Info => log::info!("{}", structured_message),
-            log::Level::Debug => log::debug!("{}", structured_message),
-            log::Level::Trace => log::trace!("{}", structured_message),
-            // This catch-all arm is technically unnecessary because all possible values of `log::Level`
-            // are already covered. It's a good practice to handle the default case, but here it could
-            // lead to unexpected behaviors if the `log::Level` enum is extended in the future.
-            // It's safe to remove this arm, ensuring all log messages are handled explicitly by their level.
-            _ => log::info!("{}", structured_message),
-        }
-    }};
+    // Named parts, in any order, each followed by a comma. Each one is collected as a key-value
+    // for the `log` record; inline object literals are turned into `serde_json::Value`s.
+    (@fields [$target:expr] [$level:expr] [$($kvs:tt)*] type: $log_type:expr, $($rest:tt)+) => {
+        $crate::structured_log!(@fields [$target] [$level] [$($kvs)* ["pogr.type" = $log_type]] $($rest)+)
+    };
+    (@fields [$target:expr] [$level:expr] [$($kvs:tt)*] data: { $($data:tt)* }, $($rest:tt)+) => {
+        $crate::structured_log!(@fields [$target] [$level] [$($kvs)* ["pogr.data":serde = $crate::__private::json!({ $($data)* })]] $($rest)+)
+    };
+    (@fields [$target:expr] [$level:expr] [$($kvs:tt)*] data: { $($data:tt)* } $(,)?) => {
+        compile_error!("expected a message after `data: ..`")
+    };
+    (@fields [$target:expr] [$level:expr] [$($kvs:tt)*] data: $data:expr, $($rest:tt)+) => {
+        $crate::structured_log!(@fields [$target] [$level] [$($kvs)* ["pogr.data":serde = $data]] $($rest)+)
+    };
+    (@fields [$target:expr] [$level:expr] [$($kvs:tt)*] tags: { $($tags:tt)* }, $($rest:tt)+) => {
+        $crate::structured_log!(@fields [$target] [$level] [$($kvs)* ["pogr.tags":serde = $crate::__private::json!({ $($tags)* })]] $($rest)+)
+    };
+    (@fields [$target:expr] [$level:expr] [$($kvs:tt)*] tags: { $($tags:tt)* } $(,)?) => {
+        compile_error!("expected a message after `tags: ..`")
+    };
+    (@fields [$target:expr] [$level:expr] [$($kvs:tt)*] tags: $tags:expr, $($rest:tt)+) => {
+        $crate::structured_log!(@fields [$target] [$level] [$($kvs)* ["pogr.tags":serde = $tags]] $($rest)+)
+    };
+    (@fields [$target:expr] [$level:expr] [$($kvs:tt)*] $part:ident: $($rest:tt)*) => {
+        compile_error!(concat!("expected a message after `", stringify!($part), ": ..`"))
+    };
+    (@fields [$target:expr] [$level:expr] [$($kvs:tt)*] $format:literal, { $($object:tt)* } $($rest:tt)*) => {
+        compile_error!("inline objects are only accepted as `data: {..}` and `tags: {..}`, or as the data and tags of `structured_log!(level, msg, type, data, tags)`")
+    };
+    // What remains is the message: a format string and its arguments.
+    (@fields [$target:expr] [$level:expr] [] $($arg:tt)+) => {
+        $crate::__private::log::log!(target: $target, $level, $($arg)+)
+    };
+    (@fields [$target:expr] [$level:expr] [$([$($kv:tt)+])+] $($arg:tt)+) => {
+        // The type, data and tags travel as key-values on the `log` record, next to the plain message,
+        // so `POGRLogger` can pick them up without serializing and re-parsing them as a JSON string.
+        $crate::__private::log::log!(target: $target, $level, $($($kv)+),+; $($arg)+)
+    };
+
+    // Named parts: a format string follows them.
+    (@args [$target:expr] [$level:expr] type: $($rest:tt)+) => {
+        $crate::structured_log!(@fields [$target] [$level] [] type: $($rest)+)
+    };
+    (@args [$target:expr] [$level:expr] data: $($rest:tt)+) => {
+        $crate::structured_log!(@fields [$target] [$level] [] data: $($rest)+)
+    };
+    (@args [$target:expr] [$level:expr] tags: $($rest:tt)+) => {
+        $crate::structured_log!(@fields [$target] [$level] [] tags: $($rest)+)
+    };
+
+    // Positional parts: message, type, data and tags. The message is logged as-is, not as a format string.
+    // Inline objects where no positional rule takes them are caught before `$..:expr` is tried on
+    // them, which would fail on their `:`.
+    (@args [$target:expr] [$level:expr] $msg:expr, { $($log_type:tt)* } $($rest:tt)*) => {
+        compile_error!("expected `structured_log!(level, msg, type, data, tags)`; to format the message, name the parts: `structured_log!(level, type: .., data: .., \"{}\", arg)`")
+    };
+    (@args [$target:expr] [$level:expr] $msg:expr, $log_type:expr, { $($data:tt)* }, { $($tags:tt)* } $(,)?) => {
+        $crate::structured_log!(@fields [$target] [$level] [] type: $log_type, data: { $($data)* }, tags: { $($tags)* }, "{}", $msg)
+    };
+    (@args [$target:expr] [$level:expr] $msg:expr, $log_type:expr, { $($data:tt)* }, $tags:expr $(,)?) => {
+        $crate::structured_log!(@fields [$target] [$level] [] type: $log_type, data: { $($data)* }, tags: $tags, "{}", $msg)
+    };
+    (@args [$target:expr] [$level:expr] $msg:expr, $log_type:expr, { $($data:tt)* } $($rest:tt)*) => {
+        compile_error!("expected `structured_log!(level, msg, type, data, tags)`; to format the message, name the parts: `structured_log!(level, type: .., data: .., \"{}\", arg)`")
+    };
+    (@args [$target:expr] [$level:expr] $msg:expr, $log_type:expr, $data:expr, { $($tags:tt)* } $(,)?) => {
+        $crate::structured_log!(@fields [$target] [$level] [] type: $log_type, data: $data, tags: { $($tags)* }, "{}", $msg)
+    };
+    (@args [$target:expr] [$level:expr] $msg:expr, $log_type:expr, $data:expr, $tags:expr $(,)?) => {
+        $crate::structured_log!(@fields [$target] [$level] [] type: $log_type, data: $data, tags: $tags, "{}", $msg)
+    };
+    // Anything else would be a format string without named parts, which a message with exactly three
+    // arguments could not be told apart from.
+    (@args [$target:expr] [$level:expr] $($rest:tt)*) => {
+        compile_error!("expected `structured_log!(level, msg, type, data, tags)`; to format the message, name the parts: `structured_log!(level, type: .., data: .., \"{}\", arg)`")
+    };
+
+    (target: $target:expr, $level:expr, $($rest:tt)+) => {
+        $crate::structured_log!(@args [$target] [$level] $($rest)+)
+    };
+    ($level:expr, $($rest:tt)+) => {
+        $crate::structured_log!(@args [::core::module_path!()] [$level] $($rest)+)
+    };
+}
+
+/// Logs a structured record at the error level; shorthand for `structured_log!(log::Level::Error, ..)`.
+///
+/// Accepts `target: ..` followed by the named parts of `structured_log!` (`type: ..`, `data: ..`,
+/// `tags: ..`, all optional) and a format string with its arguments.
+#[macro_export]
+macro_rules! pogr_error {
+    (target: $target:expr, $($rest:tt)+) => {
+        $crate::structured_log!(@fields [$target] [$crate::__private::Level::Error] [] $($rest)+)
+    };
+    ($($rest:tt)+) => {
+        $crate::structured_log!(@fields [::core::module_path!()] [$crate::__private::Level::Error] [] $($rest)+)
+    };
+}
+
+/// Logs a structured record at the warn level; shorthand for `structured_log!(log::Level::Warn, ..)`.
+///
+/// Accepts `target: ..` followed by the named parts of `structured_log!` (`type: ..`, `data: ..`,
+/// `tags: ..`, all optional) and a format string with its arguments.
+#[macro_export]
+macro_rules! pogr_warn {
+    (target: $target:expr, $($rest:tt)+) => {
+        $crate::structured_log!(@fields [$target] [$crate::__private::Level::Warn] [] $($rest)+)
+    };
+    ($($rest:tt)+) => {
+        $crate::structured_log!(@fields [::core::module_path!()] [$crate::__private::Level::Warn] [] $($rest)+)
+    };
+}
+
+/// Logs a structured record at the info level; shorthand for `structured_log!(log::Level::Info, ..)`.
+///
+/// Accepts `target: ..` followed by the named parts of `structured_log!` (`type: ..`, `data: ..`,
+/// `tags: ..`, all optional) and a format string with its arguments.
+///
+/// # Examples
+/// ```
+/// use pogr_log_rs::pogr_info;
+///
+/// let user_id = 123;
+/// pogr_info!("Cache warmed in {}ms", 42);
+/// pogr_info!(type: "login", data: {"user_id": user_id}, tags: {"env": "production"}, "User {} logged in", user_id);
+/// pogr_info!(target: "auth", type: "login", "User {user_id} logged in");
+/// ```
+#[macro_export]
+macro_rules! pogr_info {
+    (target: $target:expr, $($rest:tt)+) => {
+        $crate::structured_log!(@fields [$target] [$crate::__private::Level::Info] [] $($rest)+)
+    };
+    ($($rest:tt)+) => {
+        $crate::structured_log!(@fields [::core::module_path!()] [$crate::__private::Level::Info] [] $($rest)+)
+    };
+}
+
+/// Logs a structured record at the debug level; shorthand for `structured_log!(log::Level::Debug, ..)`.
+///
+/// Accepts `target: ..` followed by the named parts of `structured_log!` (`type: ..`, `data: ..`,
+/// `tags: ..`, all optional) and a format string with its arguments.
+#[macro_export]
+macro_rules! pogr_debug {
+    (target: $target:expr, $($rest:tt)+) => {
+        $crate::structured_log!(@fields [$target] [$crate::__private::Level::Debug] [] $($rest)+)
+    };
+    ($($rest:tt)+) => {
+        $crate::structured_log!(@fields [::core::module_path!()] [$crate::__private::Level::Debug] [] $($rest)+)
+    };
+}
+
+/// Logs a structured record at the trace level; shorthand for `structured_log!(log::Level::Trace, ..)`.
+///
+/// Accepts `target: ..` followed by the named parts of `structured_log!` (`type: ..`, `data: ..`,
+/// `tags: ..`, all optional) and a format string with its arguments.
+#[macro_export]
+macro_rules! pogr_trace {
+    (target: $target:expr, $($rest:tt)+) => {
+        $crate::structured_log!(@fields [$target] [$crate::__private::Level::Trace] [] $($rest)+)
+    };
+    ($($rest:tt)+) => {
+        $crate::structured_log!(@fields [::core::module_path!()] [$crate::__private::Level::Trace] [] $($rest)+)
+    };
+}
+
+
+/// Reports an error and its `source()` chain through the global logger.
+///
+/// # Parameters
+/// - `$err`: The error to report; any value implementing `std::error::Error + 'static`.
+///   Use `*boxed` for a `Box<dyn Error>`.
+/// - `$context`: Optional JSON-like context, attached under `data.context`.
+///
+/// # Examples
+/// ```
+/// use pogr_log_rs::report_error;
+///
+/// if let Err(e) = "not a number".parse::<u32>() {
+///     report_error!(e, {"input": "not a number"});
+/// }
+/// ```
+#[macro_export]
+macro_rules! report_error {
+    ($err:expr) => {
+        $crate::report_error!($err, null)
+    };
+    ($err:expr, $($context:tt)+) => {
+        $crate::report_error(&$err, $crate::__private::json!($($context)+))
+    };
 }
 
 
@@ -64,11 +310,47 @@ pub enum LogConfig {
 }
 
 /// Configuration for the logger itself, including service and environment identifiers.
-#[derive(Clone, Serialize, Deserialize)]
+///
+/// # Examples
+/// ```
+/// use pogr_log_rs::LoggerConfig;
+///
+/// let config = LoggerConfig::new("matchmaker", "production")
+///     .default_type("matchmaking")
+///     .tag("region", "eu-west");
+/// ```
+#[derive(Clone, Default, Serialize, Deserialize)]
 pub struct LoggerConfig {
     pub service: String,
     pub environment: String,
     pub default_type: Option<String>,
+    /// Tags added to every payload, such as `region`, `shard` or `git_sha`. A record's own tags take
+    /// precedence over these; see `POGRLogger::set_tag`.
+    #[serde(default)]
+    pub tags: serde_json::Map<String, Value>,
+}
+
+impl LoggerConfig {
+    /// A configuration for `service` in `environment`, without a default type or global tags.
+    pub fn new(service: impl Into<String>, environment: impl Into<String>) -> Self {
+        LoggerConfig {
+            service: service.into(),
+            environment: environment.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Type of records logged without one, see `POGRLogger::build_payload`.
+    pub fn default_type(mut self, log_type: impl Into<String>) -> Self {
+        self.default_type = Some(log_type.into());
+        self
+    }
+
+    /// Adds a global tag, or changes its value.
+    pub fn tag(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.tags.insert(key.into(), value.into());
+        self
+    }
 }
 
 
@@ -77,11 +359,37 @@ pub struct LoggerConfig {
 /// Utilizes `reqwest` for HTTP requests, and supports structured logging through JSON serialization.
 pub struct POGRLogger {
     client: Option<Client>,
-    api_url: Option<String>,
-    auth_config: LogConfig,
+    endpoints: Arc<EndpointSet>,
     logger_config: LoggerConfig,
+    credentials: Arc<dyn CredentialProvider>,
+    session: Option<Arc<SessionTokens>>,
+    redactor: Option<Arc<Redactor>>,
+    limits: Option<PayloadLimits>,
+    sampler: Option<Sampler>,
+    dedup: Option<Deduplicator>,
+    error_callback: Option<Arc<ErrorCallback>>,
+    in_flight: Arc<InFlight>,
+    runtime: Option<Arc<LoggerRuntime>>,
+    send_timeout: Option<Duration>,
+    #[cfg(feature = "compression")]
+    compress: bool,
+    http_config: HttpConfig,
+    tls_config: Option<TlsConfig>,
+    circuit: Option<Arc<CircuitBreaker>>,
+    stats: Arc<StatsCollector>,
+    hooks: Hooks,
+    #[cfg(feature = "otlp")]
+    otlp: Option<Arc<otlp::OtlpExporter>>,
+    #[cfg(feature = "testing")]
+    capture: Option<testing::Sink>,
 }
 
+/// How long `flush` waits for records that are still being sent.
+pub const DEFAULT_FLUSH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Callback receiving problems with individual records, see `POGRLogger::set_error_callback`.
+pub(crate) type ErrorCallback = dyn Fn(&LogError) + Send + Sync;
+
 /// Implements the `Log` trait for the `POGRLogger` struct, enabling structured and asynchronous logging.
 ///
 /// `POGRLogger` is designed to send log messages as structured JSON data to a remote logging service.
@@ -104,85 +412,108 @@ impl Log for POGRLogger {
     /// * `record` - A reference to the log `Record` that contains the log message and metadata.
     ///
     /// # Behavior
-    /// - Attempts to parse the log message as a JSON string. If successful and the parsed message is a JSON object,
-    ///   it merges the JSON fields into the structured log data. Otherwise, it includes the original log message as a string.
-    /// - Prepares structured log data with default fields (`service`, `environment`, `severity`) and any fields extracted
-    ///   from the structured log message.
+    /// - Prepares structured log data with default fields (`service`, `environment`, `severity`), the log message
+    ///   under `log`, and the `type`, `data` and `tags` that `structured_log!` attaches as key-values. The message is
+    ///   never parsed, so plain messages that happen to be valid JSON are sent as they are.
     /// - Asynchronously sends the structured log data to a configured remote API endpoint, using a cloned HTTP client
     ///   and applying authentication headers based on the logger's configuration.
     fn log(&self, record: &Record) {
         // Checks if the log level of the record is enabled for this logger.
         if self.enabled(record.metadata()) {
-            // Attempts to parse the log message as structured JSON data.
-            let maybe_structured_message: Result<serde_json::Value, _> = serde_json::from_str(record.args().to_string().as_str());
-
-            // Initializes structured data with default fields: service, environment, and severity.
-            let mut structured_data = serde_json::json!({
-                "service": self.logger_config.service,
-                "environment": self.logger_config.environment,
-                "severity": record.level().to_string().to_lowercase(),
-            });
+            StatsCollector::count(&self.stats.accepted);
 
-            // If the log message is valid JSON and is an object, merge its fields into `structured_data`.
-            if let Ok(mut structured_message) = maybe_structured_message {
-                if let serde_json::Value::Object(ref mut obj) = structured_message {
-                    // Take the map out and replace it with an empty map
-                    let drained_map = std::mem::take(obj);
+            // Builds the payload from the message, the record's key-values and the default fields.
+            let mut structured_data = self.build_payload(record);
 
-                    // Iterate over all fields in the drained map and add them to `structured_data`.
-                    for (key, value) in drained_map {
-                        structured_data[&key] = value;
-                    }
+            // Drops records suppressed by the sampler, and tells the intake how many were skipped
+            // since the last record that made it through.
+            if let Some(sampler) = &self.sampler {
+                let template = message_template(record);
+                let input = SampleInput {
+                    level: record.level(),
+                    target: record.target(),
+                    template: &template,
+                    log_type: structured_data.get("type").and_then(Value::as_str),
+                };
+                match sampler.check(&input) {
+                    Decision::Drop => {
+                        StatsCollector::count(&self.stats.sampled);
+                        self.hooks.dropped(DropReason::Sampled, &structured_data);
+                        return;
+                    },
+                    Decision::Keep { suppressed: 0 } => {},
+                    Decision::Keep { suppressed } => structured_data["_suppressed"] = suppressed.into(),
                 }
-            } else {
-                // If the log message isn't structured JSON, include it as a plain string under the "log" key.
-                structured_data["log"] = serde_json::Value::String(record.args().to_string());
             }
 
+            // Collapses repeats of a record that was already sent in the current dedup window.
+            let dedup_key = self.dedup.as_ref().map(|_| DedupKey {
+                level: record.level(),
+                target: record.target().to_string(),
+                template: message_template(record),
+                log_type: structured_data.get("type").and_then(Value::as_str).map(str::to_string),
+            });
+            if let (Some(dedup), Some(key)) = (&self.dedup, &dedup_key) {
+                if !dedup.check(key) {
+                    StatsCollector::count(&self.stats.deduplicated);
+                    self.hooks.dropped(DropReason::Deduplicated, &structured_data);
+                    return;
+                }
+            }
 
-            // Clone necessary data for the asynchronous context.
-            let api_url = self.api_url.clone().expect("API URL must be set");
-            let client = self.client.clone();
-            let auth_config = self.auth_config.clone();
-
-            // Spawn an asynchronous task to send the log data to a remote API.
-            tokio::spawn(async move {
-                // Prepare the HTTP request with the structured log data as JSON.
-                let mut req = client.expect("REASON").post(&api_url).json(&structured_data);
-
-                // Set request headers based on the authentication configuration.
-                match auth_config {
-                    LogConfig::ClientBuild { client_id, build_id, .. } => {
-                        // If using client/build ID for auth, set headers accordingly.
-                        req = req.header("POGR_CLIENT", client_id)
-                                 .header("POGR_BUILD", build_id);
-                    },
-                    LogConfig::AccessKeys { access_key, secret_key, .. } => {
-                        // If using access/secret keys for auth, set headers accordingly.
-                        req = req.header("POGR_ACCESS", access_key)
-                                 .header("POGR_SECRET", secret_key);
-                    },
+            // Applies the final processing steps (redaction, size limits) before the payload leaves the process.
+            if !self.finish_payload(&mut structured_data) {
+                if let (Some(dedup), Some(key)) = (&self.dedup, &dedup_key) {
+                    dedup.forget(key);
                 }
+                return;
+            }
 
-                // Send the request. The result is ignored with `_` since we don't handle response or errors here.
-                let _ = req.send().await;
-            });
+            // Clone necessary data for the asynchronous context.
+            let client = self.client.clone().expect("HTTP client must be set");
+            let transport = self.transport(client);
+
+            // The summary of the dedup window is built from the record that opened it.
+            if let (Some(dedup), Some(key)) = (&self.dedup, dedup_key) {
+                dedup.schedule_summary(key, structured_data.clone(), transport.clone());
+            }
+
+            // Either wait for the intake to answer, or spawn an asynchronous task to send the log data.
+            match self.send_timeout {
+                Some(timeout) => {
+                    if let Err(e) = transport.send_blocking(structured_data, timeout) {
+                        self.print_delivery_error("Failed to send log data", &e);
+                    }
+                },
+                None => transport.spawn(structured_data),
+            }
+        } else {
+            StatsCollector::count(&self.stats.filtered);
         }
     }
 
-    /// Flushes buffered log records.
+    /// Waits for log records that are still being sent.
     ///
-    /// This implementation of `flush` does not perform any action because `POGRLogger` sends each log record
-    /// asynchronously upon creation, leaving no buffered records to flush. This method is required by the `Log` trait
-    /// but can be left empty in cases like this where immediate or asynchronous log handling is used.
+    /// `POGRLogger` does not buffer records, but sends each one from a background task. This blocks the
+    /// calling thread until those tasks have finished, or until `DEFAULT_FLUSH_TIMEOUT` has passed.
+    /// Use `flush_timeout` to choose a different limit.
     ///
     /// # Examples
-    /// This method would be called by the logging framework or manually to ensure that all buffered logs are
-    /// flushed to their destination, typically during application shutdown or after a critical error to ensure
-    /// all relevant information is logged. Since `POGRLogger` does not buffer logs, calling this method has no effect.
-    fn flush(&self) {}
+    /// This method would be called by the logging framework or manually to ensure that all records are
+    /// delivered, typically during application shutdown or after a critical error. Since the tasks run
+    /// on the Tokio runtime, call it from a thread that is not needed to drive them (or from a
+    /// multi-threaded runtime).
+    fn flush(&self) {
+        self.flush_timeout(DEFAULT_FLUSH_TIMEOUT);
+    }
 }
 
+impl Drop for POGRLogger {
+    /// Gives up on the records the circuit breaker still holds, reporting them to the delivery error hook.
+    fn drop(&mut self) {
+        self.abandon_buffered();
+    }
+}
 
 
 
@@ -201,32 +532,447 @@ impl POGRLogger {
     /// A new instance of `POGRLogger` configured with the specified authentication method and API URL.
     pub fn new(client: Client, api_url: Option<String>, auth_config: LogConfig, logger_config: LoggerConfig) -> Self {
         // Attempts to retrieve the API URL from an environment variable, defaults to a predefined URL if not found.
-        let api_url = if let Some(url) = api_url {
-            url
+        let endpoints = if let Some(url) = api_url {
+            Endpoints::new([url])
         } else {
-            // If `api_url` is not provided, try retrieving the URL from an environment variable.
+            // If `api_url` is not provided, try retrieving the URLs from an environment variable.
             match env::var("POGR_INTAKE_URL") {
-                Ok(url) => url, // Use environment variable if set
-                Err(_) => "https://api.pogr.io/v1/intake/logs".to_string(), // Default URL if env var is not set
+                Ok(urls) => Endpoints::parse_list(&urls), // Use environment variable if set, in order of preference
+                Err(_) => Endpoints::new([endpoints::DEFAULT_INTAKE_URL]), // Default URL if env var is not set
             }
         };
-        //println!("POGR server URL: {}", api_url); // Log the URL to the console
+        //println!("POGR server URLs: {:?}", endpoints.urls()); // Log the URL to the console
+
+        // The credentials from `auth_config` are used until a different provider is installed.
+        let credentials = Arc::new(StaticCredentials::new(Credentials::from(&auth_config)));
 
         // Constructs the `POGRLogger` instance with the resolved configurations.
         POGRLogger {
             client: Some(client), // Initializes a new HTTP client for sending requests.
-            api_url: Some(api_url), // The determined API URL for log intake.
-            auth_config, // The provided authentication configuration.
+            endpoints: Arc::new(EndpointSet::new(endpoints)), // The determined API URLs for log intake.
             logger_config, // The determined logger configuration.
+            credentials, // Consulted before every request to the intake.
+            session: None, // Session tokens are opt-in.
+            redactor: None, // Payloads are sent unredacted unless a redactor is installed.
+            limits: None, // Payload sizes are unrestricted unless limits are configured.
+            sampler: None, // Every enabled record is sent unless a sampler is installed.
+            dedup: None, // Repeated records are sent individually unless a dedup window is set.
+            error_callback: None, // Problems are printed to stderr until a callback is installed.
+            in_flight: Arc::new(InFlight::default()), // Tracks background sends for `flush`.
+            runtime: default_runtime(), // Sends run on the caller's Tokio runtime unless a blocking transport is chosen.
+            send_timeout: None, // `log` returns without waiting for delivery by default.
+            #[cfg(feature = "compression")]
+            compress: false, // Bodies are sent as plain JSON unless compression is enabled.
+            http_config: HttpConfig::default(), // Used when the client is rebuilt by `set_tls_config`.
+            tls_config: None, // Used when the client is rebuilt by `set_http_config`.
+            circuit: None, // Every record is sent, whatever happened to the previous ones, unless a circuit breaker is set.
+            stats: Arc::new(StatsCollector::default()), // Counted from the start, see `stats`.
+            hooks: Hooks::default(), // Undelivered records are only counted until hooks are installed.
+            #[cfg(feature = "otlp")]
+            otlp: None, // Records only go to the intake unless an OTLP exporter is set.
+            #[cfg(feature = "testing")]
+            capture: None, // Payloads go to the intake unless captured for tests, see `testing`.
         }
     }
 
+    /// Starts building a logger whose HTTP client is configured by the builder; see `POGRLoggerBuilder`.
+    pub fn builder(auth_config: LogConfig) -> POGRLoggerBuilder {
+        POGRLoggerBuilder::new(auth_config)
+    }
+
     pub fn set_client(&mut self, client: Client) {
         self.client = Some(client);
     }
 
+    /// Sends to a single endpoint, replacing any endpoints set before.
     pub fn set_api_url(&mut self, api_url: String) {
-        self.api_url = Some(api_url);
+        self.set_endpoints(Endpoints::new([api_url]));
+    }
+
+    /// Sends to several endpoints with failover and routing, see the `endpoints` module.
+    pub fn set_endpoints(&mut self, endpoints: Endpoints) {
+        self.endpoints = Arc::new(EndpointSet::new(endpoints));
+    }
+
+    /// Health of each endpoint, as seen by the failover logic.
+    pub fn endpoint_status(&self) -> Vec<EndpointStatus> {
+        self.endpoints.status()
+    }
+
+    /// Replaces the HTTP client with one built from `http` and the TLS settings last passed to
+    /// `set_tls_config`, if any.
+    pub fn set_http_config(&mut self, http: HttpConfig) -> Result<(), ClientConfigError> {
+        let client = http.build_client(self.tls_config.as_ref())?;
+        #[cfg(feature = "otlp")]
+        if let Some(otlp) = &self.otlp {
+            let mut exporter = otlp::OtlpExporter::clone(otlp);
+            exporter.connect(&http)?;
+            self.otlp = Some(Arc::new(exporter));
+        }
+        self.client = Some(client);
+        self.http_config = http;
+        Ok(())
+    }
+
+    /// Replaces the HTTP client with one built from `tls`: extra root certificates, a client
+    /// certificate for mutual TLS, and SPKI pins. Connection settings are the ones last passed to
+    /// `set_http_config`, or the defaults. The session-token exchange uses the same client.
+    pub fn set_tls_config(&mut self, tls: TlsConfig) -> Result<(), ClientConfigError> {
+        self.client = Some(self.http_config.build_client(Some(&tls))?);
+        self.tls_config = Some(tls);
+        Ok(())
+    }
+
+    /// Replaces the source of credentials used to authenticate with the intake.
+    ///
+    /// The provider is asked for credentials before every request, and refreshed when the intake
+    /// responds with `401` or `403`. This takes precedence over the credentials in `LogConfig`.
+    pub fn set_credential_provider(&mut self, provider: impl CredentialProvider + 'static) {
+        self.credentials = Arc::new(provider);
+    }
+
+    /// Enables the session-token flow for `ClientBuild` credentials.
+    ///
+    /// Instead of sending the client and build ids on every request, the transport exchanges them
+    /// at `config.auth_url` for a short-lived token and sends it as `Authorization: Bearer`. The token
+    /// is cached and exchanged again before it expires, or immediately if the intake rejects it.
+    /// `AccessKeys` credentials are unaffected and keep using their headers.
+    pub fn enable_session_tokens(&mut self, config: SessionTokenConfig) {
+        self.session = Some(Arc::new(SessionTokens::new(config)));
+    }
+
+    /// Switches to a transport that does not need the caller to run a Tokio runtime.
+    ///
+    /// The logger starts a small runtime of its own, on a dedicated thread, and sends every record,
+    /// dedup summary and error report from there. With `BlockingMode::SendBeforeReturning`, `log` also
+    /// waits for the intake to answer, so the final record of a CLI run is delivered before it exits.
+    /// Failures are then reported on stderr.
+    ///
+    /// # Errors
+    /// Fails if the runtime's thread cannot be started.
+    #[cfg(feature = "blocking")]
+    pub fn use_blocking_transport(&mut self, mode: BlockingMode) -> std::io::Result<()> {
+        self.runtime = Some(Arc::new(LoggerRuntime::new()?));
+        self.send_timeout = match mode {
+            BlockingMode::Background => None,
+            BlockingMode::SendBeforeReturning { timeout } => Some(timeout),
+        };
+        Ok(())
+    }
+
+    /// Gzip-compresses the body of every request to the intake, and marks it with `Content-Encoding: gzip`.
+    #[cfg(feature = "compression")]
+    pub fn enable_compression(&mut self) {
+        self.compress = true;
+    }
+
+    /// Copies every record the intake accepts to an OpenTelemetry collector, see the `otlp` module.
+    ///
+    /// The collector is reached with the connection settings last passed to `set_http_config`, or
+    /// the defaults, but never with the intake's `TlsConfig`.
+    #[cfg(feature = "otlp")]
+    pub fn set_otlp_exporter(&mut self, mut exporter: otlp::OtlpExporter) -> Result<(), ClientConfigError> {
+        exporter.connect(&self.http_config)?;
+        self.otlp = Some(Arc::new(exporter));
+        Ok(())
+    }
+
+    /// Adds a global tag, sent with every record from now on, or changes its value; returns the previous value.
+    ///
+    /// A record's own tags take precedence over global tags with the same key.
+    pub fn set_tag(&mut self, key: impl Into<String>, value: impl Into<Value>) -> Option<Value> {
+        self.logger_config.tags.insert(key.into(), value.into())
+    }
+
+    /// Removes a global tag; returns its value if it was set.
+    pub fn remove_tag(&mut self, key: &str) -> Option<Value> {
+        self.logger_config.tags.remove(key)
+    }
+
+    /// Installs a redactor that scrubs every outgoing payload right before it is sent.
+    ///
+    /// The redactor sees the complete payload, including the message under `log`, `data` and `tags`.
+    pub fn set_redactor(&mut self, redactor: Redactor) {
+        self.redactor = Some(Arc::new(redactor));
+    }
+
+    /// Limits the size of every outgoing record; oversized records are truncated deterministically.
+    ///
+    /// Truncated values are listed under a top-level `_truncated` array, and every oversized record
+    /// is reported through the error callback. Records that cannot be truncated to fit
+    /// `max_total_bytes` are dropped.
+    pub fn set_payload_limits(&mut self, limits: PayloadLimits) {
+        self.limits = Some(limits);
+    }
+
+    /// Installs a sampler that decides which records passed to `log` are sent.
+    ///
+    /// Kept records carry a `_suppressed` count of the records dropped for the same sampling key since
+    /// the previous one. Records sent with `custom_log` are not sampled.
+    pub fn set_sampler(&mut self, sampler: Sampler) {
+        self.sampler = Some(sampler);
+    }
+
+    /// Collapses repeated identical records passed to `log` within `window`.
+    ///
+    /// Records are identical when they share level, target, message template and type. The first one
+    /// is sent right away; once `window` passes without a repeat, or on `flush`, a single summary with
+    /// `repeat_count`, `first_seen` and `last_seen` is sent in place of the repeats. Closing windows
+    /// needs a Tokio runtime; records logged without one are not deduplicated.
+    pub fn set_dedup_window(&mut self, window: Duration) {
+        self.dedup = Some(Deduplicator::new(window));
+    }
+
+    /// Stops sending to an intake that keeps failing, see the `circuit` module.
+    ///
+    /// While the circuit is open, records are buffered in memory and replayed once a probe gets
+    /// through. Buffered records are not waited for by `flush`. With
+    /// `BlockingMode::SendBeforeReturning`, `log` returns right away while the circuit is open.
+    pub fn set_circuit_breaker(&mut self, config: CircuitBreakerConfig) {
+        self.abandon_buffered();
+        self.circuit = Some(Arc::new(CircuitBreaker::new(config)));
+    }
+
+    /// Counts the records buffered by the circuit breaker as failed and passes them to the delivery
+    /// error hook, for when they will not be sent anymore.
+    fn abandon_buffered(&self) {
+        let abandoned = self.circuit.as_ref().map(|circuit| circuit.drain()).unwrap_or_default();
+        if abandoned.is_empty() {
+            return;
+        }
+        self.stats.failed.fetch_add(abandoned.len() as u64, std::sync::atomic::Ordering::Relaxed);
+        self.hooks.delivery_failed(&DeliveryError::CircuitOpen, &abandoned);
+    }
+
+    /// The circuit breaker's state and counters, or `None` if no circuit breaker is set.
+    pub fn circuit_metrics(&self) -> Option<CircuitMetrics> {
+        self.circuit.as_ref().map(|circuit| circuit.metrics())
+    }
+
+    /// A snapshot of the delivery statistics: records accepted, filtered, sampled and dropped, payloads
+    /// sent, retried and failed, bytes sent, queue depth and send latency. See the `stats` module for
+    /// exporting them.
+    pub fn stats(&self) -> LoggerStats {
+        let mut stats = self.stats.snapshot();
+        let circuit = self.circuit_metrics();
+        stats.queue_depth = self.in_flight.count() + circuit.as_ref().map_or(0, |circuit| circuit.buffered);
+        stats.dropped.buffer_full = circuit.as_ref().map_or(0, |circuit| circuit.dropped);
+        stats.circuit = circuit;
+        stats.endpoints = self.endpoint_status();
+        stats
+    }
+
+    /// Reports an error as an `error` record of type `error`.
+    ///
+    /// The `source()` chain is walked and serialized under `data.error`, with each cause's message and
+    /// type name, a backtrace when enabled, and a `fingerprint` derived from the type names so that
+    /// POGR can group incidents by error kind. `context` is attached under `data.context`.
+    ///
+    /// The error is named after its static type, so pass the concrete error rather than a `&dyn Error`
+    /// where you have it: a trait object can only be named if it is a common standard library type.
+    pub fn report_error<E: AsDynError + ?Sized>(&self, error: &E, context: Value) {
+        self.send_error_report(ErrorReport::new(error), context);
+    }
+
+    fn send_error_report(&self, report: ErrorReport, context: Value) {
+        StatsCollector::count(&self.stats.accepted);
+        let mut payload = self.base_payload(Level::Error);
+        payload["type"] = "error".into();
+        payload["log"] = report.message.clone().into();
+        payload["data"] = serde_json::json!({
+            "error": report,
+            "context": context,
+        });
+        if !self.finish_payload(&mut payload) {
+            return;
+        }
+        if let Some(client) = self.client.clone() {
+            self.transport(client).spawn(payload);
+        }
+    }
+
+    /// Installs a callback for problems with individual records, such as oversized payloads.
+    ///
+    /// The callback runs on the logging thread and should return quickly.
+    pub fn set_error_callback(&mut self, callback: impl Fn(&LogError) + Send + Sync + 'static) {
+        self.error_callback = Some(Arc::new(callback));
+    }
+
+    /// Reports a problem with a record to the error callback, or to stderr if none is installed.
+    fn report(&self, error: LogError) {
+        match &self.error_callback {
+            Some(callback) => callback(&error),
+            None => eprintln!("{}", error),
+        }
+    }
+
+    /// Calls `hook` with every delivery that failed, and the payloads that were not delivered.
+    ///
+    /// Once a hook is installed, delivery failures are no longer printed to stderr. A record is
+    /// reported once it is given up: after failover to other endpoints and after refreshing rejected
+    /// credentials, when a blocking send times out, or, with `DeliveryError::CircuitOpen`, when the
+    /// logger is dropped while the circuit breaker still buffers it. Records held back by an open
+    /// circuit breaker are not failures until then (see `set_error_callback` for its transitions),
+    /// and records evicted from its full buffer go to `on_drop`. The hook runs on the send task and
+    /// should return quickly.
+    pub fn on_delivery_error(&mut self, hook: impl Fn(&DeliveryError, &[Value]) + Send + Sync + 'static) {
+        self.hooks.delivery_error = Some(Arc::new(hook));
+    }
+
+    /// Calls `hook` with every record dropped before delivery, and why.
+    ///
+    /// Runs on the logging thread, or on the send task for records evicted from the circuit breaker's buffer.
+    pub fn on_drop(&mut self, hook: impl Fn(DropReason, &Value) + Send + Sync + 'static) {
+        self.hooks.drop = Some(Arc::new(hook));
+    }
+
+    /// Calls `hook` when the intake or the auth endpoint rejects the credentials even after
+    /// refreshing them, or the credential provider fails. These are also delivery errors.
+    pub fn on_auth_failure(&mut self, hook: impl Fn(&DeliveryError) + Send + Sync + 'static) {
+        self.hooks.auth_failure = Some(Arc::new(hook));
+    }
+
+    /// Prints a delivery failure to stderr, unless a delivery error hook has already seen it.
+    fn print_delivery_error(&self, context: &str, error: &DeliveryError) {
+        let reported = self.hooks.handles_delivery_errors() && !matches!(error, DeliveryError::CircuitOpen);
+        if !reported {
+            eprintln!("{}: {}", context, error);
+        }
+    }
+
+    /// Runs the processing steps that apply to the final payload, after all enrichment.
+    ///
+    /// Returns `false` if the record must not be sent.
+    fn finish_payload(&self, payload: &mut Value) -> bool {
+        if let Some(redactor) = &self.redactor {
+            redactor.redact(payload);
+        }
+        if let Some(limits) = &self.limits {
+            // The drop hook gets the record as it was before truncation was attempted.
+            let original = self.hooks.drop.is_some().then(|| payload.clone());
+            match limits.apply(payload) {
+                Ok(None) => {},
+                Ok(Some(oversized)) => self.report(LogError::Oversized(oversized)),
+                Err(oversized) => {
+                    StatsCollector::count(&self.stats.oversized);
+                    if let Some(original) = &original {
+                        self.hooks.dropped(DropReason::Oversized, original);
+                    }
+                    self.report(LogError::Oversized(oversized));
+                    return false;
+                },
+            }
+        }
+        true
+    }
+
+    /// Bundles the state needed to deliver one payload so it can move into a send task.
+    fn transport(&self, client: Client) -> Transport {
+        Transport {
+            client,
+            endpoints: self.endpoints.clone(),
+            credentials: self.credentials.clone(),
+            session: self.session.clone(),
+            in_flight: self.in_flight.clone(),
+            runtime: self.runtime.as_ref().and_then(|runtime| runtime.handle()),
+            #[cfg(feature = "compression")]
+            compress: self.compress,
+            circuit: self.circuit.clone(),
+            stats: self.stats.clone(),
+            hooks: self.hooks.clone(),
+            error_callback: self.error_callback.clone(),
+            #[cfg(feature = "otlp")]
+            otlp: self.otlp.clone(),
+            #[cfg(feature = "testing")]
+            capture: self.capture.clone(),
+        }
+    }
+
+    /// Blocks until every record handed to a background task has been sent, or `timeout` passes.
+    ///
+    /// Returns `true` if nothing is left in flight.
+    pub fn flush_timeout(&self, timeout: Duration) -> bool {
+        self.close_dedup_windows();
+        self.in_flight.wait(timeout)
+    }
+
+    /// Sends the summaries of open dedup windows now, so that a flush also covers repeats still being counted.
+    fn close_dedup_windows(&self) {
+        if let Some(dedup) = &self.dedup {
+            dedup.flush();
+        }
+    }
+
+    /// Sends a panic report synchronously, then waits for records still in flight, all within `timeout`.
+    pub(crate) fn report_panic(&self, mut payload: Value, timeout: Duration) {
+        StatsCollector::count(&self.stats.accepted);
+        if !self.finish_payload(&mut payload) {
+            return;
+        }
+        let client = match self.client.clone() {
+            Some(client) => client,
+            None => return,
+        };
+
+        let deadline = Instant::now() + timeout;
+        if let Err(e) = self.transport(client).send_blocking(payload, timeout) {
+            self.print_delivery_error("Failed to send panic report", &e);
+        }
+        self.close_dedup_windows();
+        self.in_flight.wait(deadline.saturating_duration_since(Instant::now()));
+    }
+
+    /// Sends a payload built earlier, for example one read back from a `Spool`, and waits up to `timeout`
+    /// for the intake to answer.
+    ///
+    /// The payload is sent unchanged, timestamp included: it went through sampling, redaction and size
+    /// limits when it was first logged. Delivery goes through the endpoints, circuit breaker, statistics
+    /// and hooks like any other record.
+    pub fn replay(&self, payload: Value, timeout: Duration) -> Result<(), DeliveryError> {
+        StatsCollector::count(&self.stats.accepted);
+        let client = self.client.clone().expect("HTTP client must be set");
+        self.transport(client).send_blocking(payload, timeout)
+    }
+
+    /// Builds the payload `log` sends for a record, before sampling, deduplication, redaction and size limits.
+    ///
+    /// The message is sent as-is under `log`. The `pogr.type`, `pogr.data` and `pogr.tags` key-values set by
+    /// `structured_log!` become `type`, `data` and `tags`; any other key-values are added to `data`. Records
+    /// without a type get the `default_type` of the `LoggerConfig`, if it has one.
+    pub fn build_payload(&self, record: &Record) -> Value {
+        let mut payload = self.base_payload(record.level());
+        payload["log"] = Value::String(record.args().to_string());
+
+        let mut visitor = PayloadVisitor { payload: &mut payload, extra: serde_json::Map::new() };
+        let _ = record.key_values().visit(&mut visitor);
+        let extra = visitor.extra;
+        if !extra.is_empty() {
+            match &mut payload["data"] {
+                Value::Object(data) => data.extend(extra),
+                data @ Value::Null => *data = Value::Object(extra),
+                // Structured data that is not an object has no place for named fields; keep it intact.
+                _ => {},
+            }
+        }
+        if let Some(default_type) = &self.logger_config.default_type {
+            if payload.get("type").is_none_or(Value::is_null) {
+                payload["type"] = default_type.as_str().into();
+            }
+        }
+        payload
+    }
+
+    /// The fields every payload starts with, including the global tags if there are any.
+    fn base_payload(&self, level: Level) -> Value {
+        let mut payload = serde_json::json!({
+            "service": self.logger_config.service,
+            "environment": self.logger_config.environment,
+            "severity": level.to_string().to_lowercase(),
+        });
+        if !self.logger_config.tags.is_empty() {
+            payload["tags"] = Value::Object(self.logger_config.tags.clone());
+        }
+        payload
     }
 
     /// Asynchronously sends a custom log message to the remote server.
@@ -243,7 +989,8 @@ impl POGRLogger {
     ///
     /// # Notes
     /// This method spawns an asynchronous task to send the log data, ensuring that logging
-    /// does not block the main execution flow of the application.
+    /// does not block the main execution flow of the application. Use `custom_log_delivered`
+    /// to wait for the intake's answer instead.
     #[allow(dead_code)]
     pub async fn custom_log(&self, level: Level, msg: &str, log_type: &str, data: Value, tags: Value) {
         // Check if the client is initialized. In this context, we assume the client should always be Some.
@@ -256,48 +1003,34 @@ impl POGRLogger {
             }
         };
     
-        let api_url = self.api_url.clone().unwrap_or_else(|| {
-            eprintln!("API URL is not set, using default.");
-            "https://api.pogr.io/v1/intake/logs".to_string()
-        });
-    
-        let auth_config = self.auth_config.clone();
-    
-        let log_data = serde_json::json!({
-            "service": self.logger_config.service,
-            "environment": self.logger_config.environment,
-            "severity": level.to_string().to_lowercase(),
-            "type": log_type,
-            "log": msg,
-            "data": data,
-            "tags": tags,
-        });
-    
-        tokio::spawn(async move {
-            let req = client.post(&api_url)
-                .json(&log_data)
-                .header("content-type", "application/json");
-    
-            let req = match auth_config {
-                LogConfig::ClientBuild { client_id, build_id, .. } => {
-                    req.header("POGR_CLIENT", &client_id)
-                        .header("POGR_BUILD", &build_id)
-                },
-                LogConfig::AccessKeys { access_key, secret_key, .. } => {
-                    req.header("POGR_ACCESS", &access_key)
-                        .header("POGR_SECRET", &secret_key)
-                },
-            };
-    
-            match req.send().await {
-                Ok(response) => {
-                    if !response.status().is_success() {
-                        eprintln!("Failed to send log data, HTTP Error: {}", response.status());
-                    }
-                },
-                Err(e) => eprintln!("Failed to send log data: {}", e),
-            }
-        });
+        if let Some(log_data) = self.custom_payload(level, msg, log_type, data, tags) {
+            self.transport(client).spawn(log_data);
+        }
+    }
+
+    /// Sends a custom log message like `custom_log`, but completes only once the intake has answered.
+    ///
+    /// Returns the error the record was given up with, after failover and credential refreshes, which
+    /// the delivery error hook sees too. Records the circuit breaker buffers for later complete with
+    /// `DeliveryError::CircuitOpen`, and records that are not sent at all, such as ones over the size
+    /// limits, with `Ok(())`.
+    pub async fn custom_log_delivered(&self, level: Level, msg: &str, log_type: &str, data: Value, tags: Value) -> Result<(), DeliveryError> {
+        let client = self.client.clone().expect("HTTP client must be set");
+        match self.custom_payload(level, msg, log_type, data, tags) {
+            Some(log_data) => self.transport(client).deliver(log_data).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Builds the final payload of a `custom_log` record, or `None` if it must not be sent.
+    fn custom_payload(&self, level: Level, msg: &str, log_type: &str, data: Value, tags: Value) -> Option<Value> {
+        StatsCollector::count(&self.stats.accepted);
+        let mut log_data = self.base_payload(level);
+        log_data["type"] = log_type.into();
+        log_data["log"] = msg.into();
+        log_data["data"] = data;
+        merge_tags(&mut log_data, tags);
+        self.finish_payload(&mut log_data).then_some(log_data)
     }
     
        
@@ -305,16 +1038,197 @@ impl POGRLogger {
 
 
 
+/// Builds a `POGRLogger` together with the HTTP client it uses.
+///
+/// # Examples
+/// ```no_run
+/// use pogr_log_rs::http::HttpConfig;
+/// use pogr_log_rs::{LogConfig, LoggerConfig, POGRLogger};
+/// use std::time::Duration;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let auth = LogConfig::AccessKeys {
+///     access_key: "access".to_string(),
+///     secret_key: "secret".to_string(),
+///     logger_config: LoggerConfig::new("matchmaker", "prod"),
+/// };
+/// let mut http = HttpConfig::from_env()?;
+/// http.request_timeout = Some(Duration::from_secs(5));
+/// POGRLogger::builder(auth)
+///     .http(http)
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct POGRLoggerBuilder {
+    auth_config: LogConfig,
+    logger_config: LoggerConfig,
+    api_url: Option<String>,
+    endpoints: Option<Endpoints>,
+    http: HttpConfig,
+    tls: Option<TlsConfig>,
+}
+
+impl POGRLoggerBuilder {
+    /// Starts from the given credentials, the `LoggerConfig` they carry, the intake URL from
+    /// `POGR_INTAKE_URL` (or the default) and the default connection settings.
+    pub fn new(auth_config: LogConfig) -> Self {
+        let logger_config = match &auth_config {
+            LogConfig::ClientBuild { logger_config, .. } | LogConfig::AccessKeys { logger_config, .. } => logger_config.clone(),
+        };
+        POGRLoggerBuilder {
+            auth_config,
+            logger_config,
+            api_url: None,
+            endpoints: None,
+            http: HttpConfig::default(),
+            tls: None,
+        }
+    }
+
+    /// Overrides the `LoggerConfig` carried by the credentials.
+    pub fn logger_config(mut self, logger_config: LoggerConfig) -> Self {
+        self.logger_config = logger_config;
+        self
+    }
+
+    /// Sends to this intake URL instead of the one from `POGR_INTAKE_URL` or the default.
+    pub fn api_url(mut self, api_url: impl Into<String>) -> Self {
+        self.api_url = Some(api_url.into());
+        self
+    }
+
+    /// Sends to several endpoints with failover and routing; takes precedence over `api_url`.
+    pub fn endpoints(mut self, endpoints: Endpoints) -> Self {
+        self.endpoints = Some(endpoints);
+        self
+    }
+
+    /// Sets timeouts, proxy, pool, HTTP/2 and keepalive settings.
+    pub fn http(mut self, http: HttpConfig) -> Self {
+        self.http = http;
+        self
+    }
+
+    /// Sets root certificates, the client certificate and SPKI pins.
+    pub fn tls(mut self, tls: TlsConfig) -> Self {
+        self.tls = Some(tls);
+        self
+    }
+
+    /// Builds the HTTP client and the logger.
+    pub fn build(self) -> Result<POGRLogger, ClientConfigError> {
+        let client = self.http.build_client(self.tls.as_ref())?;
+        let mut logger = POGRLogger::new(client, self.api_url, self.auth_config, self.logger_config);
+        if let Some(endpoints) = self.endpoints {
+            logger.set_endpoints(endpoints);
+        }
+        logger.http_config = self.http;
+        logger.tls_config = self.tls;
+        Ok(logger)
+    }
+
+    /// Builds the logger and installs it as the global logger; see `init_with_logger`.
+    pub fn init(self, filter: LevelFilter) -> Result<(), ClientConfigError> {
+        init_with_logger(self.build()?, filter);
+        Ok(())
+    }
+}
+
+/// Copies a record's key-values into a payload.
+struct PayloadVisitor<'a> {
+    payload: &'a mut Value,
+    /// Key-values other than the ones set by `structured_log!`, destined for `data`.
+    extra: serde_json::Map<String, Value>,
+}
+
+impl<'kvs> log::kv::VisitSource<'kvs> for PayloadVisitor<'_> {
+    fn visit_pair(&mut self, key: log::kv::Key<'kvs>, value: log::kv::Value<'kvs>) -> Result<(), log::kv::Error> {
+        let value = serde_json::to_value(value).unwrap_or(Value::Null);
+        match key.as_str() {
+            "pogr.type" => self.payload["type"] = value,
+            "pogr.data" => self.payload["data"] = value,
+            "pogr.tags" => merge_tags(self.payload, value),
+            other => {
+                self.extra.insert(other.to_string(), value);
+            },
+        }
+        Ok(())
+    }
+}
+
+/// Sets a record's tags over the global tags already in `payload`, the record's values winning.
+///
+/// Without global tags, the record's tags are sent as they are.
+fn merge_tags(payload: &mut Value, tags: Value) {
+    match (&mut payload["tags"], tags) {
+        (Value::Object(global), Value::Object(tags)) => global.extend(tags),
+        // Tags that are not an object have no keys to merge, and replacing the global tags with them
+        // would lose those; keep the global tags intact.
+        (Value::Object(_), _) => {},
+        (global, tags) => *global = tags,
+    }
+}
+
+/// Identifies the message template of a record: the format string if it has no arguments,
+/// otherwise the call site.
+fn message_template(record: &Record) -> String {
+    if let Some(literal) = record.args().as_str() {
+        return literal.to_string();
+    }
+    match (record.file(), record.line()) {
+        (Some(file), Some(line)) => format!("{}:{}", file, line),
+        _ => record.args().to_string(),
+    }
+}
+
+/// Milliseconds since the Unix epoch, the unit of payload timestamps.
+pub(crate) fn epoch_millis() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_millis() as u64).unwrap_or_default()
+}
+
+/// The runtime a new logger starts with: none when sends can go to the caller's Tokio runtime,
+/// and one of its own otherwise.
+fn default_runtime() -> Option<Arc<LoggerRuntime>> {
+    #[cfg(feature = "async-tokio")]
+    return None;
+    #[cfg(not(feature = "async-tokio"))]
+    return Some(Arc::new(LoggerRuntime::new().expect("failed to start the logger's runtime")));
+}
+
 static LOGGER: OnceCell<Mutex<POGRLogger>> = OnceCell::new();
 
+/// Installs a `POGRLogger` as the global logger.
+///
+/// The HTTP client is configured from the `POGR_HTTP_*` environment variables (see `HttpConfig::from_env`),
+/// with a connect timeout of 10 seconds and a request timeout of 30 seconds unless overridden.
+/// If those variables hold invalid settings, the problem is printed to stderr and the defaults are
+/// used instead.
+///
+/// # Panics
+/// Panics if a logger is already installed.
 pub fn init_logger(auth_config: LogConfig, api_url: Option<String>, logger_config: LoggerConfig, filter: LevelFilter) {
-    //let _logger = LOGGER.get_or_init(|| Mutex::new(POGRLogger::new(config)));
-    let _logger = POGRLogger::new(
-        Client::new(),
+    let client = HttpConfig::from_env()
+        .and_then(|http| http.build_client(None))
+        .unwrap_or_else(|e| {
+            eprintln!("Ignoring the POGR_HTTP_* settings: {}", e);
+            HttpConfig::default().build_client(None).expect("Failed to build HTTP client")
+        });
+    let logger = POGRLogger::new(
+        client,
         api_url, 
         auth_config,
         logger_config,
     );
+    init_with_logger(logger, filter);
+}
+
+/// Installs an already configured `POGRLogger` as the global logger.
+///
+/// # Panics
+/// Panics if a logger is already installed.
+pub fn init_with_logger(logger: POGRLogger, filter: LevelFilter) {
+    assert!(LOGGER.set(Mutex::new(logger)).is_ok(), "Failed to set logger");
     // Since set_logger requires a &'static dyn Log, we use a static function pointer to a function that
     // dereferences the logger from the LOGGER static. This requires implementing a static method that
     // can act as the Log implementation for the global logger.
@@ -324,6 +1238,40 @@ pub fn init_logger(auth_config: LogConfig, api_url: Option<String>, logger_confi
     set_max_level(filter);
 }
 
+/// Reports an error through the global logger; see `POGRLogger::report_error`.
+///
+/// Does nothing if `init_logger` has not been called.
+pub fn report_error<E: AsDynError + ?Sized>(error: &E, context: Value) {
+    if let Some(logger) = LOGGER.get() {
+        logger.lock().unwrap().report_error(error, context);
+    }
+}
+
+/// Adds or changes a global tag of the global logger; see `POGRLogger::set_tag`.
+///
+/// Does nothing if `init_logger` has not been called.
+pub fn set_tag(key: impl Into<String>, value: impl Into<Value>) {
+    if let Some(logger) = LOGGER.get() {
+        logger.lock().unwrap().set_tag(key, value);
+    }
+}
+
+/// Removes a global tag from the global logger; see `POGRLogger::remove_tag`.
+///
+/// Does nothing if `init_logger` has not been called.
+pub fn remove_tag(key: &str) {
+    if let Some(logger) = LOGGER.get() {
+        logger.lock().unwrap().remove_tag(key);
+    }
+}
+
+/// A snapshot of the global logger's delivery statistics; see `POGRLogger::stats`.
+///
+/// Returns `None` if `init_logger` has not been called.
+pub fn stats() -> Option<LoggerStats> {
+    LOGGER.get().map(|logger| logger.lock().unwrap().stats())
+}
+
 struct LoggerFn;
 
 impl Log for LoggerFn {
@@ -0,0 +1,210 @@
+//! Per-record size limits and deterministic truncation of oversized payloads.
+//!
+//! Limits are applied to the final payload, after enrichment and redaction. Every value that had
+//! to be shortened is listed under a top-level `_truncated` array, as
+//! `{"path": <JSON pointer>, "reason": <limit>, "original": <original size>}`, so the intake and
+//! anyone reading the record can tell that it is incomplete.
+
+use serde_json::{json, Value};
+
+/// Limits applied to each outgoing record. `None` disables a limit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PayloadLimits {
+    /// Maximum length in bytes of the message under `log`.
+    pub max_message_len: Option<usize>,
+    /// Maximum nesting depth of objects and arrays; the payload itself is depth 1.
+    pub max_depth: Option<usize>,
+    /// Maximum number of elements kept in any array.
+    pub max_array_len: Option<usize>,
+    /// Maximum length in bytes of any other string value.
+    pub max_string_len: Option<usize>,
+    /// Maximum size in bytes of the serialized payload.
+    pub max_total_bytes: Option<usize>,
+}
+
+impl Default for PayloadLimits {
+    fn default() -> Self {
+        PayloadLimits {
+            max_message_len: Some(8 * 1024),
+            max_depth: Some(16),
+            max_array_len: Some(256),
+            max_string_len: Some(4 * 1024),
+            max_total_bytes: Some(64 * 1024),
+        }
+    }
+}
+
+impl PayloadLimits {
+    /// Limits with every check disabled.
+    pub fn unlimited() -> Self {
+        PayloadLimits {
+            max_message_len: None,
+            max_depth: None,
+            max_array_len: None,
+            max_string_len: None,
+            max_total_bytes: None,
+        }
+    }
+
+    /// Truncates the payload in place until it fits the limits.
+    ///
+    /// Returns `Ok(None)` if nothing had to change, `Ok(Some(report))` if the payload was truncated,
+    /// and `Err(report)` if it could not be brought under `max_total_bytes` and should be dropped.
+    pub fn apply(&self, payload: &mut Value) -> Result<Option<OversizedRecord>, OversizedRecord> {
+        let original_bytes = payload.to_string().len();
+        let mut truncated = Vec::new();
+
+        if let Some(Value::String(message)) = payload.get_mut("log") {
+            if let Some(limit) = self.max_message_len {
+                if message.len() > limit {
+                    truncated.push(marker("/log", "max_message_len", message.len()));
+                    truncate_str(message, limit);
+                }
+            }
+        }
+        self.walk(payload, "", 1, &mut truncated);
+
+        if let Some(limit) = self.max_total_bytes {
+            self.fit_total_bytes(payload, limit, &mut truncated);
+        }
+
+        let dropped = self.max_total_bytes.is_some_and(|limit| serialized_len(payload, &truncated) > limit);
+        if truncated.is_empty() && !dropped {
+            return Ok(None);
+        }
+
+        let report = OversizedRecord {
+            original_bytes,
+            truncated: truncated.iter().map(|entry| entry["path"].as_str().unwrap_or_default().to_string()).collect(),
+            dropped,
+        };
+        if dropped {
+            return Err(report);
+        }
+        if let Value::Object(map) = payload {
+            map.insert("_truncated".to_string(), Value::Array(truncated));
+        }
+        Ok(Some(report))
+    }
+
+    fn walk(&self, value: &mut Value, path: &str, depth: usize, truncated: &mut Vec<Value>) {
+        match value {
+            Value::Object(map) => {
+                if self.max_depth.is_some_and(|limit| depth > limit) {
+                    truncated.push(marker(path, "max_depth", map.len()));
+                    *value = Value::String("[truncated]".to_string());
+                    return;
+                }
+                for (key, child) in map.iter_mut() {
+                    // The message has its own limit, applied before the walk.
+                    if depth == 1 && key == "log" {
+                        continue;
+                    }
+                    let child_path = format!("{}/{}", path, escape_token(key));
+                    self.walk(child, &child_path, depth + 1, truncated);
+                }
+            },
+            Value::Array(items) => {
+                if self.max_depth.is_some_and(|limit| depth > limit) {
+                    truncated.push(marker(path, "max_depth", items.len()));
+                    *value = Value::String("[truncated]".to_string());
+                    return;
+                }
+                if let Some(limit) = self.max_array_len {
+                    if items.len() > limit {
+                        truncated.push(marker(path, "max_array_len", items.len()));
+                        items.truncate(limit);
+                    }
+                }
+                for (index, item) in items.iter_mut().enumerate() {
+                    let child_path = format!("{}/{}", path, index);
+                    self.walk(item, &child_path, depth + 1, truncated);
+                }
+            },
+            Value::String(s) => {
+                if let Some(limit) = self.max_string_len {
+                    if s.len() > limit {
+                        truncated.push(marker(path, "max_string_len", s.len()));
+                        truncate_str(s, limit);
+                    }
+                }
+            },
+            _ => {},
+        }
+    }
+
+    /// Sheds the largest parts of the payload until it fits: first `data`, then the message.
+    fn fit_total_bytes(&self, payload: &mut Value, limit: usize, truncated: &mut Vec<Value>) {
+        if serialized_len(payload, truncated) <= limit {
+            return;
+        }
+
+        if let Some(data) = payload.get_mut("data") {
+            if !data.is_null() {
+                truncated.push(marker("/data", "max_total_bytes", data.to_string().len()));
+                *data = Value::Null;
+            }
+        }
+        if serialized_len(payload, truncated) <= limit {
+            return;
+        }
+
+        let original = match payload.get("log") {
+            Some(Value::String(message)) => message.len(),
+            _ => return,
+        };
+        truncated.push(marker("/log", "max_total_bytes", original));
+        loop {
+            let over = serialized_len(payload, truncated);
+            let message = match payload.get_mut("log") {
+                Some(Value::String(message)) => message,
+                _ => return,
+            };
+            if over <= limit || message.is_empty() {
+                return;
+            }
+            // Escaped characters serialize to more than one byte, so this may take a few rounds.
+            let target = message.len().saturating_sub(over - limit);
+            truncate_str(message, target);
+        }
+    }
+}
+
+/// Describes a record that exceeded the payload limits.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OversizedRecord {
+    /// Size of the serialized payload before truncation.
+    pub original_bytes: usize,
+    /// JSON pointers of the values that were truncated.
+    pub truncated: Vec<String>,
+    /// Whether the record was dropped because it could not be truncated to fit.
+    pub dropped: bool,
+}
+
+/// Size of the payload once the `_truncated` entries have been added to it.
+fn serialized_len(payload: &Value, truncated: &[Value]) -> usize {
+    let len = payload.to_string().len();
+    if truncated.is_empty() {
+        len
+    } else {
+        len + json!({"_truncated": truncated}).to_string().len()
+    }
+}
+
+fn marker(path: &str, reason: &str, original: usize) -> Value {
+    json!({"path": path, "reason": reason, "original": original})
+}
+
+/// Shortens a string to at most `limit` bytes without splitting a character.
+fn truncate_str(s: &mut String, limit: usize) {
+    let mut end = limit.min(s.len());
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    s.truncate(end);
+}
+
+/// Encodes `~` and `/` in an object key for use in a JSON pointer.
+fn escape_token(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
@@ -0,0 +1,464 @@
+//! A local stand-in for the POGR intake, for development and integration tests without network access.
+//!
+//! `MockIntake` runs an HTTP server on the caller's Tokio runtime that implements the intake API:
+//!
+//! - `POST /v1/intake/logs` takes records (one JSON object, or an array of them, optionally
+//!   gzip-compressed) after checking the `POGR_CLIENT` / `POGR_BUILD` or `POGR_ACCESS` / `POGR_SECRET`
+//!   headers, or a bearer token issued by the token endpoint.
+//! - `POST /v1/auth/token` exchanges client/build ids for a bearer token, see `SessionTokenConfig`.
+//! - `GET /mock/logs` lists the records received, filtered by the `type`, `severity`, `service`,
+//!   `environment` and `contains` query parameters and cut to `limit`; `DELETE /mock/logs` forgets them.
+//! - `POST /mock/faults` queues faults (a JSON `Fault` or an array of them) for the next intake
+//!   requests; `DELETE /mock/faults` clears the queue.
+//!
+//! Without configured credentials any complete pair of headers is accepted; missing headers are
+//! answered with `401` and unknown credentials with `403`.
+//!
+//! The `pogr-intake-mock` binary runs the same server from the command line.
+//!
+//! # Examples
+//! ```
+//! use pogr_log_rs::mock_intake::{Fault, MockIntake, MockIntakeConfig};
+//! use pogr_log_rs::Credentials;
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() -> std::io::Result<()> {
+//! let intake = MockIntake::start(MockIntakeConfig::new()
+//!     .accept(Credentials::AccessKeys {
+//!         access_key: "local".to_string(),
+//!         secret_key: "local".to_string(),
+//!     })).await?;
+//! intake.fail_next(Fault::RateLimited { retry_after_secs: 1 });
+//!
+//! // Point a logger at `intake.url()`, log, flush, then look at `intake.logs()`.
+//! assert!(intake.logs().is_empty());
+//! # Ok(())
+//! # }
+//! ```
+
+use hyper::header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_ENCODING, CONTENT_TYPE, RETRY_AFTER};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
+use std::convert::Infallible;
+use std::fmt;
+use std::io::{self, Read};
+use std::net::{SocketAddr, TcpListener};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+
+use crate::Credentials;
+
+pub const INTAKE_PATH: &str = "/v1/intake/logs";
+pub const TOKEN_PATH: &str = "/v1/auth/token";
+pub const LOGS_PATH: &str = "/mock/logs";
+pub const FAULTS_PATH: &str = "/mock/faults";
+
+/// A failure injected into one intake request.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "fault", rename_all = "snake_case")]
+pub enum Fault {
+    /// Answer after `ms` milliseconds; the request is otherwise handled as usual.
+    Latency { ms: u64 },
+    /// Answer with this status code without storing the records.
+    Status { status: u16 },
+    /// Answer `429 Too Many Requests` with a `Retry-After` header.
+    RateLimited { retry_after_secs: u64 },
+    /// Close the connection without answering.
+    DropConnection,
+}
+
+/// Settings of a `MockIntake`.
+#[derive(Clone, Debug)]
+pub struct MockIntakeConfig {
+    addr: SocketAddr,
+    credentials: Vec<Credentials>,
+    token_lifetime: Duration,
+}
+
+impl Default for MockIntakeConfig {
+    fn default() -> Self {
+        MockIntakeConfig {
+            addr: SocketAddr::from(([127, 0, 0, 1], 0)),
+            credentials: Vec::new(),
+            token_lifetime: Duration::from_secs(3600),
+        }
+    }
+}
+
+impl MockIntakeConfig {
+    /// Listens on a free port of 127.0.0.1, accepts any credentials and issues hour-long tokens.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn bind(mut self, addr: SocketAddr) -> Self {
+        self.addr = addr;
+        self
+    }
+
+    /// Accepts these credentials, and from now on only the credentials passed to `accept`.
+    pub fn accept(mut self, credentials: Credentials) -> Self {
+        self.credentials.push(credentials);
+        self
+    }
+
+    /// Lifetime of the bearer tokens issued by the token endpoint.
+    pub fn token_lifetime(mut self, lifetime: Duration) -> Self {
+        self.token_lifetime = lifetime;
+        self
+    }
+}
+
+/// A running mock intake. The server stops when this is dropped.
+pub struct MockIntake {
+    addr: SocketAddr,
+    state: Arc<State>,
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+impl MockIntake {
+    /// Binds the configured address and serves on the current Tokio runtime.
+    pub async fn start(config: MockIntakeConfig) -> io::Result<Self> {
+        let listener = TcpListener::bind(config.addr)?;
+        listener.set_nonblocking(true)?;
+        let addr = listener.local_addr()?;
+        let server = Server::from_tcp(listener).map_err(io::Error::other)?;
+
+        let state = Arc::new(State::new(config));
+        let service_state = state.clone();
+        let make_service = make_service_fn(move |_| {
+            let state = service_state.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |req| {
+                    let state = state.clone();
+                    async move { state.handle(req).await }
+                }))
+            }
+        });
+        let (shutdown, stopped) = oneshot::channel::<()>();
+        let server = server.serve(make_service).with_graceful_shutdown(async {
+            let _ = stopped.await;
+        });
+        tokio::spawn(async move {
+            if let Err(e) = server.await {
+                eprintln!("Mock intake stopped: {}", e);
+            }
+        });
+        Ok(MockIntake { addr, state, shutdown: Some(shutdown) })
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// The intake URL, to pass as a logger's `api_url`.
+    pub fn url(&self) -> String {
+        format!("http://{}{}", self.addr, INTAKE_PATH)
+    }
+
+    /// The token endpoint, for `SessionTokenConfig::new`.
+    pub fn token_url(&self) -> String {
+        format!("http://{}{}", self.addr, TOKEN_PATH)
+    }
+
+    /// The records received so far, in order.
+    pub fn logs(&self) -> Vec<Value> {
+        self.state.logs.lock().unwrap().clone()
+    }
+
+    pub fn clear_logs(&self) {
+        self.state.logs.lock().unwrap().clear();
+    }
+
+    /// Intake requests received, including rejected and failed ones.
+    pub fn requests(&self) -> u64 {
+        self.state.requests.load(Ordering::Relaxed)
+    }
+
+    /// Injects a fault into the next intake request that has none queued yet.
+    pub fn fail_next(&self, fault: Fault) {
+        self.fail_next_n(fault, 1);
+    }
+
+    /// Injects a fault into each of the next `requests` intake requests after those already queued.
+    pub fn fail_next_n(&self, fault: Fault, requests: usize) {
+        let mut faults = self.state.faults.lock().unwrap();
+        faults.extend(std::iter::repeat_n(fault, requests));
+    }
+
+    /// Delays every intake answer by `latency`, on top of injected `Fault::Latency`.
+    pub fn set_latency(&self, latency: Duration) {
+        *self.state.latency.lock().unwrap() = latency;
+    }
+
+    /// Drops queued faults and the latency set with `set_latency`.
+    pub fn clear_faults(&self) {
+        self.state.faults.lock().unwrap().clear();
+        *self.state.latency.lock().unwrap() = Duration::ZERO;
+    }
+}
+
+impl Drop for MockIntake {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+    }
+}
+
+/// Returned by the request handler to make hyper close the connection without an answer.
+#[derive(Debug)]
+struct DroppedConnection;
+
+impl fmt::Display for DroppedConnection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "connection dropped by fault injection")
+    }
+}
+
+impl std::error::Error for DroppedConnection {}
+
+/// Shared by the server's connections and the `MockIntake` handle.
+struct State {
+    config: MockIntakeConfig,
+    logs: Mutex<Vec<Value>>,
+    faults: Mutex<VecDeque<Fault>>,
+    latency: Mutex<Duration>,
+    /// Issued bearer tokens and when they expire.
+    tokens: Mutex<HashMap<String, Instant>>,
+    requests: AtomicU64,
+}
+
+impl State {
+    fn new(config: MockIntakeConfig) -> Self {
+        State {
+            config,
+            logs: Mutex::new(Vec::new()),
+            faults: Mutex::new(VecDeque::new()),
+            latency: Mutex::new(Duration::ZERO),
+            tokens: Mutex::new(HashMap::new()),
+            requests: AtomicU64::new(0),
+        }
+    }
+
+    async fn handle(&self, req: Request<Body>) -> Result<Response<Body>, DroppedConnection> {
+        let response = match (req.method(), req.uri().path()) {
+            (&Method::POST, INTAKE_PATH) => return self.intake(req).await,
+            (&Method::POST, TOKEN_PATH) => self.exchange(req).await,
+            (&Method::GET, LOGS_PATH) => self.query(req.uri().query().unwrap_or_default()),
+            (&Method::DELETE, LOGS_PATH) => {
+                self.logs.lock().unwrap().clear();
+                empty(StatusCode::NO_CONTENT)
+            },
+            (&Method::POST, FAULTS_PATH) => self.add_faults(req).await,
+            (&Method::DELETE, FAULTS_PATH) => {
+                self.faults.lock().unwrap().clear();
+                empty(StatusCode::NO_CONTENT)
+            },
+            _ => error(StatusCode::NOT_FOUND, "no such endpoint"),
+        };
+        Ok(response)
+    }
+
+    async fn intake(&self, req: Request<Body>) -> Result<Response<Body>, DroppedConnection> {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        let fault = self.faults.lock().unwrap().pop_front();
+        let mut delay = *self.latency.lock().unwrap();
+        if let Some(Fault::Latency { ms }) = fault {
+            delay += Duration::from_millis(ms);
+        }
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+        match fault {
+            Some(Fault::DropConnection) => return Err(DroppedConnection),
+            Some(Fault::Status { status }) => {
+                let status = StatusCode::from_u16(status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+                return Ok(error(status, "injected fault"));
+            },
+            Some(Fault::RateLimited { retry_after_secs }) => {
+                let mut response = error(StatusCode::TOO_MANY_REQUESTS, "injected fault");
+                response.headers_mut().insert(RETRY_AFTER, HeaderValue::from(retry_after_secs));
+                return Ok(response);
+            },
+            Some(Fault::Latency { .. }) | None => {},
+        }
+
+        if let Err((status, message)) = self.authenticate(req.headers()) {
+            return Ok(error(status, message));
+        }
+        let gzip = req.headers().get(CONTENT_ENCODING).is_some_and(|encoding| encoding == "gzip");
+        let body = match read_body(req, gzip).await {
+            Ok(body) => body,
+            Err(message) => return Ok(error(StatusCode::BAD_REQUEST, &message)),
+        };
+        let records = match body {
+            Value::Array(records) => records,
+            record @ Value::Object(_) => vec![record],
+            _ => return Ok(error(StatusCode::BAD_REQUEST, "expected a JSON object or an array of them")),
+        };
+        let accepted = records.len();
+        self.logs.lock().unwrap().extend(records);
+        Ok(json_response(StatusCode::OK, &json!({ "accepted": accepted })))
+    }
+
+    /// Checks the credentials of an intake request.
+    fn authenticate(&self, headers: &HeaderMap) -> Result<(), (StatusCode, &'static str)> {
+        if let Some(token) = headers.get(AUTHORIZATION).and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+        {
+            return match self.tokens.lock().unwrap().get(token) {
+                Some(expires) if Instant::now() < *expires => Ok(()),
+                Some(_) => Err((StatusCode::UNAUTHORIZED, "token expired")),
+                None => Err((StatusCode::UNAUTHORIZED, "unknown token")),
+            };
+        }
+        let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok()).map(str::to_string);
+        let presented = match (header("POGR_CLIENT"), header("POGR_BUILD"), header("POGR_ACCESS"), header("POGR_SECRET")) {
+            (Some(client_id), Some(build_id), _, _) => Credentials::ClientBuild { client_id, build_id },
+            (_, _, Some(access_key), Some(secret_key)) => Credentials::AccessKeys { access_key, secret_key },
+            _ => return Err((StatusCode::UNAUTHORIZED, "missing credentials")),
+        };
+        self.check(&presented)
+    }
+
+    fn check(&self, presented: &Credentials) -> Result<(), (StatusCode, &'static str)> {
+        if self.config.credentials.is_empty() || self.config.credentials.contains(presented) {
+            Ok(())
+        } else {
+            Err((StatusCode::FORBIDDEN, "unknown credentials"))
+        }
+    }
+
+    /// Exchanges client/build ids for a bearer token.
+    async fn exchange(&self, req: Request<Body>) -> Response<Body> {
+        #[derive(Deserialize)]
+        struct TokenRequest {
+            client_id: String,
+            build_id: String,
+        }
+
+        let request: TokenRequest = match read_body(req, false).await.and_then(|body| {
+            serde_json::from_value(body).map_err(|e| e.to_string())
+        }) {
+            Ok(request) => request,
+            Err(message) => return error(StatusCode::BAD_REQUEST, &message),
+        };
+        let presented = Credentials::ClientBuild { client_id: request.client_id, build_id: request.build_id };
+        if let Err((status, message)) = self.check(&presented) {
+            return error(status, message);
+        }
+        let mut tokens = self.tokens.lock().unwrap();
+        let token = format!("mock-token-{}", tokens.len() + 1);
+        tokens.insert(token.clone(), Instant::now() + self.config.token_lifetime);
+        json_response(StatusCode::OK, &json!({
+            "access_token": token,
+            "expires_in": self.config.token_lifetime.as_secs(),
+        }))
+    }
+
+    /// Lists the received records matching the query parameters.
+    fn query(&self, query: &str) -> Response<Body> {
+        let mut filters = Vec::new();
+        let mut limit = usize::MAX;
+        for (key, value) in query.split('&').filter(|pair| !pair.is_empty()).map(parse_pair) {
+            match key.as_str() {
+                "type" | "severity" | "service" | "environment" => filters.push((key, value)),
+                "contains" => filters.push(("log".to_string(), value)),
+                "limit" => match value.parse() {
+                    Ok(value) => limit = value,
+                    Err(_) => return error(StatusCode::BAD_REQUEST, "limit must be a number"),
+                },
+                _ => return error(StatusCode::BAD_REQUEST, "unknown query parameter"),
+            }
+        }
+        let logs = self.logs.lock().unwrap();
+        let matching: Vec<&Value> = logs.iter()
+            .filter(|record| filters.iter().all(|(field, value)| match record[field.as_str()].as_str() {
+                Some(actual) if field == "log" => actual.contains(value.as_str()),
+                Some(actual) => actual == value,
+                None => false,
+            }))
+            .take(limit)
+            .collect();
+        json_response(StatusCode::OK, &json!(matching))
+    }
+
+    async fn add_faults(&self, req: Request<Body>) -> Response<Body> {
+        let faults = match read_body(req, false).await.and_then(|body| {
+            let faults = match body {
+                Value::Array(_) => serde_json::from_value::<Vec<Fault>>(body),
+                _ => serde_json::from_value::<Fault>(body).map(|fault| vec![fault]),
+            };
+            faults.map_err(|e| e.to_string())
+        }) {
+            Ok(faults) => faults,
+            Err(message) => return error(StatusCode::BAD_REQUEST, &message),
+        };
+        let mut queue = self.faults.lock().unwrap();
+        queue.extend(faults);
+        json_response(StatusCode::OK, &json!({ "queued": queue.len() }))
+    }
+}
+
+/// Reads a JSON request body, gunzipping it first if `gzip` is set.
+async fn read_body(req: Request<Body>, gzip: bool) -> Result<Value, String> {
+    let bytes = hyper::body::to_bytes(req.into_body()).await.map_err(|e| e.to_string())?;
+    if gzip {
+        let mut decoded = Vec::new();
+        flate2::read::GzDecoder::new(&bytes[..]).read_to_end(&mut decoded).map_err(|e| e.to_string())?;
+        return serde_json::from_slice(&decoded).map_err(|e| e.to_string());
+    }
+    serde_json::from_slice(&bytes).map_err(|e| e.to_string())
+}
+
+/// Splits and percent-decodes one `key=value` query parameter.
+fn parse_pair(pair: &str) -> (String, String) {
+    let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+    (decode_component(key), decode_component(value))
+}
+
+fn decode_component(component: &str) -> String {
+    let bytes = component.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                match std::str::from_utf8(&bytes[i + 1..i + 3]).ok().and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                    Some(byte) => {
+                        decoded.push(byte);
+                        i += 2;
+                    },
+                    None => decoded.push(b'%'),
+                }
+            },
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn json_response(status: StatusCode, body: &Value) -> Response<Body> {
+    let mut response = Response::new(Body::from(body.to_string()));
+    *response.status_mut() = status;
+    response.headers_mut().insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    response
+}
+
+fn error(status: StatusCode, message: &str) -> Response<Body> {
+    json_response(status, &json!({ "error": message }))
+}
+
+fn empty(status: StatusCode) -> Response<Body> {
+    let mut response = Response::new(Body::empty());
+    *response.status_mut() = status;
+    response
+}
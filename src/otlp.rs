@@ -0,0 +1,164 @@
+//! Copying delivered records to an OpenTelemetry collector, over OTLP/HTTP with JSON encoding.
+//! Needs the `otlp` feature.
+//!
+//! Installed with `POGRLogger::set_otlp_exporter`. Once the intake has accepted a payload, it is
+//! posted to the collector as an OTLP log record: `log` becomes the body, `severity` and `timestamp`,
+//! if there is one, the severity and time, the time of the export the observed time, `service` and
+//! `environment` the `service.name` and
+//! `deployment.environment` resource attributes, and every other field, such as `type`, `data` and
+//! `tags`, an attribute named `pogr.<field>`. Records the intake did not accept are not exported.
+//! Export failures go to the error callback, see `POGRLogger::set_error_callback`.
+//!
+//! Exports run in tasks of their own, which `flush`, blocking sends and the panic hook do not wait
+//! for, and give up after a timeout. The collector is reached with a client of its own, built from
+//! the logger's `HttpConfig` but without its `TlsConfig`, whose pins and client certificate belong
+//! to the intake.
+//!
+//! # Examples
+//! ```no_run
+//! use pogr_log_rs::otlp::OtlpExporter;
+//! # fn attach(logger: &mut pogr_log_rs::POGRLogger) -> Result<(), pogr_log_rs::http::ClientConfigError> {
+//!
+//! logger.set_otlp_exporter(
+//!     OtlpExporter::new("http://localhost:4318/v1/logs").header("x-tenant", "game-servers"),
+//! )?;
+//! # Ok::<(), pogr_log_rs::http::ClientConfigError>(())
+//! # }
+//! ```
+
+use reqwest::Client;
+use serde_json::{json, Value};
+use std::time::Duration;
+
+use crate::epoch_millis;
+use crate::http::{ClientConfigError, HttpConfig};
+
+/// How long an export may take before it is given up, unless set with `OtlpExporter::timeout`.
+pub const DEFAULT_EXPORT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Where, and with which headers, delivered records are exported.
+#[derive(Clone, Debug)]
+pub struct OtlpExporter {
+    endpoint: String,
+    headers: Vec<(String, String)>,
+    timeout: Duration,
+    /// Built by `connect` when the exporter is installed.
+    client: Client,
+}
+
+impl OtlpExporter {
+    /// An exporter posting to `endpoint`, the full URL of the collector's logs endpoint, usually
+    /// ending in `/v1/logs`.
+    pub fn new(endpoint: impl Into<String>) -> Self {
+        OtlpExporter {
+            endpoint: endpoint.into(),
+            headers: Vec::new(),
+            timeout: DEFAULT_EXPORT_TIMEOUT,
+            client: Client::new(),
+        }
+    }
+
+    /// Adds a header to every export request, such as an API key for the collector.
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// How long an export may take, connecting included, before it is given up and reported.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Replaces the client with one built from `http`, without any TLS settings of the intake.
+    pub(crate) fn connect(&mut self, http: &HttpConfig) -> Result<(), ClientConfigError> {
+        self.client = http.build_client(None)?;
+        Ok(())
+    }
+
+    /// Posts a payload to the collector, returning why it was not accepted.
+    pub(crate) async fn export(&self, payload: &Value) -> Result<(), String> {
+        let mut req = self.client.post(&self.endpoint).json(&logs_request(payload)).timeout(self.timeout);
+        for (name, value) in &self.headers {
+            req = req.header(name, value);
+        }
+        let response = req.send().await.map_err(|e| e.to_string())?;
+        match response.status() {
+            status if status.is_success() => Ok(()),
+            status => Err(format!("HTTP Error: {}", status)),
+        }
+    }
+}
+
+/// The OTLP `ExportLogsServiceRequest` carrying one payload.
+fn logs_request(payload: &Value) -> Value {
+    let resource = [("service.name", "service"), ("deployment.environment", "environment")]
+        .into_iter()
+        .filter_map(|(attribute, field)| payload.get(field).map(|value| key_value(attribute, value)))
+        .collect::<Vec<_>>();
+    let mut record = json!({ "observedTimeUnixNano": unix_nanos(epoch_millis()) });
+    let mut attributes = Vec::new();
+    for (key, value) in payload.as_object().into_iter().flatten() {
+        match key.as_str() {
+            "service" | "environment" => {},
+            "log" => record["body"] = any_value(value),
+            "severity" => {
+                let severity = value.as_str().unwrap_or_default();
+                record["severityText"] = severity.to_uppercase().into();
+                record["severityNumber"] = severity_number(severity).into();
+            },
+            "timestamp" => {
+                if let Some(millis) = value.as_u64() {
+                    record["timeUnixNano"] = unix_nanos(millis);
+                }
+            },
+            _ => attributes.push(key_value(&format!("pogr.{}", key), value)),
+        }
+    }
+    record["attributes"] = attributes.into();
+    json!({
+        "resourceLogs": [{
+            "resource": { "attributes": resource },
+            "scopeLogs": [{
+                "scope": { "name": env!("CARGO_PKG_NAME"), "version": env!("CARGO_PKG_VERSION") },
+                "logRecords": [record],
+            }],
+        }],
+    })
+}
+
+/// Epoch milliseconds as the nanoseconds string of OTLP's JSON encoding.
+fn unix_nanos(millis: u64) -> Value {
+    (u128::from(millis) * 1_000_000).to_string().into()
+}
+
+/// The OTLP severity number of a payload's severity, `0` (unspecified) for unknown ones.
+fn severity_number(severity: &str) -> u8 {
+    match severity {
+        "trace" => 1,
+        "debug" => 5,
+        "info" => 9,
+        "warn" => 13,
+        "error" => 17,
+        _ => 0,
+    }
+}
+
+fn key_value(key: &str, value: &Value) -> Value {
+    json!({ "key": key, "value": any_value(value) })
+}
+
+/// A JSON value as an OTLP `AnyValue`. Integers are strings, as the OTLP JSON encoding requires.
+fn any_value(value: &Value) -> Value {
+    match value {
+        Value::Null => json!({}),
+        Value::Bool(value) => json!({ "boolValue": value }),
+        Value::Number(number) if number.is_f64() => json!({ "doubleValue": number }),
+        Value::Number(number) => json!({ "intValue": number.to_string() }),
+        Value::String(value) => json!({ "stringValue": value }),
+        Value::Array(values) => json!({ "arrayValue": { "values": values.iter().map(any_value).collect::<Vec<_>>() } }),
+        Value::Object(fields) => json!({
+            "kvlistValue": { "values": fields.iter().map(|(key, value)| key_value(key, value)).collect::<Vec<_>>() },
+        }),
+    }
+}
@@ -0,0 +1,89 @@
+//! A panic hook that ships panics to POGR before the process goes down.
+
+use std::backtrace::Backtrace;
+use std::panic::{self, PanicHookInfo};
+use std::sync::{Mutex, MutexGuard, TryLockError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use log::Level;
+use serde_json::{json, Value};
+
+use crate::{POGRLogger, LOGGER};
+
+/// How long the panic hook waits for the report, and for records still in flight, to be delivered.
+pub const DEFAULT_PANIC_FLUSH_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Installs a panic hook that reports panics through the global logger.
+///
+/// Each panic is sent as an `error` record of type `panic`, with the panic message, location, thread
+/// name and a captured backtrace under `data`. The report is delivered synchronously, and records
+/// still in flight are flushed, for up to `DEFAULT_PANIC_FLUSH_TIMEOUT` before the previously installed
+/// hook runs (by default, the one printing the panic to stderr). That time includes waiting for other
+/// threads that are logging at the moment of the panic.
+///
+/// Requires `init_logger` to have been called; until then, panics only reach the previous hook.
+pub fn install_panic_hook() {
+    install_panic_hook_with_timeout(DEFAULT_PANIC_FLUSH_TIMEOUT);
+}
+
+/// Like `install_panic_hook`, waiting at most `timeout` for delivery.
+pub fn install_panic_hook_with_timeout(timeout: Duration) {
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let deadline = Instant::now() + timeout;
+        if let Some(logger) = LOGGER.get().and_then(|logger| lock_before(logger, deadline)) {
+            let mut payload = logger.base_payload(Level::Error);
+            merge(&mut payload, panic_fields(info));
+            logger.report_panic(payload, deadline.saturating_duration_since(Instant::now()));
+        }
+        previous(info);
+    }));
+}
+
+/// Waits for other threads to finish logging, but gives up at `deadline`: the panicking thread may be
+/// the one holding the logger, and must not deadlock in the hook.
+fn lock_before(logger: &Mutex<POGRLogger>, deadline: Instant) -> Option<MutexGuard<'_, POGRLogger>> {
+    loop {
+        match logger.try_lock() {
+            Ok(logger) => return Some(logger),
+            // A thread panicked while logging; the logger itself is still usable.
+            Err(TryLockError::Poisoned(poisoned)) => return Some(poisoned.into_inner()),
+            Err(TryLockError::WouldBlock) if Instant::now() < deadline => thread::sleep(Duration::from_millis(1)),
+            Err(TryLockError::WouldBlock) => return None,
+        }
+    }
+}
+
+/// Builds the panic-specific part of the record.
+fn panic_fields(info: &PanicHookInfo) -> Value {
+    let message = if let Some(message) = info.payload().downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = info.payload().downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "Box<dyn Any>".to_string()
+    };
+    let location = info.location().map(|location| json!({
+        "file": location.file(),
+        "line": location.line(),
+        "column": location.column(),
+    }));
+
+    json!({
+        "type": "panic",
+        "log": message,
+        "data": {
+            "message": message,
+            "location": location,
+            "thread": thread::current().name().unwrap_or("<unnamed>"),
+            "backtrace": Backtrace::force_capture().to_string(),
+        },
+    })
+}
+
+fn merge(payload: &mut Value, fields: Value) {
+    if let (Value::Object(payload), Value::Object(fields)) = (payload, fields) {
+        payload.extend(fields);
+    }
+}
@@ -0,0 +1,251 @@
+//! Redaction of sensitive values from outgoing payloads.
+//!
+//! A `Redactor` runs over the final, enriched payload right before it is sent, so it sees the
+//! message string, `data`, `tags` and anything else the logger added. Rules select values by key
+//! name, by a regex over string values, or by JSON pointer, and each rule masks, hashes or drops
+//! what it selects.
+
+use regex::Regex;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+/// The placeholder written over masked values.
+pub const DEFAULT_MASK: &str = "[REDACTED]";
+
+/// What to do with a value selected by a redaction rule.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RedactAction {
+    /// Replace the value (or, for regex rules, the matched text) with the mask string.
+    Mask,
+    /// Replace the value (or matched text) with a salted SHA-256 hash, so that redacted values
+    /// can still be joined across records without revealing them.
+    Hash,
+    /// Remove the value from its object or array.
+    Drop,
+}
+
+/// A single rule selecting values to redact.
+#[derive(Clone, Debug)]
+pub enum RedactionRule {
+    /// Matches object keys by name, case-insensitively, at any depth.
+    Key { names: Vec<String>, action: RedactAction },
+    /// Matches string values, at any depth, that contain a match of the pattern.
+    Pattern { regex: Regex, action: RedactAction },
+    /// Matches the value at a JSON pointer (RFC 6901) from the payload root, e.g. `/data/user/ip`.
+    Path { pointer: String, action: RedactAction },
+}
+
+/// An ordered set of redaction rules applied to every outgoing payload.
+///
+/// Path rules run first, then key rules, then pattern rules.
+///
+/// # Examples
+/// ```
+/// use pogr_log_rs::redaction::{RedactAction, Redactor};
+/// use serde_json::json;
+///
+/// let redactor = Redactor::new()
+///     .salt("per-deployment-salt")
+///     .key(&["password", "token"], RedactAction::Mask)
+///     .key(&["email"], RedactAction::Hash)
+///     .pattern(r"\b\d{1,3}(\.\d{1,3}){3}\b", RedactAction::Mask).unwrap()
+///     .path("/data/session", RedactAction::Drop);
+///
+/// let mut payload = json!({"log": "login from 10.0.0.1", "data": {"password": "hunter2", "session": {}}});
+/// redactor.redact(&mut payload);
+/// assert_eq!(payload, json!({"log": "login from [REDACTED]", "data": {"password": "[REDACTED]"}}));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Redactor {
+    rules: Vec<RedactionRule>,
+    salt: String,
+    mask: String,
+}
+
+impl Default for Redactor {
+    fn default() -> Self {
+        Redactor::new()
+    }
+}
+
+impl Redactor {
+    /// Creates a redactor with no rules, an empty salt and the default mask.
+    pub fn new() -> Self {
+        Redactor {
+            rules: Vec::new(),
+            salt: String::new(),
+            mask: DEFAULT_MASK.to_string(),
+        }
+    }
+
+    /// Sets the salt prepended to values before hashing. Use the same salt across services to be
+    /// able to join on hashed values.
+    pub fn salt(mut self, salt: &str) -> Self {
+        self.salt = salt.to_string();
+        self
+    }
+
+    /// Sets the placeholder written over masked values.
+    pub fn mask(mut self, mask: &str) -> Self {
+        self.mask = mask.to_string();
+        self
+    }
+
+    /// Adds a rule matching object keys by name, case-insensitively.
+    pub fn key(mut self, names: &[&str], action: RedactAction) -> Self {
+        self.rules.push(RedactionRule::Key {
+            names: names.iter().map(|name| name.to_lowercase()).collect(),
+            action,
+        });
+        self
+    }
+
+    /// Adds a rule matching string values against a regex.
+    pub fn pattern(mut self, pattern: &str, action: RedactAction) -> Result<Self, regex::Error> {
+        self.rules.push(RedactionRule::Pattern { regex: Regex::new(pattern)?, action });
+        Ok(self)
+    }
+
+    /// Adds a rule matching the value at a JSON pointer.
+    pub fn path(mut self, pointer: &str, action: RedactAction) -> Self {
+        self.rules.push(RedactionRule::Path { pointer: pointer.to_string(), action });
+        self
+    }
+
+    /// Adds a prebuilt rule.
+    pub fn rule(mut self, rule: RedactionRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Applies every rule to the payload in place.
+    pub fn redact(&self, payload: &mut Value) {
+        for rule in &self.rules {
+            if let RedactionRule::Path { pointer, action } = rule {
+                self.redact_path(payload, pointer, *action);
+            }
+        }
+        for rule in &self.rules {
+            if let RedactionRule::Key { names, action } = rule {
+                self.redact_keys(payload, names, *action);
+            }
+        }
+        for rule in &self.rules {
+            if let RedactionRule::Pattern { regex, action } = rule {
+                self.redact_pattern(payload, regex, *action);
+            }
+        }
+    }
+
+    /// Returns the salted SHA-256 of a string as `sha256:<hex>`.
+    pub fn hash(&self, value: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.salt.as_bytes());
+        hasher.update(value.as_bytes());
+        let digest = hasher.finalize();
+        let hex: String = digest.iter().map(|byte| format!("{:02x}", byte)).collect();
+        format!("sha256:{}", hex)
+    }
+
+    /// The replacement for a whole value selected by a key or path rule.
+    fn replacement(&self, value: &Value, action: RedactAction) -> Value {
+        match action {
+            RedactAction::Hash => match value {
+                Value::String(s) => Value::String(self.hash(s)),
+                other => Value::String(self.hash(&other.to_string())),
+            },
+            _ => Value::String(self.mask.clone()),
+        }
+    }
+
+    fn redact_path(&self, payload: &mut Value, pointer: &str, action: RedactAction) {
+        if action != RedactAction::Drop {
+            if let Some(target) = payload.pointer_mut(pointer) {
+                *target = self.replacement(target, action);
+            }
+            return;
+        }
+
+        // Dropping needs the parent container and the final reference token.
+        let (parent, token) = match pointer.rfind('/') {
+            Some(split) => (&pointer[..split], unescape_token(&pointer[split + 1..])),
+            None => return,
+        };
+        match payload.pointer_mut(parent) {
+            Some(Value::Object(map)) => {
+                map.remove(&token);
+            },
+            Some(Value::Array(items)) => {
+                if let Ok(index) = token.parse::<usize>() {
+                    if index < items.len() {
+                        items.remove(index);
+                    }
+                }
+            },
+            _ => {},
+        }
+    }
+
+    fn redact_keys(&self, value: &mut Value, names: &[String], action: RedactAction) {
+        match value {
+            Value::Object(map) => {
+                if action == RedactAction::Drop {
+                    map.retain(|key, _| !names.contains(&key.to_lowercase()));
+                }
+                for (key, child) in map.iter_mut() {
+                    if names.contains(&key.to_lowercase()) {
+                        *child = self.replacement(child, action);
+                    } else {
+                        self.redact_keys(child, names, action);
+                    }
+                }
+            },
+            Value::Array(items) => {
+                for item in items {
+                    self.redact_keys(item, names, action);
+                }
+            },
+            _ => {},
+        }
+    }
+
+    fn redact_pattern(&self, value: &mut Value, regex: &Regex, action: RedactAction) {
+        match value {
+            Value::Object(map) => {
+                if action == RedactAction::Drop {
+                    map.retain(|_, child| !is_matching_string(child, regex));
+                }
+                for child in map.values_mut() {
+                    self.redact_pattern(child, regex, action);
+                }
+            },
+            Value::Array(items) => {
+                if action == RedactAction::Drop {
+                    items.retain(|child| !is_matching_string(child, regex));
+                }
+                for item in items {
+                    self.redact_pattern(item, regex, action);
+                }
+            },
+            Value::String(s) => {
+                let replaced = match action {
+                    RedactAction::Mask => regex.replace_all(s, self.mask.as_str()).into_owned(),
+                    RedactAction::Hash => regex.replace_all(s, |caps: &regex::Captures| self.hash(&caps[0])).into_owned(),
+                    // Matching strings were already removed by their parent container.
+                    RedactAction::Drop => return,
+                };
+                *s = replaced;
+            },
+            _ => {},
+        }
+    }
+}
+
+fn is_matching_string(value: &Value, regex: &Regex) -> bool {
+    matches!(value, Value::String(s) if regex.is_match(s))
+}
+
+/// Decodes `~1` and `~0` escapes in a JSON pointer reference token.
+fn unescape_token(token: &str) -> String {
+    token.replace("~1", "/").replace("~0", "~")
+}
@@ -0,0 +1,148 @@
+//! Structured reports of `std::error::Error` values and their `source()` chains.
+//!
+//! A report lists every cause with its message and type name, and carries a fingerprint derived
+//! from the type names only. Errors of the same kind therefore group together in POGR even when
+//! their messages contain ids, paths or other varying details.
+//!
+//! The reported error's own type name is taken from its static type, captured by `AsDynError` where
+//! it is reported from. Its sources, and errors reported as trait objects, are only known as
+//! `dyn Error`; common standard library and dependency error types are recognized among them, and
+//! other errors are named `unknown`.
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::backtrace::{Backtrace, BacktraceStatus};
+use std::error::Error;
+
+/// One error in a `source()` chain.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ErrorCause {
+    /// The error's `Display` output.
+    pub message: String,
+    /// The error's type name, or `unknown` for a source of a type that is not recognized.
+    #[serde(rename = "type")]
+    pub type_name: String,
+}
+
+/// A serializable report of an error and all of its causes.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ErrorReport {
+    /// The outermost error's message.
+    pub message: String,
+    /// The outermost error's type name.
+    #[serde(rename = "type")]
+    pub type_name: String,
+    /// The error followed by each of its sources, outermost first.
+    pub chain: Vec<ErrorCause>,
+    /// A stable hash of the chain's type names, for grouping incidents of the same kind.
+    pub fingerprint: String,
+    /// The backtrace at the point of reporting, when backtraces are enabled (`RUST_BACKTRACE`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backtrace: Option<String>,
+}
+
+impl ErrorReport {
+    /// Builds a report by walking the error's `source()` chain, naming the error after its static type.
+    pub fn new<E: AsDynError + ?Sized>(error: &E) -> Self {
+        ErrorReport::with_type_name(error.as_dyn_error(), error.type_name())
+    }
+
+    /// Builds a report, using `type_name` for the outermost error when the caller knows its concrete type.
+    pub fn with_type_name(error: &(dyn Error + 'static), type_name: Option<&str>) -> Self {
+        let mut chain = Vec::new();
+        let mut current = Some(error);
+        while let Some(cause) = current {
+            chain.push(ErrorCause { message: cause.to_string(), type_name: erased_type_name(cause) });
+            current = cause.source();
+        }
+        // A concrete type name is only useful if it is not itself a trait object or a box around one.
+        if let (Some(name), Some(outermost)) = (type_name, chain.first_mut()) {
+            if !name.contains("dyn ") {
+                outermost.type_name = name.to_string();
+            }
+        }
+
+        let mut hasher = Sha256::new();
+        for cause in &chain {
+            hasher.update(cause.type_name.as_bytes());
+            hasher.update(b"\n");
+        }
+        let fingerprint = hasher.finalize().iter().take(8).map(|byte| format!("{:02x}", byte)).collect();
+
+        let backtrace = Backtrace::capture();
+        let backtrace = match backtrace.status() {
+            BacktraceStatus::Captured => Some(backtrace.to_string()),
+            _ => None,
+        };
+
+        ErrorReport {
+            message: chain[0].message.clone(),
+            type_name: chain[0].type_name.clone(),
+            chain,
+            fingerprint,
+            backtrace,
+        }
+    }
+}
+
+/// Names the concrete type of a type-erased error, for the common error types recognized by downcasting.
+fn erased_type_name(error: &(dyn Error + 'static)) -> String {
+    macro_rules! known {
+        ($($ty:ty),* $(,)?) => {
+            $(
+                if error.is::<$ty>() {
+                    return std::any::type_name::<$ty>().to_string();
+                }
+            )*
+        };
+    }
+    known!(
+        std::io::Error,
+        std::fmt::Error,
+        std::num::ParseIntError,
+        std::num::ParseFloatError,
+        std::str::Utf8Error,
+        std::string::FromUtf8Error,
+        serde_json::Error,
+        reqwest::Error,
+    );
+    "unknown".to_string()
+}
+
+/// Errors that can be reported: any error type, and the `dyn Error` trait objects found in boxed errors.
+pub trait AsDynError {
+    fn as_dyn_error(&self) -> &(dyn Error + 'static);
+
+    /// The error's concrete type name, if it is known at the call site rather than erased behind `dyn Error`.
+    fn type_name(&self) -> Option<&'static str> {
+        None
+    }
+}
+
+impl<E: Error + 'static> AsDynError for E {
+    fn as_dyn_error(&self) -> &(dyn Error + 'static) {
+        self
+    }
+
+    fn type_name(&self) -> Option<&'static str> {
+        Some(std::any::type_name::<E>())
+    }
+}
+
+impl AsDynError for dyn Error + 'static {
+    fn as_dyn_error(&self) -> &(dyn Error + 'static) {
+        self
+    }
+}
+
+impl AsDynError for dyn Error + Send + 'static {
+    fn as_dyn_error(&self) -> &(dyn Error + 'static) {
+        self
+    }
+}
+
+impl AsDynError for dyn Error + Send + Sync + 'static {
+    fn as_dyn_error(&self) -> &(dyn Error + 'static) {
+        self
+    }
+}
@@ -0,0 +1,195 @@
+//! Sampling and rate limiting of log records before they are sent.
+//!
+//! A `Sampler` holds an ordered list of rules; the first rule whose matcher accepts a record decides
+//! whether it is kept. Each rule keeps separate state per key (the whole rule, the record's target, or
+//! its message template), so one noisy call site does not starve the others. When a record is kept,
+//! the number of records suppressed for the same key since the last kept one is attached to it as
+//! `_suppressed`, so dashboards can extrapolate the real volume.
+
+use log::Level;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// Selects the records a rule applies to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Matcher {
+    /// Every record.
+    Any,
+    /// Records of exactly this level.
+    Level(Level),
+    /// Records with this `type`.
+    Type(String),
+    /// Records whose target is this module path or one of its submodules.
+    Target(String),
+}
+
+impl Matcher {
+    fn matches(&self, input: &SampleInput) -> bool {
+        match self {
+            Matcher::Any => true,
+            Matcher::Level(level) => input.level == *level,
+            Matcher::Type(log_type) => input.log_type == Some(log_type.as_str()),
+            Matcher::Target(target) => {
+                input.target == target
+                    || (input.target.starts_with(target.as_str()) && input.target[target.len()..].starts_with("::"))
+            },
+        }
+    }
+}
+
+/// What a rule keeps its state per.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SampleKey {
+    /// One shared state for every record the rule matches.
+    Rule,
+    /// Separate state per record target.
+    Target,
+    /// Separate state per message template, i.e. per call site.
+    Template,
+}
+
+/// How a rule decides which records to keep.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SamplingPolicy {
+    /// Keeps this fraction of records, evenly spaced and starting with the first, so that a rare
+    /// record is seen at least once (e.g. `0.25` keeps the first, fifth, ninth... record).
+    Ratio(f64),
+    /// Token bucket: up to `burst` records at once, refilled at `per_second`.
+    RateLimit { per_second: f64, burst: u32 },
+    /// Keeps the first `first` records, then one in every `every`.
+    FirstThenEvery { first: u64, every: u64 },
+}
+
+/// A matcher, a policy and the key the policy's state is kept per.
+#[derive(Clone, Debug)]
+pub struct SamplingRule {
+    pub matcher: Matcher,
+    pub key: SampleKey,
+    pub policy: SamplingPolicy,
+}
+
+/// The parts of a record the sampler looks at.
+#[derive(Clone, Copy, Debug)]
+pub struct SampleInput<'a> {
+    pub level: Level,
+    pub target: &'a str,
+    /// Identifies the message template, e.g. `file:line` of the call site.
+    pub template: &'a str,
+    pub log_type: Option<&'a str>,
+}
+
+/// The outcome of sampling one record.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Decision {
+    /// Send the record; `suppressed` records for the same key were dropped since the last one sent.
+    Keep { suppressed: u64 },
+    /// Drop the record.
+    Drop,
+}
+
+/// Per-key state of a rule.
+struct KeyState {
+    seen: u64,
+    suppressed: u64,
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+/// Applies sampling rules to records. Records matched by no rule are always kept.
+pub struct Sampler {
+    rules: Vec<SamplingRule>,
+    state: Mutex<HashMap<(usize, String), KeyState>>,
+}
+
+impl Default for Sampler {
+    fn default() -> Self {
+        Sampler::new()
+    }
+}
+
+impl Sampler {
+    pub fn new() -> Self {
+        Sampler {
+            rules: Vec::new(),
+            state: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Adds a rule. Rules are tried in the order they were added.
+    pub fn rule(mut self, rule: SamplingRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Keeps a fixed fraction of the matching records.
+    pub fn ratio(self, matcher: Matcher, ratio: f64) -> Self {
+        self.rule(SamplingRule { matcher, key: SampleKey::Rule, policy: SamplingPolicy::Ratio(ratio) })
+    }
+
+    /// Rate limits the matching records with a token bucket per key.
+    pub fn rate_limit(self, matcher: Matcher, key: SampleKey, per_second: f64, burst: u32) -> Self {
+        self.rule(SamplingRule { matcher, key, policy: SamplingPolicy::RateLimit { per_second, burst } })
+    }
+
+    /// Keeps the first `first` matching records per key, then one in every `every`.
+    pub fn first_then_every(self, matcher: Matcher, key: SampleKey, first: u64, every: u64) -> Self {
+        self.rule(SamplingRule { matcher, key, policy: SamplingPolicy::FirstThenEvery { first, every } })
+    }
+
+    /// Decides whether a record is kept.
+    pub fn check(&self, input: &SampleInput) -> Decision {
+        let (index, rule) = match self.rules.iter().enumerate().find(|(_, rule)| rule.matcher.matches(input)) {
+            Some(found) => found,
+            None => return Decision::Keep { suppressed: 0 },
+        };
+        let key = match rule.key {
+            SampleKey::Rule => String::new(),
+            SampleKey::Target => input.target.to_string(),
+            SampleKey::Template => input.template.to_string(),
+        };
+
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap();
+        let entry = state.entry((index, key)).or_insert_with(|| KeyState {
+            seen: 0,
+            suppressed: 0,
+            tokens: match rule.policy {
+                SamplingPolicy::RateLimit { burst, .. } => f64::from(burst),
+                _ => 0.0,
+            },
+            refilled_at: now,
+        });
+        entry.seen += 1;
+
+        let keep = match rule.policy {
+            SamplingPolicy::Ratio(ratio) => {
+                // Keep the record whenever the running count of kept records ticks over, counting
+                // from zero so that the first record is kept.
+                let ratio = ratio.clamp(0.0, 1.0);
+                ((entry.seen - 1) as f64 * ratio).ceil() < (entry.seen as f64 * ratio).ceil()
+            },
+            SamplingPolicy::RateLimit { per_second, burst } => {
+                let elapsed = now.duration_since(entry.refilled_at).as_secs_f64();
+                entry.tokens = (entry.tokens + elapsed * per_second).min(f64::from(burst));
+                entry.refilled_at = now;
+                if entry.tokens >= 1.0 {
+                    entry.tokens -= 1.0;
+                    true
+                } else {
+                    false
+                }
+            },
+            SamplingPolicy::FirstThenEvery { first, every } => {
+                entry.seen <= first || (every > 0 && (entry.seen - first) % every == 0)
+            },
+        };
+
+        if keep {
+            Decision::Keep { suppressed: std::mem::take(&mut entry.suppressed) }
+        } else {
+            entry.suppressed += 1;
+            Decision::Drop
+        }
+    }
+}
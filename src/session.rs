@@ -0,0 +1,117 @@
+//! Session-token authentication for `ClientBuild` credentials.
+//!
+//! Client and build ids ship inside game clients and are effectively public. With session tokens
+//! enabled, the transport exchanges them at an auth endpoint for a short-lived bearer token and
+//! sends `Authorization: Bearer <token>` on intake calls instead of the `POGR_CLIENT` / `POGR_BUILD`
+//! headers. Tokens are cached per client/build id pair and exchanged again shortly before they expire.
+
+use reqwest::{Client, RequestBuilder};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+use crate::transport::DeliveryError;
+
+/// Configuration for the session-token exchange.
+#[derive(Clone, Debug)]
+pub struct SessionTokenConfig {
+    /// Endpoint that exchanges client/build ids for a bearer token.
+    pub auth_url: String,
+    /// How long before expiry a cached token is replaced.
+    pub refresh_before: Duration,
+}
+
+impl SessionTokenConfig {
+    /// Creates a configuration for the given auth endpoint, refreshing tokens a minute before expiry.
+    pub fn new(auth_url: &str) -> Self {
+        SessionTokenConfig {
+            auth_url: auth_url.to_string(),
+            refresh_before: Duration::from_secs(60),
+        }
+    }
+}
+
+/// The auth endpoint's answer to a token exchange.
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    /// Lifetime of the token in seconds.
+    expires_in: u64,
+}
+
+/// A cached bearer token and the moment it should be replaced.
+struct CachedToken {
+    token: String,
+    refresh_at: Instant,
+}
+
+/// Exchanges client/build ids for bearer tokens and caches the result.
+pub(crate) struct SessionTokens {
+    config: SessionTokenConfig,
+    // Keyed by client and build id, so that rotated credentials never reuse a token issued for
+    // other ids. Held across the exchange so that concurrent requests wait for one token instead of
+    // each starting their own exchange.
+    cached: Mutex<HashMap<(String, String), CachedToken>>,
+}
+
+impl SessionTokens {
+    pub(crate) fn new(config: SessionTokenConfig) -> Self {
+        SessionTokens {
+            config,
+            cached: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Adds a bearer token for the given client/build ids to the request, exchanging for a new
+    /// token if none is cached or the cached one is about to expire.
+    pub(crate) async fn authorize(
+        &self,
+        client: &Client,
+        client_id: &str,
+        build_id: &str,
+        req: RequestBuilder,
+    ) -> Result<RequestBuilder, DeliveryError> {
+        let mut cached = self.cached.lock().await;
+        let key = (client_id.to_string(), build_id.to_string());
+        let token = match cached.get(&key) {
+            Some(current) if Instant::now() < current.refresh_at => current.token.clone(),
+            _ => {
+                let fresh = self.exchange(client, client_id, build_id).await
+                    .map_err(|e| DeliveryError::TokenExchange(Box::new(e)))?;
+                let token = fresh.token.clone();
+                cached.insert(key, fresh);
+                token
+            }
+        };
+        Ok(req.bearer_auth(token))
+    }
+
+    /// Drops the cached tokens, forcing an exchange on the next request.
+    pub(crate) async fn invalidate(&self) {
+        self.cached.lock().await.clear();
+    }
+
+    async fn exchange(&self, client: &Client, client_id: &str, build_id: &str) -> Result<CachedToken, DeliveryError> {
+        let requested_at = Instant::now();
+        let response = client.post(&self.config.auth_url)
+            .json(&serde_json::json!({
+                "client_id": client_id,
+                "build_id": build_id,
+            }))
+            .send().await
+            .map_err(DeliveryError::Http)?;
+
+        if !response.status().is_success() {
+            return Err(DeliveryError::Status(response.status()));
+        }
+        let body: TokenResponse = response.json().await.map_err(DeliveryError::Http)?;
+
+        // Measure the lifetime from when the exchange started, so slow responses err on the early side.
+        let lifetime = Duration::from_secs(body.expires_in).saturating_sub(self.config.refresh_before);
+        Ok(CachedToken {
+            token: body.access_token,
+            refresh_at: requested_at + lifetime,
+        })
+    }
+}
@@ -0,0 +1,59 @@
+//! Keeping records that could not be delivered in an NDJSON file, to inspect or replay them later.
+//!
+//! Each line of a spool file is one payload, exactly as it would have been sent, plus a `timestamp`
+//! (milliseconds since the Unix epoch) if the payload had none: the time it was spooled, which for a
+//! failed delivery is within the send timeout of when it was logged. `pogr-log inspect` and
+//! `pogr-log replay` read these files; `POGRLogger::replay` sends their payloads again, timestamp
+//! included.
+//!
+//! # Examples
+//! ```no_run
+//! use pogr_log_rs::spool::Spool;
+//! use std::sync::Arc;
+//! # fn attach(logger: &mut pogr_log_rs::POGRLogger) -> std::io::Result<()> {
+//!
+//! let spool = Arc::new(Spool::open("/var/spool/game-server/pogr.ndjson")?);
+//! logger.on_delivery_error(move |_, payloads| {
+//!     for payload in payloads {
+//!         let _ = spool.append(payload);
+//!     }
+//! });
+//! # Ok(())
+//! # }
+//! ```
+
+use serde_json::Value;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::Mutex;
+
+use crate::epoch_millis;
+
+/// An NDJSON file that payloads are appended to.
+pub struct Spool {
+    file: Mutex<File>,
+}
+
+impl Spool {
+    /// Opens the file for appending, creating it if needed.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Spool { file: Mutex::new(file) })
+    }
+
+    /// Appends a payload as one line, adding a `timestamp` if it has none.
+    pub fn append(&self, payload: &Value) -> io::Result<()> {
+        let mut line = match payload {
+            Value::Object(fields) if !fields.contains_key("timestamp") => {
+                let mut stamped = fields.clone();
+                stamped.insert("timestamp".to_string(), epoch_millis().into());
+                serde_json::to_vec(&stamped)?
+            },
+            _ => serde_json::to_vec(payload)?,
+        };
+        line.push(b'\n');
+        // One write per line, so that concurrent appends never interleave within a line.
+        self.file.lock().unwrap().write_all(&line)
+    }
+}
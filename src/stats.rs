@@ -0,0 +1,247 @@
+//! Delivery statistics of a logger, see `POGRLogger::stats`.
+//!
+//! Counters start at zero when the logger is created and only grow. `LoggerStats::prometheus_text`
+//! renders a snapshot in the Prometheus text exposition format, for serving from a `/metrics`
+//! handler; with the `metrics` feature, `LoggerStats::record_metrics` publishes it through the
+//! `metrics` crate facade instead.
+//!
+//! # Examples
+//! ```no_run
+//! # fn example(logger: &pogr_log_rs::POGRLogger) {
+//! let stats = logger.stats();
+//! if stats.failed > 0 {
+//!     eprintln!("{} records failed to send, p99 latency {:?}", stats.failed, stats.send_latency_p99);
+//! }
+//! let body = stats.prometheus_text();
+//! # }
+//! ```
+
+use std::collections::VecDeque;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::circuit::{CircuitMetrics, CircuitState};
+use crate::endpoints::EndpointStatus;
+
+/// Number of recent send latencies the percentiles are computed from.
+const LATENCY_SAMPLES: usize = 1024;
+
+/// Records dropped before delivery, by reason.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DroppedRecords {
+    /// Repeats collapsed into a dedup summary.
+    pub deduplicated: u64,
+    /// Records over the total size limit that truncation could not shrink enough.
+    pub oversized: u64,
+    /// Records evicted from the circuit breaker's buffer while it was full.
+    pub buffer_full: u64,
+}
+
+impl DroppedRecords {
+    pub fn total(&self) -> u64 {
+        self.deduplicated + self.oversized + self.buffer_full
+    }
+}
+
+/// A snapshot of a logger's delivery statistics.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LoggerStats {
+    /// Records that passed the level check, plus records from `custom_log` and error and panic reports.
+    pub accepted: u64,
+    /// Records rejected by the level check.
+    pub filtered: u64,
+    /// Records dropped by the sampler.
+    pub sampled: u64,
+    pub dropped: DroppedRecords,
+    /// Payloads the intake accepted, including dedup summaries and replayed records.
+    pub sent: u64,
+    /// Additional requests made for a payload: after refreshing rejected credentials, or on the
+    /// next endpoint after a failure.
+    pub retried: u64,
+    /// Payloads given up on after a delivery error. Payloads the circuit breaker buffers only count
+    /// here if the logger is dropped before they get through.
+    pub failed: u64,
+    /// Request body bytes of delivered payloads, after compression.
+    pub bytes_sent: u64,
+    /// Payloads waiting to be sent: in flight, or held by an open circuit breaker.
+    pub queue_depth: usize,
+    /// Median time to deliver a payload, over the last 1024 deliveries.
+    pub send_latency_p50: Option<Duration>,
+    /// 99th percentile time to deliver a payload, over the last 1024 deliveries.
+    pub send_latency_p99: Option<Duration>,
+    /// The circuit breaker's state, if one is set.
+    pub circuit: Option<CircuitMetrics>,
+    /// Health of the intake endpoints.
+    pub endpoints: Vec<EndpointStatus>,
+}
+
+impl LoggerStats {
+    /// Renders the snapshot in the Prometheus text exposition format. All metric names start with `pogr_log_`.
+    pub fn prometheus_text(&self) -> String {
+        let mut out = String::new();
+        let counters = [
+            ("records_accepted_total", "Records accepted by the logger.", self.accepted),
+            ("records_filtered_total", "Records rejected by the level check.", self.filtered),
+            ("records_sampled_total", "Records dropped by the sampler.", self.sampled),
+            ("records_sent_total", "Payloads delivered to the intake.", self.sent),
+            ("records_retried_total", "Additional requests made for a payload.", self.retried),
+            ("records_failed_total", "Payloads whose delivery failed.", self.failed),
+            ("bytes_sent_total", "Request body bytes of delivered payloads.", self.bytes_sent),
+        ];
+        for (name, help, value) in counters {
+            header(&mut out, name, help, "counter");
+            let _ = writeln!(out, "pogr_log_{} {}", name, value);
+        }
+
+        header(&mut out, "records_dropped_total", "Records dropped before delivery.", "counter");
+        for (reason, value) in [
+            ("deduplicated", self.dropped.deduplicated),
+            ("oversized", self.dropped.oversized),
+            ("buffer_full", self.dropped.buffer_full),
+        ] {
+            let _ = writeln!(out, "pogr_log_records_dropped_total{{reason=\"{}\"}} {}", reason, value);
+        }
+
+        header(&mut out, "queue_depth", "Payloads waiting to be sent.", "gauge");
+        let _ = writeln!(out, "pogr_log_queue_depth {}", self.queue_depth);
+
+        // Percentiles over a window of recent deliveries, not a summary over all of them: plain gauges.
+        for (name, help, latency) in [
+            ("send_latency_p50_seconds", "Median time to deliver a payload over recent deliveries.", self.send_latency_p50),
+            ("send_latency_p99_seconds", "99th percentile time to deliver a payload over recent deliveries.", self.send_latency_p99),
+        ] {
+            if let Some(latency) = latency {
+                header(&mut out, name, help, "gauge");
+                let _ = writeln!(out, "pogr_log_{} {}", name, latency.as_secs_f64());
+            }
+        }
+
+        if let Some(circuit) = &self.circuit {
+            header(&mut out, "circuit_state", "Current state of the intake circuit breaker.", "gauge");
+            for state in [CircuitState::Closed, CircuitState::Open, CircuitState::HalfOpen] {
+                let _ = writeln!(
+                    out, "pogr_log_circuit_state{{state=\"{}\"}} {}", state, u8::from(circuit.state == state)
+                );
+            }
+            header(&mut out, "circuit_opened_total", "Times the intake circuit breaker opened.", "counter");
+            let _ = writeln!(out, "pogr_log_circuit_opened_total {}", circuit.opened);
+        }
+
+        header(&mut out, "endpoint_healthy", "Whether an intake endpoint is considered healthy.", "gauge");
+        for endpoint in &self.endpoints {
+            let _ = writeln!(
+                out, "pogr_log_endpoint_healthy{{url=\"{}\"}} {}", escape_label(&endpoint.url), u8::from(endpoint.healthy)
+            );
+        }
+        out
+    }
+
+    /// Publishes the snapshot through the `metrics` crate facade, to whatever recorder is installed.
+    /// Uses the same names as `prometheus_text`; call it periodically, or before each scrape.
+    #[cfg(feature = "metrics")]
+    pub fn record_metrics(&self) {
+        metrics::counter!("pogr_log_records_accepted_total").absolute(self.accepted);
+        metrics::counter!("pogr_log_records_filtered_total").absolute(self.filtered);
+        metrics::counter!("pogr_log_records_sampled_total").absolute(self.sampled);
+        metrics::counter!("pogr_log_records_sent_total").absolute(self.sent);
+        metrics::counter!("pogr_log_records_retried_total").absolute(self.retried);
+        metrics::counter!("pogr_log_records_failed_total").absolute(self.failed);
+        metrics::counter!("pogr_log_bytes_sent_total").absolute(self.bytes_sent);
+        metrics::counter!("pogr_log_records_dropped_total", "reason" => "deduplicated").absolute(self.dropped.deduplicated);
+        metrics::counter!("pogr_log_records_dropped_total", "reason" => "oversized").absolute(self.dropped.oversized);
+        metrics::counter!("pogr_log_records_dropped_total", "reason" => "buffer_full").absolute(self.dropped.buffer_full);
+        metrics::gauge!("pogr_log_queue_depth").set(self.queue_depth as f64);
+        if let Some(latency) = self.send_latency_p50 {
+            metrics::gauge!("pogr_log_send_latency_p50_seconds").set(latency.as_secs_f64());
+        }
+        if let Some(latency) = self.send_latency_p99 {
+            metrics::gauge!("pogr_log_send_latency_p99_seconds").set(latency.as_secs_f64());
+        }
+        if let Some(circuit) = &self.circuit {
+            for state in [CircuitState::Closed, CircuitState::Open, CircuitState::HalfOpen] {
+                metrics::gauge!("pogr_log_circuit_state", "state" => state.to_string())
+                    .set(f64::from(u8::from(circuit.state == state)));
+            }
+            metrics::counter!("pogr_log_circuit_opened_total").absolute(circuit.opened);
+        }
+        for endpoint in &self.endpoints {
+            metrics::gauge!("pogr_log_endpoint_healthy", "url" => endpoint.url.clone())
+                .set(f64::from(u8::from(endpoint.healthy)));
+        }
+    }
+}
+
+fn header(out: &mut String, name: &str, help: &str, kind: &str) {
+    let _ = writeln!(out, "# HELP pogr_log_{} {}", name, help);
+    let _ = writeln!(out, "# TYPE pogr_log_{} {}", name, kind);
+}
+
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// Counters shared by a logger and its send tasks.
+#[derive(Default)]
+pub(crate) struct StatsCollector {
+    pub(crate) accepted: AtomicU64,
+    pub(crate) filtered: AtomicU64,
+    pub(crate) sampled: AtomicU64,
+    pub(crate) deduplicated: AtomicU64,
+    pub(crate) oversized: AtomicU64,
+    pub(crate) sent: AtomicU64,
+    pub(crate) retried: AtomicU64,
+    pub(crate) failed: AtomicU64,
+    pub(crate) bytes_sent: AtomicU64,
+    latencies: Mutex<VecDeque<Duration>>,
+}
+
+impl StatsCollector {
+    pub(crate) fn count(counter: &AtomicU64) {
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records a delivered payload.
+    pub(crate) fn delivered(&self, bytes: usize, latency: Duration) {
+        Self::count(&self.sent);
+        self.bytes_sent.fetch_add(bytes as u64, Ordering::Relaxed);
+        let mut latencies = self.latencies.lock().unwrap();
+        if latencies.len() == LATENCY_SAMPLES {
+            latencies.pop_front();
+        }
+        latencies.push_back(latency);
+    }
+
+    /// The counters, with the fields owned by other parts of the logger left at their defaults.
+    pub(crate) fn snapshot(&self) -> LoggerStats {
+        let mut latencies: Vec<Duration> = self.latencies.lock().unwrap().iter().copied().collect();
+        latencies.sort_unstable();
+        LoggerStats {
+            accepted: self.accepted.load(Ordering::Relaxed),
+            filtered: self.filtered.load(Ordering::Relaxed),
+            sampled: self.sampled.load(Ordering::Relaxed),
+            dropped: DroppedRecords {
+                deduplicated: self.deduplicated.load(Ordering::Relaxed),
+                oversized: self.oversized.load(Ordering::Relaxed),
+                buffer_full: 0,
+            },
+            sent: self.sent.load(Ordering::Relaxed),
+            retried: self.retried.load(Ordering::Relaxed),
+            failed: self.failed.load(Ordering::Relaxed),
+            bytes_sent: self.bytes_sent.load(Ordering::Relaxed),
+            send_latency_p50: percentile(&latencies, 50),
+            send_latency_p99: percentile(&latencies, 99),
+            ..LoggerStats::default()
+        }
+    }
+}
+
+/// Nearest-rank percentile of sorted samples.
+fn percentile(sorted: &[Duration], percent: usize) -> Option<Duration> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = (sorted.len() * percent).div_ceil(100).max(1);
+    Some(sorted[rank - 1])
+}
@@ -0,0 +1,313 @@
+//! Capturing the payloads a `POGRLogger` would send, for tests in downstream crates. Needs the
+//! `testing` feature, usually enabled only under `[dev-dependencies]`.
+//!
+//! A logger with a capture attached goes through the usual enrichment, sampling, redaction and size
+//! limits, and then stores each final payload instead of posting it. Capturing happens on the
+//! logging thread, so records can be checked right after they are logged, without a Tokio runtime
+//! or a `flush`.
+//!
+//! For code that logs through the `log` facade, `capture()` installs a capturing logger as the
+//! global logger (once per process) and collects the records logged on the current thread until
+//! the returned guard is dropped. Parallel tests each see only their own records. The global
+//! functions such as `report_error` and `set_tag`, and the panic hook, use the capturing logger too.
+//!
+//! # Examples
+//! ```
+//! use log::Level;
+//! use pogr_log_rs::{assert_logged, pogr_info, testing};
+//!
+//! let capture = testing::capture();
+//! # if log::STATIC_MAX_LEVEL < log::LevelFilter::Info { return; }
+//! pogr_info!(type: "login", data: {"user_id": 123}, "User logged in");
+//!
+//! assert_logged!(capture, Level::Info, "login", data contains {"user_id": 123});
+//! assert_eq!(capture.records()[0]["log"], "User logged in");
+//! ```
+//!
+//! Records logged on other threads, including Tokio worker threads, are not seen by `capture()`;
+//! use `capture_logger` and pass the logger to the code under test instead.
+
+use log::{Level, LevelFilter};
+use once_cell::sync::OnceCell;
+use reqwest::Client;
+use serde_json::Value;
+use std::cell::RefCell;
+use std::marker::PhantomData;
+use std::ops::Deref;
+use std::sync::{Arc, Mutex};
+
+use crate::{LogConfig, LoggerConfig, POGRLogger};
+
+/// Payloads captured from a logger. Clones share the same records.
+#[derive(Clone, Default)]
+pub struct Capture {
+    records: Arc<Mutex<Vec<Value>>>,
+}
+
+impl Capture {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Makes `logger` store its payloads here instead of sending them.
+    pub fn attach(&self, logger: &mut POGRLogger) {
+        logger.capture = Some(Sink::Shared(self.clone()));
+    }
+
+    /// The captured payloads, in the order they were logged.
+    pub fn records(&self) -> Vec<Value> {
+        self.records.lock().unwrap().clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&self) {
+        self.records.lock().unwrap().clear();
+    }
+
+    /// The first captured payload matching the expectation.
+    pub fn find(&self, expected: &Expectation) -> Option<Value> {
+        self.records.lock().unwrap().iter().find(|record| expected.matches(record)).cloned()
+    }
+
+    /// Panics, listing what was captured, unless a payload matches the expectation.
+    #[track_caller]
+    pub fn assert_logged(&self, expected: &Expectation) {
+        if self.find(expected).is_none() {
+            panic!("no captured record matches {:?}\ncaptured:\n{}", expected, self.describe());
+        }
+    }
+
+    /// Panics, listing what was captured, if any payload matches the expectation.
+    #[track_caller]
+    pub fn assert_not_logged(&self, expected: &Expectation) {
+        if let Some(record) = self.find(expected) {
+            panic!("captured record matches {:?}: {}", expected, record);
+        }
+    }
+
+    fn push(&self, payload: Value) {
+        self.records.lock().unwrap().push(payload);
+    }
+
+    fn describe(&self) -> String {
+        let records = self.records.lock().unwrap();
+        if records.is_empty() {
+            return "  (nothing)".to_string();
+        }
+        records.iter().map(|record| format!("  {}", record)).collect::<Vec<_>>().join("\n")
+    }
+}
+
+/// What a captured payload must look like, see `assert_logged!`.
+///
+/// `data` and `tags` are matched by containment: every field of the expected object must be present
+/// with a matching value, and nested objects are compared the same way. Other values must be equal.
+#[derive(Clone, Debug, Default)]
+pub struct Expectation {
+    level: Option<Level>,
+    log_type: Option<String>,
+    message: Option<String>,
+    data: Option<Value>,
+    tags: Option<Value>,
+}
+
+impl Expectation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn level(mut self, level: Level) -> Self {
+        self.level = Some(level);
+        self
+    }
+
+    pub fn log_type(mut self, log_type: impl Into<String>) -> Self {
+        self.log_type = Some(log_type.into());
+        self
+    }
+
+    /// The exact message, as sent under `log`.
+    pub fn message(mut self, message: impl Into<String>) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+
+    pub fn data_contains(mut self, data: Value) -> Self {
+        self.data = Some(data);
+        self
+    }
+
+    pub fn tags_contains(mut self, tags: Value) -> Self {
+        self.tags = Some(tags);
+        self
+    }
+
+    pub fn matches(&self, payload: &Value) -> bool {
+        let level = self.level.map(|level| level.to_string().to_lowercase());
+        let checks = [
+            level.is_none_or(|level| payload["severity"] == level.as_str()),
+            self.log_type.as_ref().is_none_or(|log_type| payload["type"] == log_type.as_str()),
+            self.message.as_ref().is_none_or(|message| payload["log"] == message.as_str()),
+            self.data.as_ref().is_none_or(|data| contains(&payload["data"], data)),
+            self.tags.as_ref().is_none_or(|tags| contains(&payload["tags"], tags)),
+        ];
+        checks.iter().all(|check| *check)
+    }
+}
+
+/// Whether `actual` contains everything in `expected`.
+fn contains(actual: &Value, expected: &Value) -> bool {
+    match (actual, expected) {
+        (Value::Object(actual), Value::Object(expected)) => expected.iter()
+            .all(|(key, value)| actual.get(key).is_some_and(|found| contains(found, value))),
+        _ => actual == expected,
+    }
+}
+
+/// Where a capturing logger's payloads go.
+#[derive(Clone)]
+pub(crate) enum Sink {
+    Shared(Capture),
+    /// The innermost `ScopedCapture` of the logging thread, if any.
+    CurrentThread,
+}
+
+impl Sink {
+    pub(crate) fn record(&self, payload: Value) {
+        match self {
+            Sink::Shared(capture) => capture.push(payload),
+            Sink::CurrentThread => SCOPES.with(|scopes| {
+                if let Some(capture) = scopes.borrow().last() {
+                    capture.push(payload);
+                }
+            }),
+        }
+    }
+}
+
+thread_local! {
+    static SCOPES: RefCell<Vec<Capture>> = const { RefCell::new(Vec::new()) };
+}
+
+/// A logger that captures instead of sending, configured with `logger_config`, and its capture.
+pub fn capture_logger(logger_config: LoggerConfig) -> (POGRLogger, Capture) {
+    let mut logger = POGRLogger::new(
+        Client::new(),
+        Some("http://capture.invalid/v1/intake/logs".to_string()),
+        LogConfig::AccessKeys {
+            access_key: "test".to_string(),
+            secret_key: "test".to_string(),
+            logger_config: logger_config.clone(),
+        },
+        logger_config,
+    );
+    let capture = Capture::new();
+    capture.attach(&mut logger);
+    (logger, capture)
+}
+
+static GLOBAL: OnceCell<()> = OnceCell::new();
+
+/// Installs a capturing `POGRLogger` configured with `logger_config` as the global logger, for
+/// `capture`, the way `init_with_logger` does. Only the first call in a process has an effect, so
+/// call it before the first `capture` to use a configuration other than service and environment `test`.
+///
+/// # Panics
+/// Panics if a different global logger is already installed.
+pub fn install(logger_config: LoggerConfig) {
+    GLOBAL.get_or_init(|| {
+        let (mut logger, _) = capture_logger(logger_config);
+        logger.capture = Some(Sink::CurrentThread);
+        // The same path as `init_with_logger`, so that `report_error!`, `set_tag` and the panic hook
+        // reach the capture too.
+        crate::init_with_logger(logger, LevelFilter::Trace);
+    });
+}
+
+/// Captures the records logged on the current thread through the global logger until the guard is
+/// dropped. Installs the capturing global logger first if needed. Guards nest; the innermost one
+/// receives the records.
+pub fn capture() -> ScopedCapture {
+    install(LoggerConfig::new("test", "test"));
+    let capture = Capture::new();
+    SCOPES.with(|scopes| scopes.borrow_mut().push(capture.clone()));
+    ScopedCapture { capture, _not_send: PhantomData }
+}
+
+/// A capture of the current thread's records, see `capture`.
+pub struct ScopedCapture {
+    capture: Capture,
+    // Registered with the current thread, so it has to be dropped there.
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Deref for ScopedCapture {
+    type Target = Capture;
+
+    fn deref(&self) -> &Capture {
+        &self.capture
+    }
+}
+
+impl Drop for ScopedCapture {
+    fn drop(&mut self) {
+        SCOPES.with(|scopes| {
+            let mut scopes = scopes.borrow_mut();
+            if let Some(index) = scopes.iter().rposition(|capture| Arc::ptr_eq(&capture.records, &self.capture.records)) {
+                scopes.remove(index);
+            }
+        });
+    }
+}
+
+/// Asserts that a capture holds a record with the given level and, optionally, type, message,
+/// data and tags. `data` and `tags` are matched by containment, see `Expectation`.
+///
+/// ```
+/// # use log::Level;
+/// # use pogr_log_rs::{assert_logged, pogr_warn, testing};
+/// # use serde_json::json;
+/// let capture = testing::capture();
+/// pogr_warn!(type: "match", data: {"map": "harbor", "players": 10}, tags: {"region": "eu"}, "Match started");
+///
+/// assert_logged!(capture, Level::Warn);
+/// assert_logged!(capture, Level::Warn, "match");
+/// assert_logged!(capture, Level::Warn, "match", data contains {"map": "harbor"}, tags contains {"region": "eu"});
+/// assert_logged!(capture, Level::Warn, message: "Match started", data contains json!({"players": 10}));
+/// ```
+#[macro_export]
+macro_rules! assert_logged {
+    ($capture:expr, $level:expr $(, $($rest:tt)*)?) => {{
+        let expectation = $crate::testing::Expectation::new().level($level);
+        $crate::assert_logged!(@parts $capture, expectation, $($($rest)*)?)
+    }};
+
+    (@parts $capture:expr, $expectation:expr, data contains { $($data:tt)* } $(, $($rest:tt)*)?) => {
+        $crate::assert_logged!(@parts $capture, $expectation.data_contains($crate::__private::json!({ $($data)* })), $($($rest)*)?)
+    };
+    (@parts $capture:expr, $expectation:expr, data contains $data:expr $(, $($rest:tt)*)?) => {
+        $crate::assert_logged!(@parts $capture, $expectation.data_contains($data), $($($rest)*)?)
+    };
+    (@parts $capture:expr, $expectation:expr, tags contains { $($tags:tt)* } $(, $($rest:tt)*)?) => {
+        $crate::assert_logged!(@parts $capture, $expectation.tags_contains($crate::__private::json!({ $($tags)* })), $($($rest)*)?)
+    };
+    (@parts $capture:expr, $expectation:expr, tags contains $tags:expr $(, $($rest:tt)*)?) => {
+        $crate::assert_logged!(@parts $capture, $expectation.tags_contains($tags), $($($rest)*)?)
+    };
+    (@parts $capture:expr, $expectation:expr, message: $message:expr $(, $($rest:tt)*)?) => {
+        $crate::assert_logged!(@parts $capture, $expectation.message($message), $($($rest)*)?)
+    };
+    (@parts $capture:expr, $expectation:expr, $log_type:expr $(, $($rest:tt)*)?) => {
+        $crate::assert_logged!(@parts $capture, $expectation.log_type($log_type), $($($rest)*)?)
+    };
+    (@parts $capture:expr, $expectation:expr, ) => {
+        $capture.assert_logged(&$expectation)
+    };
+}
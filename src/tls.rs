@@ -0,0 +1,307 @@
+//! TLS settings for the client the logger uses to reach the intake.
+//!
+//! On-prem deployments usually sit behind an internal CA, may require clients to present a certificate
+//! (mutual TLS), and may want the connection to fail unless the server's public key is one they know.
+//! `TlsConfig` collects these settings and builds a `reqwest::Client` from them.
+//!
+//! Pins are SHA-256 digests of a certificate's DER-encoded SubjectPublicKeyInfo, base64-encoded, as
+//! printed by `openssl x509 -pubkey -noout | openssl pkey -pubin -outform der | openssl dgst -sha256 -binary | base64`.
+//! A connection is accepted if the server's own certificate matches a pin, and its chain is also valid
+//! for the configured roots. Other certificates the server sends are not checked against the pins:
+//! nothing ties them to the chain that was validated. Pinning needs the `rustls` feature.
+
+#[cfg(feature = "rustls")]
+use base64::Engine;
+use reqwest::Client;
+#[cfg(feature = "rustls")]
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::path::Path;
+
+/// Errors raised while building a client from a `TlsConfig`.
+#[derive(Debug)]
+pub enum TlsError {
+    /// A certificate or key file could not be read.
+    Io(std::io::Error),
+    /// A root or client certificate could not be parsed.
+    InvalidCertificate(String),
+    /// The client private key could not be parsed.
+    InvalidKey(String),
+    /// A pin is not the base64 encoding of a SHA-256 digest.
+    InvalidPin(String),
+    /// The HTTP client could not be built with these settings.
+    Client(reqwest::Error),
+}
+
+impl fmt::Display for TlsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TlsError::Io(e) => write!(f, "failed to read TLS file: {}", e),
+            TlsError::InvalidCertificate(reason) => write!(f, "invalid certificate: {}", reason),
+            TlsError::InvalidKey(reason) => write!(f, "invalid private key: {}", reason),
+            TlsError::InvalidPin(pin) => write!(f, "invalid SPKI pin: {}", pin),
+            TlsError::Client(e) => write!(f, "failed to build HTTP client: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for TlsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TlsError::Io(e) => Some(e),
+            TlsError::Client(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// TLS settings for the intake connection.
+///
+/// # Examples
+/// ```no_run
+/// use pogr_log_rs::tls::TlsConfig;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let tls = TlsConfig::new()
+///     .root_certificate_file("/etc/pogr/ca.pem")?
+///     .client_identity_files("/etc/pogr/client.pem", "/etc/pogr/client.key")?;
+/// #[cfg(feature = "rustls")]
+/// let tls = tls.pin_spki_sha256("47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=")?;
+/// let client = tls.build_client()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct TlsConfig {
+    /// Extra trusted roots, PEM-encoded.
+    root_certificates: Vec<Vec<u8>>,
+    /// Whether the platform's or bundled roots are trusted as well.
+    exclusive_roots: bool,
+    /// The client certificate chain and its private key, PEM-encoded.
+    client_identity: Option<(Vec<u8>, Vec<u8>)>,
+    /// Accepted SHA-256 digests of SubjectPublicKeyInfo.
+    #[cfg(feature = "rustls")]
+    spki_pins: Vec<[u8; 32]>,
+}
+
+impl TlsConfig {
+    /// Settings that trust the default roots, present no client certificate and pin nothing.
+    pub fn new() -> Self {
+        TlsConfig::default()
+    }
+
+    /// Trusts the certificates in a PEM bundle, in addition to the default roots.
+    pub fn add_root_certificate_pem(mut self, pem: &[u8]) -> Self {
+        self.root_certificates.push(pem.to_vec());
+        self
+    }
+
+    /// Trusts the certificates in a PEM file, in addition to the default roots.
+    pub fn root_certificate_file(self, path: impl AsRef<Path>) -> Result<Self, TlsError> {
+        let pem = std::fs::read(path).map_err(TlsError::Io)?;
+        Ok(self.add_root_certificate_pem(&pem))
+    }
+
+    /// Trusts only the roots added to this config, not the default ones.
+    pub fn only_added_roots(mut self) -> Self {
+        self.exclusive_roots = true;
+        self
+    }
+
+    /// Presents a client certificate for mutual TLS. `cert_chain` holds the certificate (followed by
+    /// any intermediates) and `private_key` its PKCS#8 key, both PEM-encoded.
+    pub fn client_identity_pem(mut self, cert_chain: &[u8], private_key: &[u8]) -> Self {
+        self.client_identity = Some((cert_chain.to_vec(), private_key.to_vec()));
+        self
+    }
+
+    /// Presents a client certificate for mutual TLS, read from PEM files.
+    pub fn client_identity_files(self, cert_chain: impl AsRef<Path>, private_key: impl AsRef<Path>) -> Result<Self, TlsError> {
+        let cert_chain = std::fs::read(cert_chain).map_err(TlsError::Io)?;
+        let private_key = std::fs::read(private_key).map_err(TlsError::Io)?;
+        Ok(self.client_identity_pem(&cert_chain, &private_key))
+    }
+
+    /// Accepts servers whose own certificate has a SubjectPublicKeyInfo with this base64-encoded
+    /// SHA-256 digest. Once any pin is set, connections to servers matching none of them fail.
+    #[cfg(feature = "rustls")]
+    pub fn pin_spki_sha256(mut self, pin: &str) -> Result<Self, TlsError> {
+        let digest = base64::engine::general_purpose::STANDARD
+            .decode(pin.trim())
+            .ok()
+            .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+            .ok_or_else(|| TlsError::InvalidPin(pin.to_string()))?;
+        self.spki_pins.push(digest);
+        Ok(self)
+    }
+
+    /// Builds an HTTP client with these settings.
+    pub fn build_client(&self) -> Result<Client, TlsError> {
+        self.configure(Client::builder())?.build().map_err(TlsError::Client)
+    }
+
+    /// Applies these settings to a client builder.
+    pub fn configure(&self, builder: reqwest::ClientBuilder) -> Result<reqwest::ClientBuilder, TlsError> {
+        #[cfg(feature = "rustls")]
+        return Ok(builder.use_preconfigured_tls(rustls_backend::client_config(self)?));
+
+        #[cfg(not(feature = "rustls"))]
+        return self.configure_native(builder);
+    }
+
+    /// Applies the settings through reqwest's own options, for backends other than rustls.
+    #[cfg(not(feature = "rustls"))]
+    fn configure_native(&self, builder: reqwest::ClientBuilder) -> Result<reqwest::ClientBuilder, TlsError> {
+        #[cfg(feature = "native-tls")]
+        {
+            let mut builder = builder.tls_built_in_root_certs(!self.exclusive_roots);
+            for pem in &self.root_certificates {
+                let certs = reqwest::Certificate::from_pem_bundle(pem)
+                    .map_err(|e| TlsError::InvalidCertificate(e.to_string()))?;
+                for cert in certs {
+                    builder = builder.add_root_certificate(cert);
+                }
+            }
+            if let Some((cert_chain, private_key)) = &self.client_identity {
+                let identity = reqwest::Identity::from_pkcs8_pem(cert_chain, private_key)
+                    .map_err(|e| TlsError::InvalidKey(e.to_string()))?;
+                builder = builder.identity(identity);
+            }
+            Ok(builder)
+        }
+        // Without a TLS backend there is nothing to apply the settings to.
+        #[cfg(not(feature = "native-tls"))]
+        Ok(builder)
+    }
+}
+
+/// Computes the pin of a PEM-encoded certificate: the base64 SHA-256 digest of its SubjectPublicKeyInfo.
+#[cfg(feature = "rustls")]
+pub fn spki_sha256_pin(cert_pem: &[u8]) -> Result<String, TlsError> {
+    let der = first_pem_certificate(cert_pem)?;
+    let spki = subject_public_key_info(&der)
+        .ok_or_else(|| TlsError::InvalidCertificate("malformed certificate".to_string()))?;
+    Ok(base64::engine::general_purpose::STANDARD.encode(Sha256::digest(spki)))
+}
+
+#[cfg(feature = "rustls")]
+fn first_pem_certificate(pem: &[u8]) -> Result<Vec<u8>, TlsError> {
+    let mut reader = std::io::BufReader::new(pem);
+    loop {
+        match rustls_pemfile::read_one(&mut reader).map_err(TlsError::Io)? {
+            Some(rustls_pemfile::Item::X509Certificate(der)) => return Ok(der),
+            Some(_) => continue,
+            None => return Err(TlsError::InvalidCertificate("no certificate found in PEM".to_string())),
+        }
+    }
+}
+
+/// Finds the DER-encoded SubjectPublicKeyInfo in a DER-encoded X.509 certificate.
+#[cfg(feature = "rustls")]
+fn subject_public_key_info(cert: &[u8]) -> Option<&[u8]> {
+    let (_, certificate) = x509_parser::parse_x509_certificate(cert).ok()?;
+    Some(certificate.tbs_certificate.subject_pki.raw)
+}
+
+#[cfg(feature = "rustls")]
+mod rustls_backend {
+    use super::{first_pem_certificate, subject_public_key_info, TlsConfig, TlsError};
+    use rustls::client::{ServerCertVerified, ServerCertVerifier, WebPkiVerifier};
+    use rustls::{Certificate, ClientConfig, OwnedTrustAnchor, PrivateKey, RootCertStore, ServerName};
+    use sha2::{Digest, Sha256};
+    use std::sync::Arc;
+    use std::time::SystemTime;
+
+    /// Builds the rustls configuration handed to reqwest.
+    pub(super) fn client_config(config: &TlsConfig) -> Result<ClientConfig, TlsError> {
+        let mut roots = RootCertStore::empty();
+        if !config.exclusive_roots {
+            roots.add_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.iter().map(|anchor| {
+                OwnedTrustAnchor::from_subject_spki_name_constraints(anchor.subject, anchor.spki, anchor.name_constraints)
+            }));
+        }
+        for pem in &config.root_certificates {
+            for der in certificates(pem)? {
+                roots.add(&der).map_err(|e| TlsError::InvalidCertificate(e.to_string()))?;
+            }
+        }
+
+        let builder = ClientConfig::builder().with_safe_defaults();
+        let mut client_config = if config.spki_pins.is_empty() {
+            let builder = builder.with_root_certificates(roots);
+            match &config.client_identity {
+                Some((cert_chain, private_key)) => builder
+                    .with_client_auth_cert(certificates(cert_chain)?, key(private_key)?)
+                    .map_err(|e| TlsError::InvalidKey(e.to_string()))?,
+                None => builder.with_no_client_auth(),
+            }
+        } else {
+            let verifier = PinnedVerifier {
+                inner: WebPkiVerifier::new(roots, None),
+                pins: config.spki_pins.clone(),
+            };
+            let builder = builder.with_custom_certificate_verifier(Arc::new(verifier));
+            match &config.client_identity {
+                Some((cert_chain, private_key)) => builder
+                    .with_client_auth_cert(certificates(cert_chain)?, key(private_key)?)
+                    .map_err(|e| TlsError::InvalidKey(e.to_string()))?,
+                None => builder.with_no_client_auth(),
+            }
+        };
+        // The same protocols reqwest offers with its own rustls configuration.
+        client_config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+        Ok(client_config)
+    }
+
+    fn certificates(pem: &[u8]) -> Result<Vec<Certificate>, TlsError> {
+        let ders = rustls_pemfile::certs(&mut std::io::BufReader::new(pem)).map_err(TlsError::Io)?;
+        if ders.is_empty() {
+            // Surfaces the same error as `spki_sha256_pin` for inputs without any certificate.
+            first_pem_certificate(pem)?;
+        }
+        Ok(ders.into_iter().map(Certificate).collect())
+    }
+
+    fn key(pem: &[u8]) -> Result<PrivateKey, TlsError> {
+        let mut reader = std::io::BufReader::new(pem);
+        loop {
+            match rustls_pemfile::read_one(&mut reader).map_err(TlsError::Io)? {
+                Some(rustls_pemfile::Item::PKCS8Key(der))
+                | Some(rustls_pemfile::Item::RSAKey(der))
+                | Some(rustls_pemfile::Item::ECKey(der)) => return Ok(PrivateKey(der)),
+                Some(_) => continue,
+                None => return Err(TlsError::InvalidKey("no private key found in PEM".to_string())),
+            }
+        }
+    }
+
+    /// Verifies the chain as usual, then requires the end-entity certificate to match a pin.
+    ///
+    /// The intermediates are whatever the server chose to send, not necessarily the chain the inner
+    /// verifier built, so a match among them would prove nothing.
+    struct PinnedVerifier {
+        inner: WebPkiVerifier,
+        pins: Vec<[u8; 32]>,
+    }
+
+    impl ServerCertVerifier for PinnedVerifier {
+        fn verify_server_cert(
+            &self,
+            end_entity: &Certificate,
+            intermediates: &[Certificate],
+            server_name: &ServerName,
+            scts: &mut dyn Iterator<Item = &[u8]>,
+            ocsp_response: &[u8],
+            now: SystemTime,
+        ) -> Result<ServerCertVerified, rustls::Error> {
+            let verified = self.inner.verify_server_cert(end_entity, intermediates, server_name, scts, ocsp_response, now)?;
+            let pinned = subject_public_key_info(&end_entity.0)
+                .is_some_and(|spki| self.pins.iter().any(|pin| pin[..] == Sha256::digest(spki)[..]));
+            if pinned {
+                Ok(verified)
+            } else {
+                Err(rustls::Error::General("server certificate does not match a pinned public key".to_string()))
+            }
+        }
+    }
+}
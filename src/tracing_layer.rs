@@ -0,0 +1,147 @@
+//! Sending `tracing` events through a `POGRLogger`. Needs the `tracing` feature.
+//!
+//! `PogrLayer` is a `tracing_subscriber` layer that turns each event into a log record and hands it
+//! to a logger, which enriches, samples, deduplicates and sends it like any record from the `log`
+//! facade. The event's `message` becomes `log`, a `pogr.r#type` field (`type` being a keyword, or
+//! `"pogr.type"` after another field) becomes `type`, and every other field is added to `data`.
+//! Fields of the spans the event is in are not included.
+//!
+//! Do not combine the layer with `tracing`'s own `log` feature, or a `tracing-log` bridge in the
+//! other direction, while the global logger is a `POGRLogger`: events would be sent twice, or
+//! forwarded back and forth.
+//!
+//! # Examples
+//! ```no_run
+//! use pogr_log_rs::tracing_layer::PogrLayer;
+//! use tracing_subscriber::layer::SubscriberExt;
+//!
+//! // With `init_logger` called beforehand, events go to the global logger.
+//! let subscriber = tracing_subscriber::registry().with(PogrLayer::new());
+//! tracing::subscriber::set_global_default(subscriber).unwrap();
+//!
+//! tracing::info!(pogr.r#type = "login", user_id = 123, "User logged in");
+//! ```
+
+use log::kv::Value as KvValue;
+use log::{Level, Log, Record};
+use serde_json::Value;
+use std::fmt;
+use std::sync::Arc;
+use tracing_core::field::{Field, Visit};
+use tracing_core::{Event, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+use crate::{LoggerFn, POGRLogger, LOGGER};
+
+/// A `tracing_subscriber` layer that logs events through a `POGRLogger`.
+pub struct PogrLayer {
+    /// The logger events go to, or `None` for the global logger.
+    logger: Option<Arc<POGRLogger>>,
+}
+
+impl PogrLayer {
+    /// A layer for the global logger installed with `init_logger` or `init_with_logger`. Events are
+    /// ignored while no logger is installed, and filtered by the level it was installed with.
+    pub fn new() -> Self {
+        PogrLayer { logger: None }
+    }
+
+    /// A layer with a logger of its own, which does not need to be the global logger.
+    pub fn with_logger(logger: POGRLogger) -> Self {
+        PogrLayer { logger: Some(Arc::new(logger)) }
+    }
+
+    /// Hands a record to the layer's logger.
+    fn log(&self, record: &Record) {
+        match &self.logger {
+            Some(logger) => logger.log(record),
+            None => {
+                if LOGGER.get().is_some() && record.level() <= log::max_level() {
+                    LoggerFn.log(record);
+                }
+            },
+        }
+    }
+}
+
+impl Default for PogrLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: Subscriber> Layer<S> for PogrLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        let mut fields = EventFields::default();
+        event.record(&mut fields);
+
+        let key_values: Vec<(&str, KvValue)> = fields.key_values.iter()
+            .map(|(key, value)| (key.as_str(), KvValue::from_serde(value)))
+            .collect();
+        self.log(&Record::builder()
+            .args(format_args!("{}", fields.message))
+            .level(level(metadata.level()))
+            .target(metadata.target())
+            .module_path(metadata.module_path())
+            .file(metadata.file())
+            .line(metadata.line())
+            .key_values(&key_values)
+            .build());
+    }
+}
+
+/// The `log` level of a `tracing` level.
+fn level(level: &tracing_core::Level) -> Level {
+    match *level {
+        tracing_core::Level::ERROR => Level::Error,
+        tracing_core::Level::WARN => Level::Warn,
+        tracing_core::Level::INFO => Level::Info,
+        tracing_core::Level::DEBUG => Level::Debug,
+        tracing_core::Level::TRACE => Level::Trace,
+    }
+}
+
+/// Collects an event's message and its other fields, which become key-values of the record.
+#[derive(Default)]
+struct EventFields {
+    message: String,
+    key_values: Vec<(String, Value)>,
+}
+
+impl EventFields {
+    fn record(&mut self, field: &Field, value: Value) {
+        match (field.name(), value) {
+            ("message", Value::String(message)) => self.message = message,
+            // `tracing` keeps the `r#` of a raw identifier in the field name.
+            ("pogr.r#type", value) => self.key_values.push(("pogr.type".to_string(), value)),
+            (name, value) => self.key_values.push((name.to_string(), value)),
+        }
+    }
+}
+
+impl Visit for EventFields {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.record(field, value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.record(field, value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.record(field, value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.record(field, value.into());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.record(field, value.into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.record(field, format!("{:?}", value).into());
+    }
+}
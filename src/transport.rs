@@ -0,0 +1,565 @@
+//! HTTP delivery of enriched log payloads to the POGR intake.
+
+use reqwest::{Client, RequestBuilder, StatusCode};
+use serde_json::Value;
+use std::fmt;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::circuit::{Admission, CircuitBreaker, Failure};
+use crate::credentials::{CredentialError, CredentialProvider, Credentials};
+use crate::endpoints::EndpointSet;
+use crate::error::LogError;
+use crate::hooks::{DropReason, Hooks};
+#[cfg(feature = "otlp")]
+use crate::otlp::OtlpExporter;
+use crate::session::SessionTokens;
+use crate::stats::StatsCollector;
+#[cfg(feature = "testing")]
+use crate::testing::Sink;
+use crate::ErrorCallback;
+
+/// Errors raised while delivering a payload to the intake.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum DeliveryError {
+    /// The credential provider could not supply credentials, so nothing was sent.
+    Credentials(CredentialError),
+    /// The request failed before a response was received.
+    Http(reqwest::Error),
+    /// The intake answered with a non-success status code.
+    Status(StatusCode),
+    /// Exchanging client/build ids for a session token failed.
+    TokenExchange(Box<DeliveryError>),
+    /// A blocking send did not complete in time.
+    Timeout,
+    /// The payload could not be encoded for sending.
+    Encoding(std::io::Error),
+    /// The circuit breaker is open; the record was buffered instead of sent. Passed to the delivery
+    /// error hook for records still buffered when the logger is dropped.
+    CircuitOpen,
+}
+
+impl fmt::Display for DeliveryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeliveryError::Credentials(e) => write!(f, "{}", e),
+            DeliveryError::Http(e) => write!(f, "{}", e),
+            DeliveryError::Status(status) => write!(f, "HTTP Error: {}", status),
+            DeliveryError::TokenExchange(e) => write!(f, "token exchange failed: {}", e),
+            DeliveryError::Timeout => write!(f, "timed out waiting for delivery"),
+            DeliveryError::Encoding(e) => write!(f, "failed to encode payload: {}", e),
+            DeliveryError::CircuitOpen => write!(f, "intake circuit breaker is open; record buffered"),
+        }
+    }
+}
+
+impl std::error::Error for DeliveryError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DeliveryError::Credentials(e) => Some(e),
+            DeliveryError::Http(e) => Some(e),
+            DeliveryError::Status(_) => None,
+            DeliveryError::TokenExchange(e) => Some(e.as_ref()),
+            DeliveryError::Timeout => None,
+            DeliveryError::Encoding(e) => Some(e),
+            DeliveryError::CircuitOpen => None,
+        }
+    }
+}
+
+impl DeliveryError {
+    /// Whether the intake or the auth endpoint rejected the credentials (`401` or `403`, after
+    /// refreshing them once), or the credential provider could not supply any.
+    pub fn is_auth_failure(&self) -> bool {
+        match self {
+            DeliveryError::Credentials(_) => true,
+            DeliveryError::Status(status) => *status == StatusCode::UNAUTHORIZED || *status == StatusCode::FORBIDDEN,
+            DeliveryError::TokenExchange(e) => e.is_auth_failure(),
+            DeliveryError::Http(_) | DeliveryError::Timeout | DeliveryError::Encoding(_) | DeliveryError::CircuitOpen => false,
+        }
+    }
+
+    /// Whether the failure points at the intake being unreachable or overloaded, rather than at the
+    /// record or the credentials. Only these count towards opening the circuit breaker.
+    pub(crate) fn is_endpoint_failure(&self) -> bool {
+        match self {
+            DeliveryError::Http(_) | DeliveryError::Timeout => true,
+            DeliveryError::Status(status) => status.is_server_error() || *status == StatusCode::TOO_MANY_REQUESTS,
+            DeliveryError::TokenExchange(e) => e.is_endpoint_failure(),
+            DeliveryError::Credentials(_) | DeliveryError::Encoding(_) | DeliveryError::CircuitOpen => false,
+        }
+    }
+}
+
+/// Everything needed to deliver a payload, cloned into each send task.
+#[derive(Clone)]
+pub(crate) struct Transport {
+    pub(crate) client: Client,
+    pub(crate) endpoints: Arc<EndpointSet>,
+    pub(crate) credentials: Arc<dyn CredentialProvider>,
+    pub(crate) session: Option<Arc<SessionTokens>>,
+    pub(crate) in_flight: Arc<InFlight>,
+    /// The logger's own runtime, if it has one; otherwise tasks go to the caller's runtime.
+    pub(crate) runtime: Option<tokio::runtime::Handle>,
+    /// Whether request bodies are gzip-compressed.
+    #[cfg(feature = "compression")]
+    pub(crate) compress: bool,
+    pub(crate) circuit: Option<Arc<CircuitBreaker>>,
+    pub(crate) stats: Arc<StatsCollector>,
+    pub(crate) hooks: Hooks,
+    /// Receives circuit breaker transitions; stderr is used without one.
+    pub(crate) error_callback: Option<Arc<ErrorCallback>>,
+    /// Receives a copy of every delivered payload, see `otlp`.
+    #[cfg(feature = "otlp")]
+    pub(crate) otlp: Option<Arc<OtlpExporter>>,
+    /// Takes the payloads instead of the intake, see `testing`.
+    #[cfg(feature = "testing")]
+    pub(crate) capture: Option<Sink>,
+}
+
+impl Transport {
+    /// Sends a payload from a background task, counting it as in flight until the send completes.
+    pub(crate) fn spawn(&self, payload: Value) {
+        #[cfg(feature = "testing")]
+        if let Some(sink) = &self.capture {
+            return self.capture(sink, payload);
+        }
+        let send = self.send_task(payload);
+        self.spawn_task(send);
+    }
+
+    /// Like `spawn`, but on the given runtime, for callers that may be on a thread without one.
+    pub(crate) fn spawn_on(&self, runtime: &tokio::runtime::Handle, payload: Value) {
+        #[cfg(feature = "testing")]
+        if let Some(sink) = &self.capture {
+            return self.capture(sink, payload);
+        }
+        runtime.spawn(self.send_task(payload));
+    }
+
+    /// The task that delivers a payload, counted as in flight from now until it completes.
+    fn send_task(&self, payload: Value) -> impl Future<Output = ()> + Send + 'static {
+        // Taken before spawning so that a flush right after this call already waits for the payload.
+        let guard = self.in_flight.start();
+        let transport = self.clone();
+        async move {
+            // The result is ignored since we don't handle response or errors here.
+            let _ = transport.deliver(payload).await;
+            drop(guard);
+        }
+    }
+
+    /// Runs a task on the logger's runtime if it has one, or else on the caller's Tokio runtime.
+    pub(crate) fn spawn_task(&self, task: impl Future<Output = ()> + Send + 'static) {
+        match &self.runtime {
+            Some(runtime) => {
+                runtime.spawn(task);
+            },
+            // Without `async-tokio`, every logger is created with a runtime of its own.
+            #[cfg(feature = "async-tokio")]
+            None => {
+                tokio::spawn(task);
+            },
+            #[cfg(not(feature = "async-tokio"))]
+            None => unreachable!("logger has no runtime"),
+        }
+    }
+
+    /// The runtime timers and follow-up sends can run on: the logger's own, or else the caller's if
+    /// there is one.
+    pub(crate) fn runtime_handle(&self) -> Option<tokio::runtime::Handle> {
+        self.runtime.clone().or_else(|| tokio::runtime::Handle::try_current().ok())
+    }
+
+    /// Sends a payload and blocks the calling thread until it was delivered or `timeout` passed.
+    ///
+    /// The send runs on the logger's runtime, or on a dedicated thread with its own runtime if the
+    /// logger has none, so this works from any thread, including one that is itself driving a Tokio
+    /// runtime (as a panicking task would be).
+    pub(crate) fn send_blocking(&self, payload: Value, timeout: Duration) -> Result<(), DeliveryError> {
+        #[cfg(feature = "testing")]
+        if let Some(sink) = &self.capture {
+            self.capture(sink, payload);
+            return Ok(());
+        }
+        let guard = self.in_flight.start();
+        // Kept for the delivery error hook in case the send does not finish in time.
+        let pending = self.hooks.handles_delivery_errors().then(|| payload.clone());
+        // Set by whichever gives up on the payload first: the send, or the wait timing out.
+        let given_up = Arc::new(AtomicBool::new(false));
+        let transport = self.clone();
+        let abandoned = given_up.clone();
+        let (done, result) = std::sync::mpsc::channel();
+        let send = async move {
+            let result = transport.try_deliver(payload).await.map_err(|(error, rejected)| {
+                if let Some(payload) = rejected {
+                    if !abandoned.swap(true, Ordering::SeqCst) {
+                        transport.give_up(&error, &payload);
+                    }
+                }
+                error
+            });
+            let _ = done.send(result);
+            drop(guard);
+        };
+        match &self.runtime {
+            Some(runtime) => {
+                runtime.spawn(send);
+            },
+            None => {
+                let spawned = std::thread::Builder::new()
+                    .name("pogr-log-flush".to_string())
+                    .spawn(move || {
+                        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build();
+                        if let Ok(runtime) = runtime {
+                            runtime.block_on(send);
+                        }
+                    });
+                if spawned.is_err() {
+                    return Err(DeliveryError::Timeout);
+                }
+            },
+        }
+        result.recv_timeout(timeout).unwrap_or_else(|_| {
+            // The caller stops waiting, typically to exit, so the payload counts as given up even
+            // though the send goes on.
+            let error = DeliveryError::Timeout;
+            if !given_up.swap(true, Ordering::SeqCst) {
+                StatsCollector::count(&self.stats.failed);
+                if let Some(payload) = pending {
+                    self.hooks.delivery_failed(&error, &[payload]);
+                }
+            }
+            Err(error)
+        })
+    }
+
+    /// Sends a payload through the circuit breaker, if there is one: while it is open, the payload is
+    /// buffered and `DeliveryError::CircuitOpen` returned without contacting the intake.
+    ///
+    /// A payload that is not delivered and not kept for later is given up: counted as failed and
+    /// passed to the delivery error hook.
+    pub(crate) async fn deliver(&self, payload: Value) -> Result<(), DeliveryError> {
+        self.try_deliver(payload).await.map_err(|(error, rejected)| {
+            if let Some(payload) = rejected {
+                self.give_up(&error, &payload);
+            }
+            error
+        })
+    }
+
+    /// Like `deliver`, but hands a payload that is not kept for later back to the caller to give up on.
+    async fn try_deliver(&self, payload: Value) -> Result<(), (DeliveryError, Option<Value>)> {
+        #[cfg(feature = "testing")]
+        if let Some(sink) = &self.capture {
+            self.capture(sink, payload);
+            return Ok(());
+        }
+        let circuit = match &self.circuit {
+            Some(circuit) => circuit,
+            None => return self.send(&payload).await.map_err(|error| (error, Some(payload))),
+        };
+        match circuit.admit(payload) {
+            Admission::Send { payload, probe, transition } => {
+                if let Some(transition) = transition {
+                    self.report(LogError::Circuit(transition));
+                }
+                self.send_through(circuit, payload, probe).await
+            },
+            Admission::Buffered { evicted } => {
+                if let Some(evicted) = evicted {
+                    self.hooks.dropped(DropReason::BufferFull, &evicted);
+                }
+                Err((DeliveryError::CircuitOpen, None))
+            },
+        }
+    }
+
+    /// Sends an admitted payload and feeds the outcome back to the circuit breaker. A failed payload
+    /// is handed back unless the circuit breaker buffered it.
+    async fn send_through(&self, circuit: &Arc<CircuitBreaker>, payload: Value, probe: bool) -> Result<(), (DeliveryError, Option<Value>)> {
+        let error = match self.send(&payload).await {
+            Err(error) if error.is_endpoint_failure() => error,
+            result => {
+                if let Some((transition, replay)) = circuit.on_success(probe) {
+                    self.report(LogError::Circuit(transition));
+                    for payload in replay {
+                        self.spawn(payload);
+                    }
+                }
+                return result.map_err(|error| (error, Some(payload)));
+            },
+        };
+        match circuit.on_failure(payload, probe) {
+            Failure::Buffered { transition, evicted } => {
+                if let Some(evicted) = evicted {
+                    self.hooks.dropped(DropReason::BufferFull, &evicted);
+                }
+                if let Some(transition) = transition {
+                    self.report(LogError::Circuit(transition));
+                    self.schedule_probe(circuit.clone());
+                }
+                Err((error, None))
+            },
+            Failure::Rejected { payload } => Err((error, Some(payload))),
+        }
+    }
+
+    /// Counts a payload that will not be delivered as failed and passes it to the delivery error hook.
+    pub(crate) fn give_up(&self, error: &DeliveryError, payload: &Value) {
+        StatsCollector::count(&self.stats.failed);
+        self.hooks.delivery_failed(error, std::slice::from_ref(payload));
+    }
+
+    /// Sends a probe once the circuit has been open for its `open_duration`, so that buffered
+    /// records are delivered after recovery even if nothing else is logged.
+    fn schedule_probe(&self, circuit: Arc<CircuitBreaker>) {
+        let transport = self.clone();
+        self.spawn_task(async move {
+            tokio::time::sleep(circuit.open_duration()).await;
+            if let Some((transition, probe)) = circuit.start_probe() {
+                transport.report(LogError::Circuit(transition));
+                if let Some(payload) = probe {
+                    if let Err((error, Some(payload))) = transport.send_through(&circuit, payload, true).await {
+                        transport.give_up(&error, &payload);
+                    }
+                }
+            }
+        });
+    }
+
+    /// Passes a diagnostic to the error callback, or prints it to stderr.
+    fn report(&self, error: LogError) {
+        match &self.error_callback {
+            Some(callback) => callback(&error),
+            None => eprintln!("{}", error),
+        }
+    }
+
+    /// Hands a payload to a test capture on the calling thread, counting it as sent.
+    #[cfg(feature = "testing")]
+    fn capture(&self, sink: &Sink, payload: Value) {
+        let bytes = serde_json::to_vec(&payload).map_or(0, |body| body.len());
+        self.stats.delivered(bytes, Duration::ZERO);
+        sink.record(payload);
+    }
+
+    /// Posts a payload to the first healthy intake endpoint, failing over to the next one when an
+    /// endpoint cannot be reached or answers with `429` or `5xx`. A delivered payload is then
+    /// exported to the OTLP collector, if there is one.
+    async fn send(&self, payload: &Value) -> Result<(), DeliveryError> {
+        let started = Instant::now();
+        let bytes = self.send_to_endpoints(payload).await?;
+        self.stats.delivered(bytes, started.elapsed());
+        #[cfg(feature = "otlp")]
+        if let Some(otlp) = &self.otlp {
+            self.export(otlp.clone(), payload.clone());
+        }
+        Ok(())
+    }
+
+    /// Exports a delivered payload from a task of its own, which is not counted as in flight, so
+    /// that a slow collector does not hold up `flush` or blocking sends.
+    #[cfg(feature = "otlp")]
+    fn export(&self, otlp: Arc<OtlpExporter>, payload: Value) {
+        let transport = self.clone();
+        self.spawn_task(async move {
+            if let Err(e) = otlp.export(&payload).await {
+                transport.report(LogError::Otlp(e));
+            }
+        });
+    }
+
+    /// Tries the candidate endpoints in turn. Returns the size of the delivered body.
+    async fn send_to_endpoints(&self, payload: &Value) -> Result<usize, DeliveryError> {
+        let body = self.body(payload)?;
+        let mut candidates = self.endpoints.candidates(payload).into_iter().peekable();
+        while let Some(url) = candidates.next() {
+            match self.send_to(&url, &body).await {
+                Err(e) if is_intake_failure(&e) => {
+                    self.endpoints.mark_down(&url);
+                    if candidates.peek().is_none() {
+                        return Err(e);
+                    }
+                    StatsCollector::count(&self.stats.retried);
+                },
+                result => {
+                    if result.is_ok() {
+                        self.endpoints.mark_up(&url);
+                    }
+                    return result.map(|_| body.bytes.len());
+                },
+            }
+        }
+        Ok(0)
+    }
+
+    /// Posts a payload to one endpoint, authenticating with the provider's current credentials.
+    ///
+    /// If the intake rejects the credentials (`401` or `403`), the provider is asked to refresh
+    /// and the request is retried once with whatever credentials it returns next.
+    async fn send_to(&self, url: &str, body: &Body) -> Result<(), DeliveryError> {
+        let mut refreshed = false;
+        loop {
+            let mut req = self.client.post(url)
+                .header("content-type", "application/json")
+                .body(body.bytes.clone());
+            if body.gzip {
+                req = req.header("content-encoding", "gzip");
+            }
+
+            let response = self.authorize(req).await?.send().await.map_err(DeliveryError::Http)?;
+            let status = response.status();
+            if status.is_success() {
+                return Ok(());
+            }
+            if !refreshed && (status == StatusCode::UNAUTHORIZED || status == StatusCode::FORBIDDEN) {
+                // Rotated credentials or an expired session token are the usual causes;
+                // pick up new ones and try again.
+                self.credentials.refresh();
+                if let Some(session) = &self.session {
+                    session.invalidate().await;
+                }
+                refreshed = true;
+                StatsCollector::count(&self.stats.retried);
+                continue;
+            }
+            return Err(DeliveryError::Status(status));
+        }
+    }
+
+    /// Serializes the payload, gzip-compressed if compression is enabled.
+    fn body(&self, payload: &Value) -> Result<Body, DeliveryError> {
+        #[cfg(feature = "compression")]
+        if self.compress {
+            let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            let compressed = serde_json::to_writer(&mut encoder, payload)
+                .map_err(std::io::Error::from)
+                .and_then(|_| encoder.finish());
+            return match compressed {
+                Ok(bytes) => Ok(Body { bytes, gzip: true }),
+                Err(e) => Err(DeliveryError::Encoding(e)),
+            };
+        }
+        match serde_json::to_vec(payload) {
+            Ok(bytes) => Ok(Body { bytes, gzip: false }),
+            Err(e) => Err(DeliveryError::Encoding(e.into())),
+        }
+    }
+
+    /// Adds authentication to a request: a session token for client/build ids when session tokens
+    /// are enabled, and the plain credential headers otherwise.
+    async fn authorize(&self, req: RequestBuilder) -> Result<RequestBuilder, DeliveryError> {
+        let current = self.credentials.credentials().map_err(DeliveryError::Credentials)?;
+        match (&self.session, &current) {
+            (Some(session), Credentials::ClientBuild { client_id, build_id }) => {
+                session.authorize(&self.client, client_id, build_id, req).await
+            },
+            _ => Ok(current.apply(req)),
+        }
+    }
+}
+
+/// A serialized payload, sent as-is to each endpoint tried.
+struct Body {
+    bytes: Vec<u8>,
+    gzip: bool,
+}
+
+/// Whether the intake endpoint itself failed, as opposed to the token exchange or the credentials.
+fn is_intake_failure(error: &DeliveryError) -> bool {
+    matches!(error, DeliveryError::Http(_) | DeliveryError::Status(_)) && error.is_endpoint_failure()
+}
+
+/// How records are delivered by a logger that runs without the caller's Tokio runtime.
+///
+/// In both modes the logger starts a small runtime of its own, on a dedicated thread, and sends from
+/// there, so `log` can be called from plain synchronous code.
+#[cfg(feature = "blocking")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlockingMode {
+    /// `log` returns right away; records are sent in the background. Use `flush` before exiting.
+    Background,
+    /// `log` returns once the intake has answered, or after `timeout`, so the last record of a short
+    /// run is delivered before the process exits.
+    SendBeforeReturning { timeout: Duration },
+}
+
+/// A runtime owned by a logger. Shut down without waiting when dropped, so that dropping the
+/// logger never blocks, even from inside another runtime.
+pub(crate) struct LoggerRuntime(Option<tokio::runtime::Runtime>);
+
+impl LoggerRuntime {
+    #[cfg(any(feature = "blocking", not(feature = "async-tokio")))]
+    pub(crate) fn new() -> std::io::Result<Self> {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("pogr-log")
+            .enable_all()
+            .build()?;
+        Ok(LoggerRuntime(Some(runtime)))
+    }
+
+    pub(crate) fn handle(&self) -> Option<tokio::runtime::Handle> {
+        self.0.as_ref().map(|runtime| runtime.handle().clone())
+    }
+}
+
+impl Drop for LoggerRuntime {
+    fn drop(&mut self) {
+        if let Some(runtime) = self.0.take() {
+            runtime.shutdown_background();
+        }
+    }
+}
+
+/// Counts payloads that have been handed to a background task but not yet delivered.
+#[derive(Default)]
+pub(crate) struct InFlight {
+    count: Mutex<usize>,
+    drained: Condvar,
+}
+
+impl InFlight {
+    /// Marks one payload as in flight until the returned guard is dropped.
+    pub(crate) fn start(self: &Arc<Self>) -> InFlightGuard {
+        *self.count.lock().unwrap() += 1;
+        InFlightGuard { in_flight: self.clone() }
+    }
+
+    pub(crate) fn count(&self) -> usize {
+        *self.count.lock().unwrap()
+    }
+
+    /// Blocks until nothing is in flight or the timeout passes. Returns `true` if everything drained.
+    pub(crate) fn wait(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        let mut count = self.count.lock().unwrap();
+        while *count > 0 {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return false;
+            }
+            count = self.drained.wait_timeout(count, remaining).unwrap().0;
+        }
+        true
+    }
+}
+
+/// Keeps a payload counted as in flight while alive.
+pub(crate) struct InFlightGuard {
+    in_flight: Arc<InFlight>,
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        let mut count = self.in_flight.count.lock().unwrap();
+        *count -= 1;
+        if *count == 0 {
+            self.in_flight.drained.notify_all();
+        }
+    }
+}
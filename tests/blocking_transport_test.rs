@@ -0,0 +1,49 @@
+#![cfg(feature = "blocking")]
+
+mod common;
+
+use pogr_log_rs::BlockingMode;
+use log::Level;
+use serde_json::json;
+use std::time::Duration;
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Plain `#[test]`s: no Tokio runtime is running on the logging thread.
+    #[test]
+    fn test_background_mode_sends_without_a_runtime() {
+        let mut server = mockito::Server::new();
+        let mock = server.mock("POST", "/v1/intake/logs")
+            .match_body(mockito::Matcher::PartialJson(json!({"log": "import started"})))
+            .with_status(200)
+            .expect(1)
+            .create();
+
+        let mut logger = common::logger_for(&server);
+        logger.use_blocking_transport(BlockingMode::Background).unwrap();
+        common::log_message(&logger, Level::Info, "import started");
+
+        assert!(logger.flush_timeout(Duration::from_secs(5)));
+        mock.assert();
+    }
+
+    #[test]
+    fn test_send_before_returning_delivers_before_log_returns() {
+        let mut server = mockito::Server::new();
+        let mock = server.mock("POST", "/v1/intake/logs")
+            .match_body(mockito::Matcher::PartialJson(json!({"log": "import finished"})))
+            .with_status(200)
+            .expect(1)
+            .create();
+
+        let mut logger = common::logger_for(&server);
+        logger.use_blocking_transport(BlockingMode::SendBeforeReturning { timeout: Duration::from_secs(5) }).unwrap();
+        common::log_message(&logger, Level::Info, "import finished");
+
+        // No flush: the record is already delivered.
+        mock.assert();
+    }
+}
@@ -0,0 +1,71 @@
+use pogr_log_rs::{LogConfig, LoggerConfig, POGRLogger};
+use log::{Level, Record};
+use log::kv::Value as KvValue;
+use reqwest::Client;
+use serde_json::json;
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn logger() -> POGRLogger {
+        let logger_config = LoggerConfig {
+            service: "test_service".to_string(),
+            environment: "test_env".to_string(),
+            default_type: None,
+            tags: Default::default(),
+        };
+        POGRLogger::new(
+            Client::new(),
+            Some("http://localhost/v1/intake/logs".to_string()),
+            LogConfig::AccessKeys {
+                access_key: "test_access_key".to_string(),
+                secret_key: "test_secret_key".to_string(),
+                logger_config: logger_config.clone(),
+            },
+            logger_config,
+        )
+    }
+
+    #[test]
+    fn test_structured_key_values_become_payload_fields() {
+        let data = json!({"user_id": 123});
+        let tags = json!({"env": "production"});
+        let kvs: [(&str, KvValue); 3] = [
+            ("pogr.type", KvValue::from("login")),
+            ("pogr.data", KvValue::from_serde(&data)),
+            ("pogr.tags", KvValue::from_serde(&tags)),
+        ];
+        let payload = logger().build_payload(&Record::builder()
+            .args(format_args!("User logged in"))
+            .level(Level::Info)
+            .key_values(&kvs)
+            .build());
+
+        assert_eq!(payload, json!({
+            "service": "test_service",
+            "environment": "test_env",
+            "severity": "info",
+            "log": "User logged in",
+            "type": "login",
+            "data": {"user_id": 123},
+            "tags": {"env": "production"},
+        }));
+    }
+
+    #[test]
+    fn test_plain_messages_are_never_parsed() {
+        let kvs: [(&str, KvValue); 1] = [("attempt", KvValue::from(3))];
+        let payload = logger().build_payload(&Record::builder()
+            .args(format_args!(r#"{{"type": "spoofed"}}"#))
+            .level(Level::Warn)
+            .key_values(&kvs)
+            .build());
+
+        assert_eq!(payload["log"], r#"{"type": "spoofed"}"#);
+        assert!(payload.get("type").is_none());
+        // Other key-values end up in `data`.
+        assert_eq!(payload["data"], json!({"attempt": 3}));
+    }
+}
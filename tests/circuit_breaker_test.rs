@@ -0,0 +1,164 @@
+mod common;
+
+use pogr_log_rs::circuit::{CircuitBreakerConfig, CircuitState};
+use pogr_log_rs::{LogError, POGRLogger};
+use log::Level;
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A logger with a circuit breaker, and the states it transitioned to.
+    fn logger_for(server: &mockito::Server, config: CircuitBreakerConfig) -> (POGRLogger, Arc<Mutex<Vec<CircuitState>>>) {
+        let mut logger = common::logger_for(server);
+        logger.set_circuit_breaker(config);
+        let transitions = Arc::new(Mutex::new(Vec::new()));
+        let sink = transitions.clone();
+        logger.set_error_callback(move |error| {
+            if let LogError::Circuit(transition) = error {
+                sink.lock().unwrap().push(transition.to);
+            }
+        });
+        (logger, transitions)
+    }
+
+    fn wait_for(condition: impl Fn() -> bool) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !condition() {
+            assert!(Instant::now() < deadline, "condition not reached in time");
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_opens_after_consecutive_failures_and_buffers() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server.mock("POST", "/v1/intake/logs")
+            .with_status(503)
+            .expect(3)
+            .create_async().await;
+
+        let (logger, transitions) = logger_for(&server, CircuitBreakerConfig::new()
+            .failure_threshold(3)
+            .open_duration(Duration::from_secs(60)));
+        for i in 0..5 {
+            common::log_and_flush(&logger, Level::Info, &format!("record {}", i));
+        }
+
+        // Only the first three records reached the intake; the one that opened the circuit and the
+        // two after it are buffered.
+        mock.assert_async().await;
+        let metrics = logger.circuit_metrics().unwrap();
+        assert_eq!(metrics.state, CircuitState::Open);
+        assert_eq!(metrics.opened, 1);
+        assert_eq!(metrics.buffered, 3);
+        assert_eq!(*transitions.lock().unwrap(), vec![CircuitState::Open]);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_closes_after_successful_probe_and_replays_buffer() {
+        let mut server = mockito::Server::new_async().await;
+        let down = server.mock("POST", "/v1/intake/logs")
+            .with_status(500)
+            .expect(2)
+            .create_async().await;
+
+        let (logger, transitions) = logger_for(&server, CircuitBreakerConfig::new()
+            .failure_threshold(2)
+            .open_duration(Duration::from_millis(300)));
+        common::log_and_flush(&logger, Level::Info, "first");
+        common::log_and_flush(&logger, Level::Info, "second");
+        common::log_and_flush(&logger, Level::Info, "third");
+        down.assert_async().await;
+        down.remove_async().await;
+
+        let up = server.mock("POST", "/v1/intake/logs")
+            .match_body(mockito::Matcher::Regex("second|third".to_string()))
+            .with_status(200)
+            .expect(2)
+            .create_async().await;
+
+        // Nothing else is logged; the probe timer sends the oldest buffered record.
+        wait_for(|| logger.circuit_metrics().unwrap().state == CircuitState::Closed);
+        assert!(logger.flush_timeout(Duration::from_secs(5)));
+        up.assert_async().await;
+
+        let metrics = logger.circuit_metrics().unwrap();
+        assert_eq!(metrics.closed, 1);
+        assert_eq!(metrics.replayed, 1);
+        assert_eq!(metrics.buffered, 0);
+        assert_eq!(metrics.consecutive_failures, 0);
+        assert_eq!(
+            *transitions.lock().unwrap(),
+            vec![CircuitState::Open, CircuitState::HalfOpen, CircuitState::Closed]
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_failed_probe_reopens() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server.mock("POST", "/v1/intake/logs")
+            .with_status(502)
+            .expect(2)
+            .create_async().await;
+
+        let (logger, transitions) = logger_for(&server, CircuitBreakerConfig::new()
+            .failure_threshold(1)
+            .open_duration(Duration::from_millis(200)));
+        common::log_and_flush(&logger, Level::Info, "first");
+
+        wait_for(|| logger.circuit_metrics().unwrap().opened == 2);
+        mock.assert_async().await;
+        let metrics = logger.circuit_metrics().unwrap();
+        assert_eq!(metrics.state, CircuitState::Open);
+        assert_eq!(metrics.half_opened, 1);
+        assert_eq!(metrics.buffered, 1);
+        assert_eq!(
+            transitions.lock().unwrap()[..3],
+            [CircuitState::Open, CircuitState::HalfOpen, CircuitState::Open]
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_full_buffer_drops_oldest_records() {
+        let mut server = mockito::Server::new_async().await;
+        server.mock("POST", "/v1/intake/logs")
+            .with_status(503)
+            .create_async().await;
+
+        let (logger, _) = logger_for(&server, CircuitBreakerConfig::new()
+            .failure_threshold(1)
+            .open_duration(Duration::from_secs(60))
+            .buffer_capacity(2));
+        for i in 0..4 {
+            common::log_and_flush(&logger, Level::Info, &format!("record {}", i));
+        }
+
+        let metrics = logger.circuit_metrics().unwrap();
+        assert_eq!(metrics.buffered, 2);
+        assert_eq!(metrics.dropped, 2);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_client_errors_do_not_open_the_circuit() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server.mock("POST", "/v1/intake/logs")
+            .match_body(mockito::Matcher::PartialJson(json!({"service": "test_service"})))
+            .with_status(400)
+            .expect(3)
+            .create_async().await;
+
+        let (logger, transitions) = logger_for(&server, CircuitBreakerConfig::new().failure_threshold(1));
+        for i in 0..3 {
+            common::log_and_flush(&logger, Level::Info, &format!("record {}", i));
+        }
+
+        mock.assert_async().await;
+        assert_eq!(logger.circuit_metrics().unwrap().state, CircuitState::Closed);
+        assert!(transitions.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,137 @@
+#![cfg(feature = "cli")]
+
+use mockito::Matcher;
+use serde_json::json;
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Runs `pogr-log` against the mock server with test credentials, feeding it `stdin`.
+    fn pogr_log(server: &mockito::Server, args: &[&str], stdin: &str) -> Output {
+        let url = format!("{}/v1/intake/logs", server.url());
+        let mut child = Command::new(env!("CARGO_BIN_EXE_pogr-log"))
+            .args(args)
+            .args(["--url", &url, "--access-key", "test_access_key", "--secret-key", "test_secret_key"])
+            .args(["--service", "test_service", "--environment", "test_env", "--timeout-secs", "5"])
+            .env_remove("POGR_INTAKE_URL")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        child.stdin.take().unwrap().write_all(stdin.as_bytes()).unwrap();
+        child.wait_with_output().unwrap()
+    }
+
+    #[test]
+    fn test_send_single_event() {
+        let mut server = mockito::Server::new();
+        let mock = server.mock("POST", "/v1/intake/logs")
+            .match_header("POGR_ACCESS", "test_access_key")
+            .match_body(Matcher::Json(json!({
+                "service": "test_service",
+                "environment": "test_env",
+                "severity": "warn",
+                "type": "deploy",
+                "log": "Deploy started",
+                "data": {"version": "1.4.2"},
+            })))
+            .with_status(200)
+            .create();
+
+        let output = pogr_log(&server, &["send", "--level", "warn", "--type", "deploy", "--data", r#"{"version": "1.4.2"}"#, "Deploy", "started"], "");
+
+        assert_eq!(output.status.code(), Some(0), "{}", String::from_utf8_lossy(&output.stderr));
+        mock.assert();
+    }
+
+    #[test]
+    fn test_send_fails_when_rejected() {
+        let mut server = mockito::Server::new();
+        server.mock("POST", "/v1/intake/logs")
+            .with_status(500)
+            .create();
+
+        let output = pogr_log(&server, &["send", "lost"], "");
+
+        assert_eq!(output.status.code(), Some(1));
+        assert!(String::from_utf8_lossy(&output.stderr).contains("500"));
+    }
+
+    #[test]
+    fn test_tail_text_lines() {
+        let mut server = mockito::Server::new();
+        let mock = server.mock("POST", "/v1/intake/logs")
+            .match_body(Matcher::PartialJson(json!({"type": "legacy", "tags": {"host": "build-1"}})))
+            .with_status(200)
+            .expect(3)
+            .create();
+
+        let output = pogr_log(&server, &["tail", "--type", "legacy", "--tags", r#"{"host": "build-1"}"#, "--flush-every", "2"], "first\nsecond\n\nthird\n");
+
+        assert_eq!(output.status.code(), Some(0), "{}", String::from_utf8_lossy(&output.stderr));
+        mock.assert();
+    }
+
+    #[test]
+    fn test_tail_json_lines() {
+        let mut server = mockito::Server::new();
+        let structured = server.mock("POST", "/v1/intake/logs")
+            .match_body(Matcher::Json(json!({
+                "service": "test_service",
+                "environment": "test_env",
+                "severity": "error",
+                "type": "job",
+                "log": "Job failed",
+                "data": {"attempt": 3, "job_id": 7},
+            })))
+            .with_status(200)
+            .create();
+        let plain = server.mock("POST", "/v1/intake/logs")
+            .match_body(Matcher::PartialJson(json!({"log": "not json", "severity": "info"})))
+            .with_status(200)
+            .create();
+
+        let input = concat!(
+            r#"{"message": "Job failed", "level": "ERROR", "type": "job", "data": {"attempt": 3}, "job_id": 7}"#, "\n",
+            "not json\n",
+        );
+        let output = pogr_log(&server, &["tail", "--format", "json"], input);
+
+        assert_eq!(output.status.code(), Some(0), "{}", String::from_utf8_lossy(&output.stderr));
+        structured.assert();
+        plain.assert();
+    }
+
+    #[test]
+    fn test_tail_file() {
+        let mut server = mockito::Server::new();
+        let mock = server.mock("POST", "/v1/intake/logs")
+            .with_status(200)
+            .expect(2)
+            .create();
+        let path = std::env::temp_dir().join(format!("pogr-log-cli-test-{}.log", std::process::id()));
+        std::fs::write(&path, "from a file\r\nand another\n").unwrap();
+
+        let output = pogr_log(&server, &["tail", path.to_str().unwrap()], "");
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(output.status.code(), Some(0), "{}", String::from_utf8_lossy(&output.stderr));
+        mock.assert();
+    }
+
+    #[test]
+    fn test_usage_errors() {
+        let server = mockito::Server::new();
+
+        assert_eq!(pogr_log(&server, &["send"], "").status.code(), Some(2));
+        assert_eq!(pogr_log(&server, &["send", "--level", "loud", "hi"], "").status.code(), Some(2));
+        assert_eq!(pogr_log(&server, &["send", "--data", "[1]", "hi"], "").status.code(), Some(2));
+        assert_eq!(pogr_log(&server, &["tail", "--follow"], "").status.code(), Some(2));
+        assert_eq!(pogr_log(&server, &["shout"], "").status.code(), Some(2));
+    }
+}
@@ -0,0 +1,52 @@
+//! Fixtures shared by the integration tests.
+
+// Every test file uses a different subset of these.
+#![allow(dead_code)]
+
+use pogr_log_rs::{LogConfig, LoggerConfig, POGRLogger};
+use log::{Level, Log, Record};
+use reqwest::Client;
+use std::time::Duration;
+
+/// Service `test_service` in `test_env`, without a default type or global tags.
+pub fn logger_config() -> LoggerConfig {
+    LoggerConfig::new("test_service", "test_env")
+}
+
+/// A logger sending to `url` with the given access key and `test_secret_key`.
+pub fn logger_with_key(url: impl Into<String>, access_key: &str) -> POGRLogger {
+    POGRLogger::new(
+        Client::new(),
+        Some(url.into()),
+        LogConfig::AccessKeys {
+            access_key: access_key.to_string(),
+            secret_key: "test_secret_key".to_string(),
+            logger_config: logger_config(),
+        },
+        logger_config(),
+    )
+}
+
+/// A logger sending to `url` with the access key `test_access_key`.
+pub fn logger_for_url(url: impl Into<String>) -> POGRLogger {
+    logger_with_key(url, "test_access_key")
+}
+
+/// A logger sending to the `/v1/intake/logs` endpoint of a mock server.
+pub fn logger_for(server: &mockito::Server) -> POGRLogger {
+    logger_for_url(format!("{}/v1/intake/logs", server.url()))
+}
+
+/// Logs a plain record, without waiting for it to be sent.
+pub fn log_message(logger: &POGRLogger, level: Level, message: &str) {
+    logger.log(&Record::builder()
+        .args(format_args!("{}", message))
+        .level(level)
+        .build());
+}
+
+/// Logs a plain record and waits until it has been delivered or given up on.
+pub fn log_and_flush(logger: &POGRLogger, level: Level, message: &str) {
+    log_message(logger, level, message);
+    assert!(logger.flush_timeout(Duration::from_secs(5)));
+}
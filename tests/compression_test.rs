@@ -0,0 +1,43 @@
+#![cfg(feature = "compression")]
+
+mod common;
+
+use log::Level;
+use serde_json::Value;
+use std::io::Read;
+use std::sync::{Arc, Mutex};
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_request_bodies_are_gzipped() {
+        let mut server = mockito::Server::new_async().await;
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let sink = received.clone();
+        let mock = server.mock("POST", "/v1/intake/logs")
+            .match_header("content-encoding", "gzip")
+            .with_status(200)
+            .with_body_from_request(move |request| {
+                sink.lock().unwrap().push(request.body().unwrap().clone());
+                Vec::new()
+            })
+            .expect(1)
+            .create_async().await;
+
+        let mut logger = common::logger_for(&server);
+        logger.enable_compression();
+
+        common::log_and_flush(&logger, Level::Info, "compressed");
+        mock.assert_async().await;
+
+        let body = received.lock().unwrap().pop().unwrap();
+        let mut json = String::new();
+        flate2::read::GzDecoder::new(&body[..]).read_to_string(&mut json).unwrap();
+        let payload: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(payload["log"], "compressed");
+        assert_eq!(payload["service"], "test_service");
+    }
+}
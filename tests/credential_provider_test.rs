@@ -0,0 +1,112 @@
+mod common;
+
+use pogr_log_rs::{CredentialProvider, CredentialError, Credentials};
+use pogr_log_rs::credentials::FileCredentials;
+use log::Level;
+use serde_json::json;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Hands out a stale key until the transport asks for a refresh.
+    struct RotatingCredentials {
+        rotated: AtomicBool,
+    }
+
+    impl CredentialProvider for RotatingCredentials {
+        fn credentials(&self) -> Result<Credentials, CredentialError> {
+            let access_key = if self.rotated.load(Ordering::SeqCst) { "new_key" } else { "old_key" };
+            Ok(Credentials::AccessKeys {
+                access_key: access_key.to_string(),
+                secret_key: "secret".to_string(),
+            })
+        }
+
+        fn refresh(&self) {
+            self.rotated.store(true, Ordering::SeqCst);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unauthorized_response_refreshes_credentials() {
+        let mut server = mockito::Server::new_async().await;
+
+        let rejected = server.mock("POST", "/v1/intake/logs")
+            .match_header("POGR_ACCESS", "old_key")
+            .with_status(401)
+            .expect(1)
+            .create_async().await;
+        let accepted = server.mock("POST", "/v1/intake/logs")
+            .match_header("POGR_ACCESS", "new_key")
+            .with_status(200)
+            .expect(1)
+            .create_async().await;
+
+        // The provider's credentials replace the logger's own.
+        let mut logger = common::logger_for(&server);
+        logger.set_credential_provider(RotatingCredentials { rotated: AtomicBool::new(false) });
+
+        logger.custom_log_delivered(Level::Info, "rotated", "test_log", json!({}), json!({})).await.unwrap();
+
+        rejected.assert_async().await;
+        accepted.assert_async().await;
+    }
+
+    #[test]
+    fn test_file_credentials_pick_up_rewrites() {
+        let path = std::env::temp_dir().join(format!("pogr_credentials_{}.json", std::process::id()));
+        std::fs::write(&path, r#"{"client_id": "client", "build_id": "build_1"}"#).unwrap();
+
+        let provider = FileCredentials::new(&path);
+        assert_eq!(provider.credentials().unwrap(), Credentials::ClientBuild {
+            client_id: "client".to_string(),
+            build_id: "build_1".to_string(),
+        });
+
+        std::fs::write(&path, r#"{"access_key": "access", "secret_key": "secret"}"#).unwrap();
+        provider.refresh();
+        assert_eq!(provider.credentials().unwrap(), Credentials::AccessKeys {
+            access_key: "access".to_string(),
+            secret_key: "secret".to_string(),
+        });
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(provider.credentials(), Err(CredentialError::Io(_))));
+    }
+
+    #[tokio::test]
+    async fn test_file_credentials_rotate_without_refresh() {
+        let mut server = mockito::Server::new_async().await;
+        let first = server.mock("POST", "/v1/intake/logs")
+            .match_header("POGR_ACCESS", "first_key")
+            .with_status(200)
+            .expect(1)
+            .create_async().await;
+        let second = server.mock("POST", "/v1/intake/logs")
+            .match_header("POGR_ACCESS", "second_key")
+            .with_status(200)
+            .expect(1)
+            .create_async().await;
+        let path = std::env::temp_dir().join(format!("pogr_credentials_rotation_{}.json", std::process::id()));
+        std::fs::write(&path, r#"{"access_key": "first_key", "secret_key": "secret"}"#).unwrap();
+
+        // The provider's credentials replace the logger's own.
+        let mut logger = common::logger_for(&server);
+        logger.set_credential_provider(FileCredentials::new(&path));
+        logger.custom_log_delivered(Level::Info, "before rotation", "test_log", json!({}), json!({})).await.unwrap();
+
+        // A secret mount is replaced in place; move the mtime on explicitly, since some
+        // filesystems only record whole seconds.
+        std::fs::write(&path, r#"{"access_key": "second_key", "secret_key": "secret"}"#).unwrap();
+        let file = std::fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(std::time::SystemTime::now() + std::time::Duration::from_secs(5)).unwrap();
+        logger.custom_log_delivered(Level::Info, "after rotation", "test_log", json!({}), json!({})).await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        first.assert_async().await;
+        second.assert_async().await;
+    }
+}
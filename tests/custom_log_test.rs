@@ -17,7 +17,7 @@ mod tests {
         Arc::new(Mutex::new(()))
     });
 
-    #[tokio::test]
+    #[tokio::test(flavor = "multi_thread")]
     async fn test_custom_log_sends_correct_request() {
         let _lock = INIT.lock().await;
         let expected_body = json!({
@@ -56,18 +56,46 @@ mod tests {
                         service: "test_service".to_string(),
                         environment: "test_env".to_string(),
                         default_type: None,
+                        tags: Default::default(),
                     },
                 },
                 LoggerConfig {
                     service: "test_service".to_string(),
                     environment: "test_env".to_string(),
                     default_type: None,
+                    tags: Default::default(),
                 },
             );
             
 
         logger.custom_log(Level::Info, "This is a test log", "test_log", json!({"test": "data"}), json!({"tag1": "value1"})).await;
+        // `custom_log` only spawns the send; wait for it.
+        tokio::task::spawn_blocking(move || assert!(logger.flush_timeout(std::time::Duration::from_secs(5)))).await.unwrap();
 
         _m.assert_async().await;
     }
+
+    #[tokio::test]
+    async fn test_custom_log_delivered_returns_the_delivery_error() {
+        let _lock = INIT.lock().await;
+        let mut server = mockito::Server::new_async().await;
+        let _m = server.mock("POST", "/v1/intake/logs")
+            .with_status(500)
+            .create_async().await;
+        let logger_config = LoggerConfig::new("test_service", "test_env");
+        let logger = POGRLogger::new(
+            Client::new(),
+            Some(format!("{}/v1/intake/logs", server.url())),
+            LogConfig::AccessKeys {
+                access_key: "test_access_key".to_string(),
+                secret_key: "test_secret_key".to_string(),
+                logger_config: logger_config.clone(),
+            },
+            logger_config,
+        );
+
+        let result = logger.custom_log_delivered(Level::Info, "This is a test log", "test_log", json!({}), json!({})).await;
+
+        assert!(matches!(result, Err(pogr_log_rs::DeliveryError::Status(status)) if status == 500));
+    }
 }
@@ -0,0 +1,165 @@
+mod common;
+
+use pogr_log_rs::POGRLogger;
+use log::{Level, Log, Record};
+use serde_json::json;
+use std::time::Duration;
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log_dependency_down(logger: &POGRLogger) {
+        logger.log(&Record::builder()
+            .args(format_args!("dependency down"))
+            .level(Level::Error)
+            .target("game::db")
+            .build());
+    }
+
+    fn logger(server: &mockito::Server, window: Duration) -> POGRLogger {
+        let mut logger = common::logger_for(server);
+        logger.set_dedup_window(window);
+        logger
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_repeats_collapse_into_one_summary() {
+        let mut server = mockito::Server::new_async().await;
+        // Listed first so that it wins over the broader mock for the summary record.
+        let summary = server.mock("POST", "/v1/intake/logs")
+            .match_body(mockito::Matcher::PartialJson(json!({"log": "dependency down", "repeat_count": 4})))
+            .with_status(200)
+            .expect(1)
+            .create_async().await;
+
+        let first = server.mock("POST", "/v1/intake/logs")
+            .match_body(mockito::Matcher::PartialJson(json!({"log": "dependency down", "severity": "error"})))
+            .with_status(200)
+            .expect(2)
+            .create_async().await;
+        let logger = logger(&server, Duration::from_millis(200));
+
+        for _ in 0..5 {
+            log_dependency_down(&logger);
+        }
+
+        // Only the first occurrence goes out before the window closes.
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(!summary.matched_async().await);
+
+        // The summary follows once the window has closed.
+        for _ in 0..50 {
+            if summary.matched_async().await {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        summary.assert_async().await;
+
+        // A repeat after the window closed opens a new one and is sent immediately.
+        log_dependency_down(&logger);
+        for _ in 0..50 {
+            if first.matched_async().await {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        first.assert_async().await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_repeats_slide_the_window() {
+        let mut server = mockito::Server::new_async().await;
+        let summary = server.mock("POST", "/v1/intake/logs")
+            .match_body(mockito::Matcher::PartialJson(json!({"log": "dependency down", "repeat_count": 6})))
+            .with_status(200)
+            .expect(1)
+            .create_async().await;
+        let first = server.mock("POST", "/v1/intake/logs")
+            .with_status(200)
+            .expect(1)
+            .create_async().await;
+        let logger = logger(&server, Duration::from_millis(300));
+
+        // Repeats every 100ms keep the window open well past its length.
+        log_dependency_down(&logger);
+        for _ in 0..6 {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            log_dependency_down(&logger);
+        }
+        assert!(!summary.matched_async().await);
+
+        for _ in 0..50 {
+            if summary.matched_async().await {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        summary.assert_async().await;
+        first.assert_async().await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_flush_sends_pending_summaries() {
+        let mut server = mockito::Server::new_async().await;
+        let summary = server.mock("POST", "/v1/intake/logs")
+            .match_body(mockito::Matcher::PartialJson(json!({"log": "dependency down", "repeat_count": 2})))
+            .with_status(200)
+            .expect(1)
+            .create_async().await;
+        let first = server.mock("POST", "/v1/intake/logs")
+            .with_status(200)
+            .expect(1)
+            .create_async().await;
+        let logger = logger(&server, Duration::from_secs(60));
+
+        for _ in 0..3 {
+            log_dependency_down(&logger);
+        }
+        tokio::task::spawn_blocking(move || assert!(logger.flush_timeout(Duration::from_secs(5)))).await.unwrap();
+
+        summary.assert_async().await;
+        first.assert_async().await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_flush_from_a_thread_without_runtime() {
+        let mut server = mockito::Server::new_async().await;
+        let summary = server.mock("POST", "/v1/intake/logs")
+            .match_body(mockito::Matcher::PartialJson(json!({"log": "dependency down", "repeat_count": 2})))
+            .with_status(200)
+            .expect(1)
+            .create_async().await;
+        let first = server.mock("POST", "/v1/intake/logs")
+            .with_status(200)
+            .expect(1)
+            .create_async().await;
+        let logger = logger(&server, Duration::from_secs(60));
+
+        for _ in 0..3 {
+            log_dependency_down(&logger);
+        }
+        // A plain thread, unlike `spawn_blocking`, has no runtime to fall back on.
+        let flushed = std::thread::spawn(move || logger.flush_timeout(Duration::from_secs(5)));
+        tokio::task::spawn_blocking(move || assert!(flushed.join().unwrap())).await.unwrap();
+
+        summary.assert_async().await;
+        first.assert_async().await;
+    }
+
+    // Without `async-tokio`, every logger has a runtime of its own.
+    #[cfg(feature = "async-tokio")]
+    #[test]
+    fn test_no_dedup_without_runtime() {
+        let (mut logger, capture) = pogr_log_rs::testing::capture_logger(common::logger_config());
+        logger.set_dedup_window(Duration::from_secs(60));
+
+        for _ in 0..3 {
+            log_dependency_down(&logger);
+        }
+
+        assert_eq!(capture.len(), 3);
+    }
+}
@@ -0,0 +1,214 @@
+mod common;
+
+use pogr_log_rs::endpoints::{EndpointStatus, Endpoints};
+use pogr_log_rs::POGRLogger;
+use log::{Level, Log, Record};
+use serde_json::Value;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A minimal intake that answers every request with `200` and closes the connection, so that
+    /// stopping it makes the port refuse connections, like a crashed region.
+    struct KillableIntake {
+        addr: SocketAddr,
+        received: Arc<Mutex<Vec<Value>>>,
+        task: JoinHandle<()>,
+    }
+
+    impl KillableIntake {
+        async fn start() -> Self {
+            Self::bind("127.0.0.1:0".parse().unwrap()).await
+        }
+
+        async fn bind(addr: SocketAddr) -> Self {
+            let listener = TcpListener::bind(addr).await.unwrap();
+            let addr = listener.local_addr().unwrap();
+            let received = Arc::new(Mutex::new(Vec::new()));
+            let sink = received.clone();
+            let task = tokio::spawn(async move {
+                while let Ok((mut stream, _)) = listener.accept().await {
+                    if let Some(body) = read_body(&mut stream).await {
+                        sink.lock().unwrap().push(body);
+                    }
+                    let _ = stream.write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\nconnection: close\r\n\r\n").await;
+                }
+            });
+            KillableIntake { addr, received, task }
+        }
+
+        fn url(&self) -> String {
+            format!("http://{}/v1/intake/logs", self.addr)
+        }
+
+        fn messages(&self) -> Vec<String> {
+            self.received.lock().unwrap().iter().map(|payload| payload["log"].as_str().unwrap().to_string()).collect()
+        }
+
+        async fn kill(self) -> SocketAddr {
+            self.task.abort();
+            let _ = self.task.await;
+            self.addr
+        }
+    }
+
+    async fn read_body(stream: &mut tokio::net::TcpStream) -> Option<Value> {
+        let mut request = Vec::new();
+        let mut chunk = [0u8; 4096];
+        loop {
+            let read = stream.read(&mut chunk).await.ok()?;
+            if read == 0 {
+                return None;
+            }
+            request.extend_from_slice(&chunk[..read]);
+            let text = String::from_utf8_lossy(&request);
+            if let Some(end) = text.find("\r\n\r\n") {
+                let length = text[..end].lines()
+                    .find_map(|line| line.to_ascii_lowercase().strip_prefix("content-length:").map(|v| v.trim().parse::<usize>()))
+                    .and_then(Result::ok)
+                    .unwrap_or(0);
+                if request.len() >= end + 4 + length {
+                    return serde_json::from_slice(&request[end + 4..end + 4 + length]).ok();
+                }
+            }
+        }
+    }
+
+    fn logger_with(endpoints: Endpoints) -> POGRLogger {
+        let mut logger = common::logger_for_url(endpoints.urls()[0].clone());
+        logger.set_endpoints(endpoints);
+        logger
+    }
+
+    /// Logs a record of the given type and waits for its delivery.
+    fn log_typed_and_flush(logger: &POGRLogger, message: &str, log_type: &str) {
+        logger.log(&Record::builder()
+            .args(format_args!("{}", message))
+            .level(Level::Info)
+            .key_values(&[("pogr.type", log_type)])
+            .build());
+        assert!(logger.flush_timeout(Duration::from_secs(5)));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_fails_over_when_primary_is_killed() {
+        let primary = KillableIntake::start().await;
+        let mut backup = mockito::Server::new_async().await;
+        let mock = backup.mock("POST", "/v1/intake/logs")
+            .match_body(mockito::Matcher::Regex("after kill".to_string()))
+            .with_status(200)
+            .expect(2)
+            .create_async().await;
+
+        let primary_url = primary.url();
+        let backup_url = format!("{}/v1/intake/logs", backup.url());
+        let logger = logger_with(Endpoints::new([primary_url.clone(), backup_url.clone()]));
+
+        common::log_and_flush(&logger, Level::Info, "before kill 1");
+        common::log_and_flush(&logger, Level::Info, "before kill 2");
+        assert_eq!(primary.messages(), vec!["before kill 1", "before kill 2"]);
+
+        primary.kill().await;
+        common::log_and_flush(&logger, Level::Info, "after kill 1");
+        common::log_and_flush(&logger, Level::Info, "after kill 2");
+        mock.assert_async().await;
+
+        assert_eq!(logger.endpoint_status(), vec![
+            EndpointStatus { url: primary_url, healthy: false },
+            EndpointStatus { url: backup_url, healthy: true },
+        ]);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_recovered_endpoint_is_used_again_after_retry_after() {
+        let primary = KillableIntake::start().await;
+        let mut backup = mockito::Server::new_async().await;
+        let mock = backup.mock("POST", "/v1/intake/logs")
+            .with_status(200)
+            .expect(1)
+            .create_async().await;
+
+        let logger = logger_with(Endpoints::new([primary.url(), format!("{}/v1/intake/logs", backup.url())])
+            .retry_after(Duration::from_millis(200)));
+
+        let addr = primary.kill().await;
+        common::log_and_flush(&logger, Level::Info, "while down");
+        mock.assert_async().await;
+
+        let primary = KillableIntake::bind(addr).await;
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        common::log_and_flush(&logger, Level::Info, "after restart");
+        assert_eq!(primary.messages(), vec!["after restart"]);
+        assert!(logger.endpoint_status().iter().all(|status| status.healthy));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_routes_by_type() {
+        let default = KillableIntake::start().await;
+        let payments = KillableIntake::start().await;
+        let logger = logger_with(Endpoints::new([default.url()]).route_type("payment", payments.url()));
+
+        log_typed_and_flush(&logger, "login", "session");
+        log_typed_and_flush(&logger, "charge", "payment");
+        common::log_and_flush(&logger, Level::Info, "untyped");
+
+        assert_eq!(default.messages(), vec!["login", "untyped"]);
+        assert_eq!(payments.messages(), vec!["charge"]);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_routed_endpoint_falls_back_to_list_when_down() {
+        let region = KillableIntake::start().await;
+        let global = KillableIntake::start().await;
+        let logger = logger_with(Endpoints::new([global.url()]).route_environment_with_fallback("test_env", region.url()));
+
+        common::log_and_flush(&logger, Level::Info, "routed");
+        assert_eq!(region.messages(), vec!["routed"]);
+
+        region.kill().await;
+        common::log_and_flush(&logger, Level::Info, "fallback");
+        assert_eq!(global.messages(), vec!["fallback"]);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_routed_records_stay_in_their_region_when_it_is_down() {
+        let region = KillableIntake::start().await;
+        let global = KillableIntake::start().await;
+        let logger = logger_with(Endpoints::new([global.url()]).route_environment("test_env", region.url()));
+
+        common::log_and_flush(&logger, Level::Info, "routed");
+        assert_eq!(region.messages(), vec!["routed"]);
+
+        region.kill().await;
+        common::log_and_flush(&logger, Level::Info, "kept in region");
+        assert!(global.messages().is_empty());
+        assert_eq!(logger.stats().failed, 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_all_endpoints_down_are_marked_unhealthy() {
+        let first = KillableIntake::start().await;
+        let second = KillableIntake::start().await;
+        let logger = logger_with(Endpoints::new([first.url(), second.url()]));
+        first.kill().await;
+        second.kill().await;
+
+        common::log_and_flush(&logger, Level::Info, "lost");
+        assert!(logger.endpoint_status().iter().all(|status| !status.healthy));
+    }
+
+    #[test]
+    fn test_parse_list() {
+        let endpoints = Endpoints::parse_list(" https://eu.example.com/logs, https://us.example.com/logs ,");
+        assert_eq!(endpoints.urls(), ["https://eu.example.com/logs", "https://us.example.com/logs"]);
+        assert_eq!(Endpoints::parse_list("").urls(), [pogr_log_rs::endpoints::DEFAULT_INTAKE_URL]);
+    }
+}
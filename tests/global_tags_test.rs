@@ -0,0 +1,152 @@
+use pogr_log_rs::testing;
+use pogr_log_rs::{pogr_error, LoggerConfig};
+use log::{Level, Log, Record};
+use serde_json::json;
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn logger_config() -> LoggerConfig {
+        LoggerConfig::new("test_service", "test_env")
+            .tag("region", "eu-west")
+            .tag("shard", 3)
+    }
+
+    #[test]
+    fn test_logger_config_builder() {
+        let config = LoggerConfig::new("test_service", "test_env").default_type("match");
+
+        assert_eq!(config.service, "test_service");
+        assert_eq!(config.environment, "test_env");
+        assert_eq!(config.default_type.as_deref(), Some("match"));
+        assert!(config.tags.is_empty());
+        assert_eq!(serde_json::to_value(logger_config().tags).unwrap(), json!({"region": "eu-west", "shard": 3}));
+    }
+
+    #[test]
+    fn test_plain_records_get_global_tags() {
+        let (logger, capture) = testing::capture_logger(logger_config());
+        logger.log(&Record::builder()
+            .args(format_args!("Player joined"))
+            .level(Level::Info)
+            .build());
+
+        assert_eq!(capture.records()[0]["tags"], json!({"region": "eu-west", "shard": 3}));
+    }
+
+    #[test]
+    fn test_record_tags_take_precedence() {
+        let (logger, capture) = testing::capture_logger(logger_config());
+        logger.log(&Record::builder()
+            .args(format_args!("Match started"))
+            .level(Level::Info)
+            .key_values(&[("pogr.tags", log::kv::Value::from_serde(&json!({"shard": 7, "mode": "ranked"})))])
+            .build());
+
+        assert_eq!(capture.records()[0]["tags"], json!({"region": "eu-west", "shard": 7, "mode": "ranked"}));
+    }
+
+    #[test]
+    fn test_default_type() {
+        let (logger, capture) = testing::capture_logger(logger_config().default_type("server"));
+        logger.log(&Record::builder()
+            .args(format_args!("Started"))
+            .level(Level::Info)
+            .build());
+        logger.log(&Record::builder()
+            .args(format_args!("Match started"))
+            .level(Level::Info)
+            .key_values(&[("pogr.type", log::kv::Value::from("match"))])
+            .build());
+
+        let records = capture.records();
+        assert_eq!(records[0]["type"], "server");
+        assert_eq!(records[1]["type"], "match");
+    }
+
+    #[test]
+    fn test_scalar_record_tags_keep_global_tags() {
+        let (logger, capture) = testing::capture_logger(logger_config());
+        logger.log(&Record::builder()
+            .args(format_args!("Hotfix deployed"))
+            .level(Level::Info)
+            .key_values(&[("pogr.tags", log::kv::Value::from_serde(&json!("hotfix")))])
+            .build());
+
+        assert_eq!(capture.records()[0]["tags"], json!({"region": "eu-west", "shard": 3}));
+    }
+
+    #[test]
+    fn test_tags_changed_at_runtime() {
+        let (mut logger, capture) = testing::capture_logger(logger_config());
+        assert_eq!(logger.set_tag("git_sha", "4f2c1d9"), None);
+        assert_eq!(logger.set_tag("shard", 4), Some(json!(3)));
+        assert_eq!(logger.remove_tag("region"), Some(json!("eu-west")));
+        assert_eq!(logger.remove_tag("region"), None);
+        logger.log(&Record::builder()
+            .args(format_args!("Deployed"))
+            .level(Level::Info)
+            .build());
+
+        assert_eq!(capture.records()[0]["tags"], json!({"git_sha": "4f2c1d9", "shard": 4}));
+    }
+
+    #[test]
+    fn test_no_tags_without_global_tags() {
+        let (mut logger, capture) = testing::capture_logger(LoggerConfig { tags: Default::default(), ..logger_config() });
+        logger.log(&Record::builder()
+            .args(format_args!("Untagged"))
+            .level(Level::Info)
+            .build());
+        logger.set_tag("build_id", "b-118");
+        logger.remove_tag("build_id");
+        logger.log(&Record::builder()
+            .args(format_args!("Still untagged"))
+            .level(Level::Info)
+            .build());
+
+        for record in capture.records() {
+            assert!(record.get("tags").is_none(), "{}", record);
+        }
+    }
+
+    #[test]
+    fn test_global_logger_tags() {
+        testing::install(logger_config());
+        let capture = testing::capture();
+        pogr_error!(tags: {"mode": "casual"}, "Lobby opened");
+
+        assert_eq!(capture.records()[0]["tags"], json!({"region": "eu-west", "shard": 3, "mode": "casual"}));
+    }
+
+    #[tokio::test]
+    async fn test_custom_log_merges_tags() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server.mock("POST", "/v1/intake/logs")
+            .match_body(mockito::Matcher::PartialJson(json!({"tags": {"region": "eu-west", "shard": 1, "queue": "solo"}})))
+            .with_status(200)
+            .create_async().await;
+        let untagged = server.mock("POST", "/v1/intake/logs")
+            .match_body(mockito::Matcher::PartialJson(json!({"log": "untagged", "tags": {"region": "eu-west", "shard": 3}})))
+            .with_status(200)
+            .create_async().await;
+        let logger = pogr_log_rs::POGRLogger::new(
+            reqwest::Client::new(),
+            Some(format!("{}/v1/intake/logs", server.url())),
+            pogr_log_rs::LogConfig::AccessKeys {
+                access_key: "test_access_key".to_string(),
+                secret_key: "test_secret_key".to_string(),
+                logger_config: logger_config(),
+            },
+            logger_config(),
+        );
+
+        logger.custom_log_delivered(Level::Info, "queued", "queue", json!({}), json!({"shard": 1, "queue": "solo"})).await.unwrap();
+        logger.custom_log_delivered(Level::Info, "untagged", "queue", json!({}), serde_json::Value::Null).await.unwrap();
+
+        mock.assert_async().await;
+        untagged.assert_async().await;
+    }
+}
@@ -0,0 +1,237 @@
+mod common;
+
+use pogr_log_rs::circuit::CircuitBreakerConfig;
+use pogr_log_rs::hooks::DropReason;
+use pogr_log_rs::sampling::{Matcher, SampleKey, Sampler};
+use pogr_log_rs::{DeliveryError, POGRLogger, PayloadLimits};
+use log::Level;
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn logger_for(server: &mockito::Server) -> POGRLogger {
+        let mut logger = common::logger_for(server);
+        logger.set_error_callback(|_| {});
+        logger
+    }
+
+    /// Installs an `on_drop` hook recording the reason and message of each dropped record.
+    fn record_drops(logger: &mut POGRLogger) -> Arc<Mutex<Vec<(DropReason, String)>>> {
+        let drops = Arc::new(Mutex::new(Vec::new()));
+        let sink = drops.clone();
+        logger.on_drop(move |reason, payload| {
+            sink.lock().unwrap().push((reason, payload["log"].as_str().unwrap_or_default().to_string()));
+        });
+        drops
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_delivery_error_hook_receives_error_and_payload() {
+        let mut server = mockito::Server::new_async().await;
+        server.mock("POST", "/v1/intake/logs")
+            .with_status(500)
+            .create_async().await;
+
+        let mut logger = logger_for(&server);
+        let failures = Arc::new(Mutex::new(Vec::new()));
+        let sink = failures.clone();
+        logger.on_delivery_error(move |error, payloads| {
+            let status = match error {
+                DeliveryError::Status(status) => Some(*status),
+                _ => None,
+            };
+            sink.lock().unwrap().push((status, payloads.to_vec()));
+        });
+        common::log_and_flush(&logger, Level::Error, "lost record");
+
+        let failures = failures.lock().unwrap();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].0, Some(StatusCode::INTERNAL_SERVER_ERROR));
+        assert_eq!(failures[0].1.len(), 1);
+        assert_eq!(failures[0].1[0]["log"], "lost record");
+        assert_eq!(failures[0].1[0]["severity"], "error");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_auth_failure_hook() {
+        let mut server = mockito::Server::new_async().await;
+        server.mock("POST", "/v1/intake/logs")
+            .with_status(403)
+            .expect(2)
+            .create_async().await;
+
+        let mut logger = logger_for(&server);
+        let auth_failures = Arc::new(Mutex::new(0));
+        let delivery_errors = Arc::new(Mutex::new(0));
+        let (auth_sink, delivery_sink) = (auth_failures.clone(), delivery_errors.clone());
+        logger.on_auth_failure(move |error| {
+            assert!(error.is_auth_failure());
+            *auth_sink.lock().unwrap() += 1;
+        });
+        logger.on_delivery_error(move |_, _| *delivery_sink.lock().unwrap() += 1);
+        common::log_and_flush(&logger, Level::Info, "forbidden");
+
+        assert_eq!(*auth_failures.lock().unwrap(), 1);
+        assert_eq!(*delivery_errors.lock().unwrap(), 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_server_errors_are_not_auth_failures() {
+        let mut server = mockito::Server::new_async().await;
+        server.mock("POST", "/v1/intake/logs")
+            .with_status(503)
+            .create_async().await;
+
+        let mut logger = logger_for(&server);
+        let auth_failures = Arc::new(Mutex::new(0));
+        let sink = auth_failures.clone();
+        logger.on_auth_failure(move |_| *sink.lock().unwrap() += 1);
+        common::log_and_flush(&logger, Level::Info, "unavailable");
+
+        assert_eq!(*auth_failures.lock().unwrap(), 0);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_drop_hook_reports_reasons() {
+        let mut server = mockito::Server::new_async().await;
+        server.mock("POST", "/v1/intake/logs")
+            .with_status(200)
+            .create_async().await;
+
+        let mut logger = logger_for(&server);
+        let drops = record_drops(&mut logger);
+        logger.set_sampler(Sampler::new().first_then_every(Matcher::Level(Level::Warn), SampleKey::Rule, 1, 100));
+        logger.set_dedup_window(Duration::from_secs(60));
+        common::log_and_flush(&logger, Level::Warn, "kept");
+        common::log_and_flush(&logger, Level::Warn, "sampled out");
+        // A flush closes the dedup window, so the repeat has to come before it.
+        common::log_message(&logger, Level::Info, "repeated");
+        common::log_and_flush(&logger, Level::Info, "repeated");
+
+        logger.set_payload_limits(PayloadLimits {
+            max_total_bytes: Some(10),
+            ..PayloadLimits::unlimited()
+        });
+        common::log_and_flush(&logger, Level::Info, "too large");
+
+        assert_eq!(*drops.lock().unwrap(), vec![
+            (DropReason::Sampled, "sampled out".to_string()),
+            (DropReason::Deduplicated, "repeated".to_string()),
+            (DropReason::Oversized, "too large".to_string()),
+        ]);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_drop_hook_reports_buffer_evictions() {
+        let mut server = mockito::Server::new_async().await;
+        server.mock("POST", "/v1/intake/logs")
+            .with_status(503)
+            .create_async().await;
+
+        let mut logger = logger_for(&server);
+        let drops = record_drops(&mut logger);
+        logger.on_delivery_error(|_, _| {});
+        logger.set_circuit_breaker(CircuitBreakerConfig::new()
+            .failure_threshold(1)
+            .open_duration(Duration::from_secs(60))
+            .buffer_capacity(1));
+        common::log_and_flush(&logger, Level::Info, "opens the circuit");
+        common::log_and_flush(&logger, Level::Info, "replaces it in the buffer");
+
+        assert_eq!(*drops.lock().unwrap(), vec![(DropReason::BufferFull, "opens the circuit".to_string())]);
+    }
+
+    /// Installs an `on_delivery_error` hook recording the error and message of each failed payload.
+    fn record_failures(logger: &mut POGRLogger) -> Arc<Mutex<Vec<(String, String)>>> {
+        let failures = Arc::new(Mutex::new(Vec::new()));
+        let sink = failures.clone();
+        logger.on_delivery_error(move |error, payloads| {
+            for payload in payloads {
+                sink.lock().unwrap().push((error.to_string(), payload["log"].as_str().unwrap_or_default().to_string()));
+            }
+        });
+        failures
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_buffered_then_delivered_is_not_a_failure() {
+        let mut server = mockito::Server::new_async().await;
+        let down = server.mock("POST", "/v1/intake/logs")
+            .with_status(503)
+            .create_async().await;
+
+        let mut logger = logger_for(&server);
+        let failures = record_failures(&mut logger);
+        logger.set_circuit_breaker(CircuitBreakerConfig::new()
+            .failure_threshold(1)
+            .open_duration(Duration::from_millis(200)));
+        common::log_and_flush(&logger, Level::Info, "buffered");
+        down.remove_async().await;
+        let up = server.mock("POST", "/v1/intake/logs")
+            .match_body(mockito::Matcher::PartialJson(json!({"log": "buffered"})))
+            .with_status(200)
+            .create_async().await;
+
+        // The probe timer sends the buffered record once the circuit has been open long enough.
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while logger.stats().sent == 0 {
+            assert!(std::time::Instant::now() < deadline, "buffered record not delivered");
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        up.assert_async().await;
+        assert!(failures.lock().unwrap().is_empty());
+        assert_eq!(logger.stats().failed, 0);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_buffered_records_fail_when_the_logger_is_dropped() {
+        let mut server = mockito::Server::new_async().await;
+        server.mock("POST", "/v1/intake/logs")
+            .with_status(503)
+            .create_async().await;
+
+        let mut logger = logger_for(&server);
+        let failures = record_failures(&mut logger);
+        logger.set_circuit_breaker(CircuitBreakerConfig::new()
+            .failure_threshold(2)
+            .open_duration(Duration::from_secs(60)));
+        common::log_and_flush(&logger, Level::Info, "below the threshold");
+        common::log_and_flush(&logger, Level::Info, "opens the circuit");
+        common::log_and_flush(&logger, Level::Info, "held back");
+        assert_eq!(logger.stats().failed, 1);
+        assert_eq!(failures.lock().unwrap().len(), 1);
+
+        drop(logger);
+        let failures = failures.lock().unwrap();
+        let messages: Vec<&str> = failures.iter().map(|(_, message)| message.as_str()).collect();
+        assert_eq!(messages, ["below the threshold", "opens the circuit", "held back"]);
+        assert_eq!(failures[0].0, "HTTP Error: 503 Service Unavailable");
+        assert_eq!(failures[2].0, DeliveryError::CircuitOpen.to_string());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_no_hooks_called_on_success() {
+        let mut server = mockito::Server::new_async().await;
+        server.mock("POST", "/v1/intake/logs")
+            .with_status(200)
+            .create_async().await;
+
+        let mut logger = logger_for(&server);
+        let calls: Arc<Mutex<Vec<Value>>> = Arc::new(Mutex::new(Vec::new()));
+        let (errors, auth) = (calls.clone(), calls.clone());
+        logger.on_delivery_error(move |_, payloads| errors.lock().unwrap().extend_from_slice(payloads));
+        logger.on_auth_failure(move |_| auth.lock().unwrap().push(Value::Null));
+        let drops = record_drops(&mut logger);
+        common::log_and_flush(&logger, Level::Info, "delivered");
+
+        assert!(calls.lock().unwrap().is_empty());
+        assert!(drops.lock().unwrap().is_empty());
+    }
+}
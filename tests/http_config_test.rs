@@ -0,0 +1,168 @@
+use pogr_log_rs::http::{ClientConfigError, HttpConfig};
+use pogr_log_rs::{LogConfig, LoggerConfig, POGRLogger};
+use log::{Level, Log, Record};
+use serde_json::json;
+use std::time::Duration;
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn auth_config() -> LogConfig {
+        LogConfig::AccessKeys {
+            access_key: "test_access_key".to_string(),
+            secret_key: "test_secret_key".to_string(),
+            logger_config: LoggerConfig {
+                service: "test_service".to_string(),
+                environment: "test_env".to_string(),
+                default_type: None,
+                tags: Default::default(),
+            },
+        }
+    }
+
+    // All environment variables are handled in one test, since tests in this file share the process environment.
+    #[test]
+    fn test_from_env() {
+        assert_eq!(HttpConfig::from_env().unwrap(), HttpConfig::default());
+
+        std::env::set_var("POGR_HTTP_CONNECT_TIMEOUT_MS", "2500");
+        std::env::set_var("POGR_HTTP_REQUEST_TIMEOUT_MS", "0");
+        std::env::set_var("POGR_HTTP_PROXY", "http://proxy.internal:3128");
+        std::env::set_var("POGR_HTTP_NO_PROXY", "localhost,.internal");
+        std::env::set_var("POGR_HTTP_POOL_MAX_IDLE_PER_HOST", "4");
+        std::env::set_var("POGR_HTTP2_PRIOR_KNOWLEDGE", "true");
+        let config = HttpConfig::from_env().unwrap();
+        assert_eq!(config.connect_timeout, Some(Duration::from_millis(2500)));
+        assert_eq!(config.request_timeout, None);
+        assert_eq!(config.proxy.as_deref(), Some("http://proxy.internal:3128"));
+        assert_eq!(config.no_proxy.as_deref(), Some("localhost,.internal"));
+        assert_eq!(config.pool_max_idle_per_host, Some(4));
+        assert!(config.http2_prior_knowledge);
+        assert_eq!(config.tcp_keepalive, Some(Duration::from_secs(60)));
+
+        std::env::set_var("POGR_HTTP_TCP_KEEPALIVE_MS", "soon");
+        match HttpConfig::from_env() {
+            Err(ClientConfigError::Env { var, value }) => {
+                assert_eq!(var, "POGR_HTTP_TCP_KEEPALIVE_MS");
+                assert_eq!(value, "soon");
+            },
+            other => panic!("expected an Env error, got {:?}", other),
+        }
+
+        for var in [
+            "POGR_HTTP_CONNECT_TIMEOUT_MS",
+            "POGR_HTTP_REQUEST_TIMEOUT_MS",
+            "POGR_HTTP_PROXY",
+            "POGR_HTTP_NO_PROXY",
+            "POGR_HTTP_POOL_MAX_IDLE_PER_HOST",
+            "POGR_HTTP2_PRIOR_KNOWLEDGE",
+            "POGR_HTTP_TCP_KEEPALIVE_MS",
+        ] {
+            std::env::remove_var(var);
+        }
+    }
+
+    #[test]
+    fn test_deserialize_keeps_defaults_for_missing_fields() {
+        let config: HttpConfig = serde_json::from_value(json!({
+            "connect_timeout_ms": 2000,
+            "request_timeout_ms": null,
+            "proxy": "socks5://10.0.0.1:1080",
+            "pool_idle_timeout_ms": 90000,
+        })).unwrap();
+        assert_eq!(config, HttpConfig {
+            connect_timeout: Some(Duration::from_secs(2)),
+            request_timeout: None,
+            proxy: Some("socks5://10.0.0.1:1080".to_string()),
+            pool_idle_timeout: Some(Duration::from_secs(90)),
+            ..HttpConfig::default()
+        });
+
+        let round_trip: HttpConfig = serde_json::from_value(serde_json::to_value(&config).unwrap()).unwrap();
+        assert_eq!(round_trip, config);
+    }
+
+    #[test]
+    fn test_invalid_proxy_is_rejected() {
+        let config = HttpConfig {
+            proxy: Some("not a url".to_string()),
+            ..HttpConfig::default()
+        };
+        assert!(matches!(config.build_client(None), Err(ClientConfigError::Proxy(_))));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_requests_go_through_the_proxy() {
+        let mut proxy = mockito::Server::new_async().await;
+        let mock = proxy.mock("POST", "/v1/intake/logs")
+            .match_header("host", "intake.pogr.invalid")
+            .match_body(mockito::Matcher::PartialJson(json!({"log": "via proxy"})))
+            .with_status(200)
+            .expect(1)
+            .create_async().await;
+
+        let logger = POGRLogger::builder(auth_config())
+            .api_url("http://intake.pogr.invalid/v1/intake/logs")
+            .http(HttpConfig {
+                proxy: Some(proxy.url()),
+                ..HttpConfig::default()
+            })
+            .build()
+            .unwrap();
+        logger.log(&Record::builder()
+            .args(format_args!("via proxy"))
+            .level(Level::Info)
+            .build());
+
+        assert!(logger.flush_timeout(Duration::from_secs(5)));
+        mock.assert_async().await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_request_timeout_applies() {
+        // Accepts connections but never answers.
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut open = Vec::new();
+            while let Ok((stream, _)) = listener.accept().await {
+                open.push(stream);
+            }
+        });
+
+        let client = HttpConfig {
+            request_timeout: Some(Duration::from_millis(200)),
+            ..HttpConfig::default()
+        }.build_client(None).unwrap();
+        let error = client.get(format!("http://{}/", addr)).send().await.unwrap_err();
+        assert!(error.is_timeout());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_builder_uses_logger_config_from_credentials() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server.mock("POST", "/v1/intake/logs")
+            .match_body(mockito::Matcher::PartialJson(json!({
+                "service": "test_service",
+                "environment": "test_env",
+                "log": "built",
+            })))
+            .with_status(200)
+            .expect(1)
+            .create_async().await;
+
+        let logger = POGRLogger::builder(auth_config())
+            .api_url(format!("{}/v1/intake/logs", server.url()))
+            .build()
+            .unwrap();
+        logger.log(&Record::builder()
+            .args(format_args!("built"))
+            .level(Level::Info)
+            .build());
+
+        assert!(logger.flush_timeout(Duration::from_secs(5)));
+        mock.assert_async().await;
+    }
+}
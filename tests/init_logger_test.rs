@@ -0,0 +1,41 @@
+use pogr_log_rs::{init_logger, LogConfig, LoggerConfig};
+use log::LevelFilter;
+use serde_json::json;
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // In its own file: it installs the global logger and sets process environment variables.
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_invalid_http_env_falls_back_to_defaults() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server.mock("POST", "/v1/intake/logs")
+            .match_body(mockito::Matcher::PartialJson(json!({"log": "Started"})))
+            .with_status(200)
+            .create_async().await;
+        let logger_config = LoggerConfig {
+            service: "test_service".to_string(),
+            environment: "test_env".to_string(),
+            default_type: None,
+            tags: Default::default(),
+        };
+
+        std::env::set_var("POGR_HTTP_TCP_KEEPALIVE_MS", "soon");
+        init_logger(
+            LogConfig::AccessKeys {
+                access_key: "test_access_key".to_string(),
+                secret_key: "test_secret_key".to_string(),
+                logger_config: logger_config.clone(),
+            },
+            Some(format!("{}/v1/intake/logs", server.url())),
+            logger_config,
+            LevelFilter::Info,
+        );
+        log::error!("Started");
+        tokio::task::spawn_blocking(|| log::logger().flush()).await.unwrap();
+
+        mock.assert_async().await;
+    }
+}
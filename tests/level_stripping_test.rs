@@ -0,0 +1,70 @@
+extern crate log;
+
+use log::{Log, Metadata, Record};
+use once_cell::sync::Lazy;
+use pogr_log_rs::{pogr_debug, pogr_info, structured_log};
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+struct TestLogger {
+    pub messages: Mutex<Vec<String>>,
+}
+
+impl Log for TestLogger {
+    fn enabled(&self, _metadata: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        self.messages.lock().unwrap().push(format!("{}", record.args()));
+    }
+
+    fn flush(&self) {}
+}
+
+static TEST_LOGGER: Lazy<TestLogger> = Lazy::new(|| TestLogger { messages: Mutex::new(vec![]) });
+
+static EVALUATED: AtomicUsize = AtomicUsize::new(0);
+
+/// Stands in for an expensive argument; counts how often it is built.
+fn expensive() -> Value {
+    EVALUATED.fetch_add(1, Ordering::SeqCst);
+    json!({"frame": 1})
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // One test, so that the counter and the max level are not shared between threads.
+    #[test]
+    fn test_disabled_levels_do_not_evaluate_arguments() {
+        log::set_logger(&*TEST_LOGGER).unwrap();
+
+        // Filtered at runtime.
+        log::set_max_level(log::LevelFilter::Info);
+        structured_log!(log::Level::Debug, "frame", "render", expensive(), json!({}));
+        pogr_debug!(data: expensive(), "frame {}", EVALUATED.fetch_add(1, Ordering::SeqCst));
+        assert_eq!(EVALUATED.load(Ordering::SeqCst), 0);
+        assert!(TEST_LOGGER.messages.lock().unwrap().is_empty());
+
+        // Filtered at compile time: `--features max_level_info`, or `release_max_level_warn` in a
+        // release build, strips debug even though the runtime level would let it through.
+        log::set_max_level(log::LevelFilter::Trace);
+        pogr_debug!(data: expensive(), "frame");
+        if log::STATIC_MAX_LEVEL < log::LevelFilter::Debug {
+            assert_eq!(EVALUATED.load(Ordering::SeqCst), 0);
+            assert!(TEST_LOGGER.messages.lock().unwrap().is_empty());
+        } else {
+            assert_eq!(EVALUATED.load(Ordering::SeqCst), 1);
+        }
+
+        pogr_info!(data: expensive(), "info");
+        if log::STATIC_MAX_LEVEL < log::LevelFilter::Info {
+            assert!(TEST_LOGGER.messages.lock().unwrap().is_empty());
+        } else {
+            assert_eq!(TEST_LOGGER.messages.lock().unwrap().last().unwrap(), "info");
+        }
+    }
+}
@@ -0,0 +1,12 @@
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_structured_log_syntax() {
+        let t = trybuild::TestCases::new();
+        // The pass cases count the records they log, which a `max_level_*` feature would strip.
+        if log::STATIC_MAX_LEVEL == log::LevelFilter::Trace {
+            t.pass("tests/ui/pass/*.rs");
+        }
+        t.compile_fail("tests/ui/fail/*.rs");
+    }
+}
@@ -0,0 +1,42 @@
+#![cfg(feature = "metrics")]
+
+mod common;
+
+use log::Level;
+use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_record_metrics_publishes_through_the_facade() {
+        let mut server = mockito::Server::new_async().await;
+        server.mock("POST", "/v1/intake/logs")
+            .with_status(200)
+            .expect(3)
+            .create_async().await;
+
+        let logger = common::logger_for(&server);
+        for i in 0..3 {
+            common::log_and_flush(&logger, Level::Info, &format!("record {}", i));
+        }
+
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        metrics::with_local_recorder(&recorder, || logger.stats().record_metrics());
+
+        let metrics = snapshotter.snapshot().into_vec();
+        let value_of = |name: &str| metrics.iter()
+            .find(|(key, _, _, _)| key.key().name() == name)
+            .map(|(_, _, _, value)| value);
+        assert_eq!(value_of("pogr_log_records_sent_total"), Some(&DebugValue::Counter(3)));
+        assert_eq!(value_of("pogr_log_records_accepted_total"), Some(&DebugValue::Counter(3)));
+        assert!(matches!(value_of("pogr_log_queue_depth"), Some(DebugValue::Gauge(depth)) if depth.into_inner() == 0.0));
+        let dropped = metrics.iter()
+            .filter(|(key, _, _, _)| key.key().name() == "pogr_log_records_dropped_total")
+            .count();
+        assert_eq!(dropped, 3);
+    }
+}
@@ -0,0 +1,169 @@
+#![cfg(feature = "mock-intake")]
+
+mod common;
+
+use pogr_log_rs::mock_intake::{Fault, MockIntake, MockIntakeConfig};
+use pogr_log_rs::{Credentials, DeliveryError, LogConfig, POGRLogger, SessionTokenConfig};
+use log::Level;
+use reqwest::{Client, StatusCode};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn access_keys() -> Credentials {
+        Credentials::AccessKeys {
+            access_key: "test_access_key".to_string(),
+            secret_key: "test_secret_key".to_string(),
+        }
+    }
+
+    fn logger_for(intake: &MockIntake, access_key: &str) -> POGRLogger {
+        let mut logger = common::logger_with_key(intake.url(), access_key);
+        logger.set_error_callback(|_| {});
+        logger
+    }
+
+    /// Records the delivery errors of a logger.
+    fn record_errors(logger: &mut POGRLogger) -> Arc<Mutex<Vec<String>>> {
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        let sink = errors.clone();
+        logger.on_delivery_error(move |error, _| {
+            let description = match error {
+                DeliveryError::Status(status) => status.as_u16().to_string(),
+                DeliveryError::Http(_) => "http".to_string(),
+                other => other.to_string(),
+            };
+            sink.lock().unwrap().push(description);
+        });
+        errors
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_receives_records_from_logger() {
+        let intake = MockIntake::start(MockIntakeConfig::new().accept(access_keys())).await.unwrap();
+        let logger = logger_for(&intake, "test_access_key");
+        common::log_and_flush(&logger, Level::Info, "Player joined");
+
+        let logs = intake.logs();
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0]["log"], "Player joined");
+        assert_eq!(logs[0]["service"], "test_service");
+        assert_eq!(intake.requests(), 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_rejects_unknown_and_missing_credentials() {
+        let intake = MockIntake::start(MockIntakeConfig::new().accept(access_keys())).await.unwrap();
+        let mut logger = logger_for(&intake, "wrong_key");
+        let errors = record_errors(&mut logger);
+        common::log_and_flush(&logger, Level::Info, "rejected");
+        assert_eq!(*errors.lock().unwrap(), vec!["403".to_string()]);
+        assert!(intake.logs().is_empty());
+
+        let response = Client::new().post(intake.url()).json(&json!({"log": "anonymous"})).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_session_tokens_for_client_build_credentials() {
+        let intake = MockIntake::start(MockIntakeConfig::new().accept(Credentials::ClientBuild {
+            client_id: "client".to_string(),
+            build_id: "build".to_string(),
+        })).await.unwrap();
+        let mut logger = POGRLogger::new(
+            Client::new(),
+            Some(intake.url()),
+            LogConfig::ClientBuild {
+                client_id: "client".to_string(),
+                build_id: "build".to_string(),
+                logger_config: common::logger_config(),
+            },
+            common::logger_config(),
+        );
+        logger.enable_session_tokens(SessionTokenConfig::new(&intake.token_url()));
+        common::log_and_flush(&logger, Level::Info, "with a token");
+
+        assert_eq!(intake.logs().len(), 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_injected_faults() {
+        let intake = MockIntake::start(MockIntakeConfig::new()).await.unwrap();
+        let mut logger = logger_for(&intake, "any_key");
+        let errors = record_errors(&mut logger);
+        intake.fail_next(Fault::Status { status: 503 });
+        intake.fail_next(Fault::DropConnection);
+        intake.fail_next(Fault::Latency { ms: 200 });
+
+        common::log_and_flush(&logger, Level::Info, "unavailable");
+        common::log_and_flush(&logger, Level::Info, "dropped");
+        let started = Instant::now();
+        common::log_and_flush(&logger, Level::Info, "slow");
+        assert!(started.elapsed() >= Duration::from_millis(200));
+        common::log_and_flush(&logger, Level::Info, "healthy again");
+
+        assert_eq!(*errors.lock().unwrap(), vec!["503".to_string(), "http".to_string()]);
+        let messages: Vec<Value> = intake.logs().iter().map(|record| record["log"].clone()).collect();
+        assert_eq!(messages, vec![json!("slow"), json!("healthy again")]);
+        assert_eq!(intake.requests(), 4);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_rate_limit_sets_retry_after() {
+        let intake = MockIntake::start(MockIntakeConfig::new()).await.unwrap();
+        intake.fail_next(Fault::RateLimited { retry_after_secs: 7 });
+
+        let response = Client::new().post(intake.url())
+            .header("POGR_ACCESS", "key")
+            .header("POGR_SECRET", "secret")
+            .json(&json!({"log": "too fast"}))
+            .send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()["retry-after"], "7");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_query_and_control_endpoints() {
+        let intake = MockIntake::start(MockIntakeConfig::new()).await.unwrap();
+        let client = Client::new();
+        let base = format!("http://{}", intake.addr());
+        let post = |body: Value| client.post(intake.url())
+            .header("POGR_ACCESS", "key")
+            .header("POGR_SECRET", "secret")
+            .json(&body)
+            .send();
+        post(json!([
+            {"log": "Match started on harbor", "type": "match", "severity": "info"},
+            {"log": "Match ended", "type": "match", "severity": "warn"},
+            {"log": "Player joined", "type": "lobby", "severity": "info"},
+        ])).await.unwrap();
+
+        let query = |query: &str| {
+            let url = format!("{}/mock/logs{}", base, query);
+            let client = client.clone();
+            async move { client.get(url).send().await.unwrap().json::<Vec<Value>>().await.unwrap() }
+        };
+        assert_eq!(query("").await.len(), 3);
+        assert_eq!(query("?type=match").await.len(), 2);
+        assert_eq!(query("?type=match&severity=info").await[0]["log"], "Match started on harbor");
+        assert_eq!(query("?contains=on%20harbor").await.len(), 1);
+        assert_eq!(query("?limit=1").await.len(), 1);
+        let bad = client.get(format!("{}/mock/logs?colour=red", base)).send().await.unwrap();
+        assert_eq!(bad.status(), StatusCode::BAD_REQUEST);
+
+        let queued = client.post(format!("{}/mock/faults", base))
+            .json(&json!([{"fault": "status", "status": 500}, {"fault": "drop_connection"}]))
+            .send().await.unwrap();
+        assert_eq!(queued.json::<Value>().await.unwrap()["queued"], 2);
+        assert_eq!(post(json!({"log": "failed"})).await.unwrap().status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(post(json!({"log": "dropped"})).await.is_err());
+
+        client.delete(format!("{}/mock/logs", base)).send().await.unwrap();
+        assert!(intake.logs().is_empty());
+    }
+}
@@ -0,0 +1,182 @@
+#![cfg(feature = "otlp")]
+
+mod common;
+
+use pogr_log_rs::otlp::OtlpExporter;
+use pogr_log_rs::{LogError, POGRLogger};
+use log::{Level, Log, Record};
+use mockito::Matcher;
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn logger_for(server: &mockito::Server) -> POGRLogger {
+        let mut logger = common::logger_for(server);
+        logger.set_otlp_exporter(OtlpExporter::new(format!("{}/v1/logs", server.url())).header("x-tenant", "game")).unwrap();
+        logger
+    }
+
+    /// Exports are not waited for by `flush`, so tests wait for them to arrive.
+    async fn wait_until(mut done: impl FnMut() -> bool) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !done() && Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+
+    /// Logs a `matchmaking` record with data, and waits for its delivery.
+    fn log_matchmaking_and_flush(logger: &POGRLogger, message: &str) {
+        logger.log(&Record::builder()
+            .args(format_args!("{}", message))
+            .level(Level::Warn)
+            .key_values(&[
+                ("pogr.type", log::kv::Value::from("matchmaking")),
+                ("pogr.data", log::kv::Value::from_serde(&json!({"queue": "ranked", "players": 10}))),
+            ])
+            .build());
+        assert!(logger.flush_timeout(Duration::from_secs(5)));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_delivered_records_are_exported() {
+        let mut server = mockito::Server::new_async().await;
+        let intake = server.mock("POST", "/v1/intake/logs")
+            .with_status(200)
+            .create_async().await;
+        let collector = server.mock("POST", "/v1/logs")
+            .match_header("x-tenant", "game")
+            .match_body(Matcher::PartialJson(json!({
+                "resourceLogs": [{
+                    "resource": { "attributes": [
+                        { "key": "service.name", "value": { "stringValue": "test_service" } },
+                        { "key": "deployment.environment", "value": { "stringValue": "test_env" } },
+                    ] },
+                    "scopeLogs": [{
+                        "logRecords": [{
+                            "body": { "stringValue": "Queue is slow" },
+                            "severityText": "WARN",
+                            "severityNumber": 13,
+                        }],
+                    }],
+                }],
+            })))
+            .with_status(200)
+            .create_async().await;
+
+        let logger = logger_for(&server);
+        log_matchmaking_and_flush(&logger, "Queue is slow");
+        wait_until(|| collector.matched()).await;
+
+        intake.assert_async().await;
+        collector.assert_async().await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_fields_become_attributes() {
+        let mut server = mockito::Server::new_async().await;
+        server.mock("POST", "/v1/intake/logs")
+            .with_status(200)
+            .create_async().await;
+        let exported = Arc::new(Mutex::new(None));
+        let body = exported.clone();
+        server.mock("POST", "/v1/logs")
+            .with_status(200)
+            .with_body_from_request(move |request| {
+                *body.lock().unwrap() = serde_json::from_slice(request.body().unwrap()).ok();
+                Vec::new()
+            })
+            .create_async().await;
+
+        let logger = logger_for(&server);
+        log_matchmaking_and_flush(&logger, "Queue is slow");
+        wait_until(|| exported.lock().unwrap().is_some()).await;
+
+        let exported: serde_json::Value = exported.lock().unwrap().take().expect("nothing was exported");
+        let record = &exported["resourceLogs"][0]["scopeLogs"][0]["logRecords"][0];
+        let attributes = record["attributes"].as_array().unwrap();
+        assert!(attributes.contains(&json!({ "key": "pogr.type", "value": { "stringValue": "matchmaking" } })));
+        assert!(attributes.contains(&json!({ "key": "pogr.data", "value": { "kvlistValue": { "values": [
+            { "key": "players", "value": { "intValue": "10" } },
+            { "key": "queue", "value": { "stringValue": "ranked" } },
+        ] } } })));
+        assert!(record["observedTimeUnixNano"].as_str().unwrap().ends_with("000000"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_rejected_records_are_not_exported() {
+        let mut server = mockito::Server::new_async().await;
+        server.mock("POST", "/v1/intake/logs")
+            .with_status(400)
+            .create_async().await;
+        let collector = server.mock("POST", "/v1/logs")
+            .expect(0)
+            .create_async().await;
+
+        let logger = logger_for(&server);
+        log_matchmaking_and_flush(&logger, "Queue is slow");
+
+        collector.assert_async().await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_export_failures_go_to_the_error_callback() {
+        let mut server = mockito::Server::new_async().await;
+        server.mock("POST", "/v1/intake/logs")
+            .with_status(200)
+            .create_async().await;
+        server.mock("POST", "/v1/logs")
+            .with_status(503)
+            .create_async().await;
+
+        let mut logger = logger_for(&server);
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        let sink = errors.clone();
+        logger.set_error_callback(move |error| {
+            if let LogError::Otlp(e) = error {
+                sink.lock().unwrap().push(e.clone());
+            }
+        });
+        log_matchmaking_and_flush(&logger, "Queue is slow");
+        wait_until(|| !errors.lock().unwrap().is_empty()).await;
+
+        let errors = errors.lock().unwrap();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("503"), "{}", errors[0]);
+        assert_eq!(logger.stats().failed, 0);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_unresponsive_collector_does_not_delay_flush() {
+        let mut server = mockito::Server::new_async().await;
+        server.mock("POST", "/v1/intake/logs")
+            .with_status(200)
+            .create_async().await;
+        // Accepts connections but never answers.
+        let collector = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+
+        let mut logger = logger_for(&server);
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        let sink = errors.clone();
+        logger.set_error_callback(move |error| {
+            if let LogError::Otlp(e) = error {
+                sink.lock().unwrap().push(e.clone());
+            }
+        });
+        let exporter = OtlpExporter::new(format!("http://{}/v1/logs", collector.local_addr().unwrap()))
+            .timeout(Duration::from_millis(300));
+        logger.set_otlp_exporter(exporter).unwrap();
+
+        let started = Instant::now();
+        log_matchmaking_and_flush(&logger, "Queue is slow");
+        assert!(started.elapsed() < Duration::from_millis(300), "flush waited {:?}", started.elapsed());
+
+        // The export gives up after its timeout and is reported.
+        wait_until(|| !errors.lock().unwrap().is_empty()).await;
+        assert_eq!(errors.lock().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,68 @@
+use pogr_log_rs::limits::PayloadLimits;
+use pogr_log_rs::{init_with_logger, install_panic_hook, LogConfig, LoggerConfig, POGRLogger};
+use log::LevelFilter;
+use reqwest::Client;
+use serde_json::json;
+use std::sync::mpsc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_panic_is_reported_while_another_thread_is_logging() {
+        let mut server = mockito::Server::new();
+        let report = server.mock("POST", "/v1/intake/logs")
+            .match_body(mockito::Matcher::PartialJson(json!({"type": "panic", "log": "boom"})))
+            .with_status(200)
+            .expect(1)
+            .create();
+        server.mock("POST", "/v1/intake/logs")
+            .with_status(200)
+            .create();
+
+        let logger_config = LoggerConfig {
+            service: "test_service".to_string(),
+            environment: "test_env".to_string(),
+            default_type: None,
+            tags: Default::default(),
+        };
+        let mut logger = POGRLogger::new(
+            Client::new(),
+            Some(format!("{}/v1/intake/logs", server.url())),
+            LogConfig::AccessKeys {
+                access_key: "test_access_key".to_string(),
+                secret_key: "test_secret_key".to_string(),
+                logger_config: logger_config.clone(),
+            },
+            logger_config,
+        );
+        // The error callback runs inside `log`, with the global logger locked; a slow one keeps it
+        // locked while the other thread panics.
+        let (entered, logging) = mpsc::channel();
+        let entered = Mutex::new(entered);
+        logger.set_payload_limits(PayloadLimits { max_message_len: Some(8), ..PayloadLimits::unlimited() });
+        logger.set_error_callback(move |_| {
+            let _ = entered.lock().unwrap().send(());
+            std::thread::sleep(Duration::from_millis(500));
+        });
+        init_with_logger(logger, LevelFilter::Info);
+        install_panic_hook();
+
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let handle = runtime.handle().clone();
+        let busy = std::thread::spawn(move || {
+            let _runtime = handle.enter();
+            log::error!("a message too long to fit");
+        });
+        logging.recv().unwrap();
+
+        let result = std::thread::spawn(|| panic!("boom")).join();
+        assert!(result.is_err());
+        report.assert();
+        busy.join().unwrap();
+    }
+}
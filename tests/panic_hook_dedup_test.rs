@@ -0,0 +1,64 @@
+use pogr_log_rs::{init_with_logger, install_panic_hook, LogConfig, LoggerConfig, POGRLogger};
+use log::LevelFilter;
+use reqwest::Client;
+use serde_json::json;
+use std::time::Duration;
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // In its own file: it installs the global logger and the panic hook.
+    #[test]
+    fn test_panic_closes_dedup_windows_from_a_thread_without_runtime() {
+        let mut server = mockito::Server::new();
+        let summary = server.mock("POST", "/v1/intake/logs")
+            .match_body(mockito::Matcher::PartialJson(json!({"log": "dependency down", "repeat_count": 2})))
+            .with_status(200)
+            .expect(1)
+            .create();
+        let report = server.mock("POST", "/v1/intake/logs")
+            .match_body(mockito::Matcher::PartialJson(json!({"type": "panic", "log": "boom"})))
+            .with_status(200)
+            .expect(1)
+            .create();
+        let first = server.mock("POST", "/v1/intake/logs")
+            .with_status(200)
+            .expect(1)
+            .create();
+
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let logger_config = LoggerConfig::new("test_service", "test_env");
+        let mut logger = POGRLogger::new(
+            Client::new(),
+            Some(format!("{}/v1/intake/logs", server.url())),
+            LogConfig::AccessKeys {
+                access_key: "test_access_key".to_string(),
+                secret_key: "test_secret_key".to_string(),
+                logger_config: logger_config.clone(),
+            },
+            logger_config,
+        );
+        logger.set_dedup_window(Duration::from_secs(60));
+        init_with_logger(logger, LevelFilter::Info);
+        install_panic_hook();
+
+        // The window opens on the runtime; the panic happens on a thread outside of it.
+        runtime.block_on(async {
+            for _ in 0..3 {
+                log::error!("dependency down");
+            }
+        });
+        let result = std::thread::Builder::new()
+            .name("worker".to_string())
+            .spawn(|| panic!("boom"))
+            .unwrap()
+            .join();
+        assert!(result.is_err());
+
+        report.assert();
+        summary.assert();
+        first.assert();
+    }
+}
@@ -0,0 +1,53 @@
+use pogr_log_rs::{init_logger, install_panic_hook, LogConfig, LoggerConfig};
+use log::LevelFilter;
+use serde_json::json;
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_panics_are_reported_before_the_thread_unwinds() {
+        let mut server = mockito::Server::new();
+        let report = server.mock("POST", "/v1/intake/logs")
+            .match_body(mockito::Matcher::PartialJson(json!({
+                "service": "test_service",
+                "severity": "error",
+                "type": "panic",
+                "log": "boom",
+                "data": {"message": "boom", "thread": "worker"},
+            })))
+            .with_status(200)
+            .expect(1)
+            .create();
+
+        let logger_config = LoggerConfig {
+            service: "test_service".to_string(),
+            environment: "test_env".to_string(),
+            default_type: None,
+            tags: Default::default(),
+        };
+        init_logger(
+            LogConfig::AccessKeys {
+                access_key: "test_access_key".to_string(),
+                secret_key: "test_secret_key".to_string(),
+                logger_config: logger_config.clone(),
+            },
+            Some(format!("{}/v1/intake/logs", server.url())),
+            logger_config,
+            LevelFilter::Info,
+        );
+        install_panic_hook();
+
+        let result = std::thread::Builder::new()
+            .name("worker".to_string())
+            .spawn(|| panic!("boom"))
+            .unwrap()
+            .join();
+        assert!(result.is_err());
+
+        // The hook delivers synchronously, so the report is in by the time the thread is joined.
+        report.assert();
+    }
+}
@@ -0,0 +1,116 @@
+use pogr_log_rs::{LogConfig, LogError, LoggerConfig, PayloadLimits, POGRLogger};
+use reqwest::Client;
+use log::Level;
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncation_is_deterministic_and_marked() {
+        let limits = PayloadLimits {
+            max_message_len: Some(5),
+            max_depth: Some(3),
+            max_array_len: Some(2),
+            max_string_len: Some(3),
+            max_total_bytes: None,
+        };
+        let original = json!({
+            "log": "hello world",
+            "data": {
+                "name": "abcdef",
+                "list": [1, 2, 3, 4],
+                "deep": {"deeper": {"deepest": 1}},
+                "short": "ok",
+            },
+        });
+
+        let mut first = original.clone();
+        let mut second = original.clone();
+        let report = limits.apply(&mut first).unwrap().unwrap();
+        limits.apply(&mut second).unwrap();
+        assert_eq!(first, second);
+
+        assert_eq!(first["log"], "hello");
+        assert_eq!(first["data"]["name"], "abc");
+        assert_eq!(first["data"]["list"], json!([1, 2]));
+        assert_eq!(first["data"]["deep"], json!({"deeper": "[truncated]"}));
+        assert_eq!(first["data"]["short"], "ok");
+        assert_eq!(first["_truncated"], json!([
+            {"path": "/log", "reason": "max_message_len", "original": 11},
+            {"path": "/data/deep/deeper", "reason": "max_depth", "original": 1},
+            {"path": "/data/list", "reason": "max_array_len", "original": 4},
+            {"path": "/data/name", "reason": "max_string_len", "original": 6},
+        ]));
+        assert_eq!(report.truncated, vec!["/log", "/data/deep/deeper", "/data/list", "/data/name"]);
+        assert!(!report.dropped);
+    }
+
+    #[test]
+    fn test_total_bytes_sheds_data_then_message() {
+        let limits = PayloadLimits {
+            max_total_bytes: Some(200),
+            ..PayloadLimits::unlimited()
+        };
+
+        let mut payload = json!({"service": "svc", "log": "m".repeat(150), "data": {"blob": "x".repeat(500)}});
+        let report = limits.apply(&mut payload).unwrap().unwrap();
+        assert!(payload.to_string().len() <= 200);
+        assert!(payload["data"].is_null());
+        assert!(payload["log"].as_str().unwrap().starts_with("mmm"));
+        assert_eq!(report.truncated, vec!["/data", "/log"]);
+
+        let mut small = json!({"log": "fits"});
+        assert_eq!(limits.apply(&mut small), Ok(None));
+        assert_eq!(small, json!({"log": "fits"}));
+
+        let tiny = PayloadLimits { max_total_bytes: Some(10), ..PayloadLimits::unlimited() };
+        let mut envelope = json!({"service": "a service name that is too long"});
+        assert!(tiny.apply(&mut envelope).unwrap_err().dropped);
+    }
+
+    #[tokio::test]
+    async fn test_oversized_records_are_reported_and_sent_truncated() {
+        let mut server = mockito::Server::new_async().await;
+        let intake = server.mock("POST", "/v1/intake/logs")
+            .match_body(mockito::Matcher::PartialJson(json!({
+                "log": "0123456789",
+                "_truncated": [{"path": "/log", "reason": "max_message_len", "original": 20}],
+            })))
+            .with_status(200)
+            .expect(1)
+            .create_async().await;
+
+        let logger_config = LoggerConfig {
+            service: "test_service".to_string(),
+            environment: "test_env".to_string(),
+            default_type: None,
+            tags: Default::default(),
+        };
+        let mut logger = POGRLogger::new(
+            Client::new(),
+            Some(format!("{}/v1/intake/logs", server.url())),
+            LogConfig::AccessKeys {
+                access_key: "test_access_key".to_string(),
+                secret_key: "test_secret_key".to_string(),
+                logger_config: logger_config.clone(),
+            },
+            logger_config,
+        );
+        logger.set_payload_limits(PayloadLimits { max_message_len: Some(10), ..PayloadLimits::unlimited() });
+
+        let reported = Arc::new(Mutex::new(Vec::new()));
+        let sink = reported.clone();
+        logger.set_error_callback(move |error| sink.lock().unwrap().push(error.clone()));
+
+        logger.custom_log_delivered(Level::Info, "01234567890123456789", "test_log", json!({}), json!({})).await.unwrap();
+
+        intake.assert_async().await;
+        let reported = reported.lock().unwrap();
+        assert_eq!(reported.len(), 1);
+        assert!(matches!(&reported[0], LogError::Oversized(record) if record.truncated == vec!["/log"]));
+    }
+}
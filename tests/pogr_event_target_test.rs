@@ -0,0 +1,52 @@
+#![cfg(feature = "derive")]
+
+use log::{LevelFilter, Log, Metadata, Record};
+use once_cell::sync::Lazy;
+use pogr_log_rs::PogrEvent;
+use std::sync::Mutex;
+
+/// Records the target of every record, which the captured payloads do not include.
+struct TargetLogger {
+    pub targets: Mutex<Vec<String>>,
+}
+
+impl Log for TargetLogger {
+    fn enabled(&self, _metadata: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        self.targets.lock().unwrap().push(record.target().to_string());
+    }
+
+    fn flush(&self) {}
+}
+
+static TARGET_LOGGER: Lazy<TargetLogger> = Lazy::new(|| TargetLogger { targets: Mutex::new(vec![]) });
+
+mod matchmaking {
+    use pogr_log_rs::PogrEvent;
+    use serde::Serialize;
+
+    #[derive(Serialize, PogrEvent)]
+    #[pogr(type = "match_found", level = "error")]
+    pub struct MatchFound {
+        pub match_id: u64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_events_are_logged_with_their_module_as_target() {
+        log::set_logger(&*TARGET_LOGGER).unwrap();
+        log::set_max_level(LevelFilter::Trace);
+
+        matchmaking::MatchFound { match_id: 7 }.emit();
+
+        assert_eq!(matchmaking::MatchFound::TARGET, "pogr_event_target_test::matchmaking");
+        assert_eq!(*TARGET_LOGGER.targets.lock().unwrap(), ["pogr_event_target_test::matchmaking"]);
+    }
+}
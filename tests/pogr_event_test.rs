@@ -0,0 +1,73 @@
+#![cfg(feature = "derive")]
+
+use log::Level;
+use pogr_log_rs::testing;
+use pogr_log_rs::PogrEvent;
+use serde::Serialize;
+use serde_json::json;
+
+// Stands in for a crate that renames the dependency or re-exports it.
+use pogr_log_rs as telemetry;
+
+
+#[derive(Serialize, PogrEvent)]
+#[pogr(type = "login", level = "warn", message = "User logged in", tags(env = "production", platform = "web"))]
+struct Login {
+    user_id: u64,
+    method: &'static str,
+}
+
+#[derive(Serialize, PogrEvent)]
+#[pogr(type = "match_end")]
+struct MatchEnd<T: Serialize> {
+    score: T,
+}
+
+#[derive(Serialize, telemetry::PogrEvent)]
+#[pogr(crate = "crate::telemetry", type = "purchase", level = "warn", tags(store = "steam"))]
+struct Purchase {
+    sku: &'static str,
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_derived_event_metadata() {
+        assert_eq!(Login::TYPE, "login");
+        assert_eq!(Login::LEVEL, Level::Warn);
+        assert_eq!(Login::MESSAGE, "User logged in");
+
+        // Level and message default to `info` and the type.
+        assert_eq!(MatchEnd::<u32>::LEVEL, Level::Info);
+        assert_eq!(MatchEnd::<u32>::MESSAGE, "match_end");
+        assert_eq!(MatchEnd { score: 3 }.tags(), json!({}));
+    }
+
+    #[test]
+    fn test_emit_logs_through_structured_log() {
+        let capture = testing::capture();
+
+        Login { user_id: 123, method: "password" }.emit();
+
+        let records = capture.records();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0]["severity"], "warn");
+        assert_eq!(records[0]["log"], "User logged in");
+        assert_eq!(records[0]["type"], "login");
+        assert_eq!(records[0]["data"], json!({"user_id": 123, "method": "password"}));
+        assert_eq!(records[0]["tags"], json!({"env": "production", "platform": "web"}));
+    }
+
+    #[test]
+    fn test_crate_path_override() {
+        let capture = testing::capture();
+
+        Purchase { sku: "cosmetic-17" }.emit();
+
+        assert_eq!(Purchase::TYPE, "purchase");
+        assert_eq!(capture.records()[0]["tags"], json!({"store": "steam"}));
+    }
+}
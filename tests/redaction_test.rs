@@ -0,0 +1,104 @@
+use pogr_log_rs::redaction::{RedactAction, Redactor};
+use serde_json::json;
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_rules_reach_nested_objects_and_arrays() {
+        let redactor = Redactor::new()
+            .key(&["password", "Token"], RedactAction::Mask)
+            .key(&["ssn"], RedactAction::Drop);
+
+        let mut payload = json!({
+            "log": "login",
+            "data": {
+                "user": {"name": "alice", "PASSWORD": "hunter2", "ssn": "123-45-6789"},
+                "sessions": [
+                    {"token": "abc", "ttl": 60},
+                    {"token": {"nested": true}, "ssn": 1},
+                ],
+            },
+        });
+        redactor.redact(&mut payload);
+
+        assert_eq!(payload, json!({
+            "log": "login",
+            "data": {
+                "user": {"name": "alice", "PASSWORD": "[REDACTED]"},
+                "sessions": [
+                    {"token": "[REDACTED]", "ttl": 60},
+                    {"token": "[REDACTED]"},
+                ],
+            },
+        }));
+    }
+
+    #[test]
+    fn test_hashing_is_salted_and_stable() {
+        let redactor = Redactor::new().salt("salt").key(&["email"], RedactAction::Hash);
+
+        let mut first = json!({"data": {"email": "player@example.com"}});
+        let mut second = json!({"data": [{"email": "player@example.com"}]});
+        redactor.redact(&mut first);
+        redactor.redact(&mut second);
+
+        let hashed = first["data"]["email"].as_str().unwrap();
+        assert!(hashed.starts_with("sha256:"));
+        assert_eq!(hashed.len(), "sha256:".len() + 64);
+        assert_eq!(second["data"][0]["email"], hashed);
+
+        let unsalted = Redactor::new().hash("player@example.com");
+        assert_ne!(unsalted, hashed);
+    }
+
+    #[test]
+    fn test_pattern_rules_on_string_values() {
+        let redactor = Redactor::new()
+            .pattern(r"[\w.+-]+@[\w-]+\.[\w.]+", RedactAction::Hash).unwrap()
+            .pattern(r"\b\d{1,3}(\.\d{1,3}){3}\b", RedactAction::Mask).unwrap()
+            .pattern(r"^Bearer ", RedactAction::Drop).unwrap();
+
+        let mut payload = json!({
+            "log": "connection from 192.168.0.7 for bob@example.com",
+            "data": {
+                "peers": ["10.0.0.1", "not an ip", "Bearer abc.def"],
+                "header": "Bearer abc.def",
+                "count": 3,
+            },
+        });
+        redactor.redact(&mut payload);
+
+        let email_hash = Redactor::new().hash("bob@example.com");
+        assert_eq!(payload, json!({
+            "log": format!("connection from [REDACTED] for {}", email_hash),
+            "data": {
+                "peers": ["[REDACTED]", "not an ip"],
+                "count": 3,
+            },
+        }));
+    }
+
+    #[test]
+    fn test_path_rules() {
+        let redactor = Redactor::new()
+            .mask("***")
+            .path("/data/user/ip", RedactAction::Mask)
+            .path("/data/items/1", RedactAction::Drop)
+            .path("/tags/a~1b", RedactAction::Drop)
+            .path("/data/missing", RedactAction::Mask);
+
+        let mut payload = json!({
+            "data": {"user": {"ip": "1.2.3.4", "id": 7}, "items": [1, 2, 3]},
+            "tags": {"a/b": "x", "keep": "y"},
+        });
+        redactor.redact(&mut payload);
+
+        assert_eq!(payload, json!({
+            "data": {"user": {"ip": "***", "id": 7}, "items": [1, 3]},
+            "tags": {"keep": "y"},
+        }));
+    }
+}
@@ -0,0 +1,137 @@
+#![cfg(feature = "cli")]
+
+use mockito::Matcher;
+use pogr_log_rs::spool::Spool;
+use serde_json::json;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+use std::time::Instant;
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writes a dump with a deploy warning, an untimed info record, a stray line and a spooled crash.
+    fn dump(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("pogr-log-replay-test-{}-{}.ndjson", name, std::process::id()));
+        let lines = concat!(
+            r#"{"service":"game","environment":"prod","severity":"warn","log":"Deploy started","type":"deploy","tags":{"region":"eu"},"timestamp":"2026-10-18T12:30:00.250Z"}"#, "\n",
+            r#"{"service":"game","environment":"prod","severity":"info","log":"Player joined","type":"session"}"#, "\n",
+            "not json\n",
+        );
+        std::fs::write(&path, lines).unwrap();
+        Spool::open(&path).unwrap()
+            .append(&json!({"service": "game", "environment": "prod", "severity": "error", "log": "Crashed", "type": "crash", "data": {"code": 139}}))
+            .unwrap();
+        path
+    }
+
+    fn pogr_log(args: &[&str]) -> Output {
+        Command::new(env!("CARGO_BIN_EXE_pogr-log"))
+            .args(args)
+            .env_remove("POGR_INTAKE_URL")
+            .output()
+            .unwrap()
+    }
+
+    fn replay(server: &mockito::Server, args: &[&str]) -> Output {
+        let url = format!("{}/v1/intake/logs", server.url());
+        let connection = ["--url", &url, "--access-key", "test_access_key", "--secret-key", "test_secret_key", "--timeout-secs", "5"];
+        pogr_log(&[&["replay"], args, &connection].concat())
+    }
+
+    #[test]
+    fn test_spool_adds_timestamp() {
+        let path = dump("spool");
+        let text = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let spooled: serde_json::Value = serde_json::from_str(text.lines().last().unwrap()).unwrap();
+        assert!(spooled["timestamp"].as_u64().unwrap() > 1_700_000_000_000);
+        assert_eq!(spooled["data"], json!({"code": 139}));
+    }
+
+    #[test]
+    fn test_spool_keeps_logged_timestamp() {
+        let path = std::env::temp_dir().join(format!("pogr-log-replay-test-logged-{}.ndjson", std::process::id()));
+        Spool::open(&path).unwrap()
+            .append(&json!({"service": "game", "log": "Logged earlier", "timestamp": 1_760_000_000_000u64}))
+            .unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let spooled: serde_json::Value = serde_json::from_str(text.trim_end()).unwrap();
+        assert_eq!(spooled["timestamp"], 1_760_000_000_000u64);
+    }
+
+    #[test]
+    fn test_inspect_filters() {
+        let path = dump("inspect");
+        let path = path.to_str().unwrap();
+
+        let all = pogr_log(&["inspect", path]);
+        let severe = pogr_log(&["inspect", "--level", "warn", "--output", "ndjson", path]);
+        let tagged = pogr_log(&["inspect", "--tag", "region=eu", path]);
+        let timed = pogr_log(&["inspect", "--since", "2026-10-18", "--until", "2026-10-18T12:31:00Z", path]);
+        let typed = pogr_log(&["inspect", "--type", "session", "--type", "crash", "--output", "ndjson", path]);
+        std::fs::remove_file(path).unwrap();
+
+        assert_eq!(all.status.code(), Some(0));
+        let all = String::from_utf8(all.stdout).unwrap();
+        assert!(all.starts_with("2026-10-18T12:30:00.250Z WARN  game/prod deploy: Deploy started\n    tags {\"region\":\"eu\"}\n"), "{}", all);
+        assert!(all.contains("    data {\"code\":139}\n"));
+        assert!(String::from_utf8_lossy(&severe.stderr).contains(":3: not a JSON object"));
+
+        let severe = String::from_utf8(severe.stdout).unwrap();
+        let severities: Vec<_> = severe.lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap()["severity"].clone())
+            .collect();
+        assert_eq!(severities, [json!("warn"), json!("error")]);
+        assert_eq!(String::from_utf8(tagged.stdout).unwrap().lines().count(), 2);
+        assert!(String::from_utf8(timed.stdout).unwrap().contains("Deploy started"));
+        assert_eq!(String::from_utf8(typed.stdout).unwrap().lines().count(), 2);
+    }
+
+    #[test]
+    fn test_replay_preserves_timestamps() {
+        let mut server = mockito::Server::new();
+        let deploy = server.mock("POST", "/v1/intake/logs")
+            .match_header("POGR_ACCESS", "test_access_key")
+            .match_body(Matcher::PartialJson(json!({"service": "game", "log": "Deploy started", "timestamp": "2026-10-18T12:30:00.250Z"})))
+            .with_status(200)
+            .create();
+        let crash = server.mock("POST", "/v1/intake/logs")
+            .match_body(Matcher::PartialJson(json!({"type": "crash", "data": {"code": 139}})))
+            .with_status(200)
+            .create();
+        let path = dump("replay");
+
+        let started = Instant::now();
+        let output = replay(&server, &["--level", "warn", "--rate", "10", path.to_str().unwrap()]);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(output.status.code(), Some(0), "{}", String::from_utf8_lossy(&output.stderr));
+        assert!(String::from_utf8_lossy(&output.stderr).contains("replayed 2 of 2 records"));
+        assert!(started.elapsed().as_millis() >= 100);
+        deploy.assert();
+        crash.assert();
+    }
+
+    #[test]
+    fn test_replay_reports_failures() {
+        let mut server = mockito::Server::new();
+        server.mock("POST", "/v1/intake/logs")
+            .with_status(500)
+            .create();
+        let path = dump("failures");
+
+        let output = replay(&server, &["--type", "crash", "--rate", "0", path.to_str().unwrap()]);
+        let dry_run = replay(&server, &["--dry-run", path.to_str().unwrap()]);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(output.status.code(), Some(1));
+        assert!(String::from_utf8_lossy(&output.stderr).contains("replayed 0 of 1 records"));
+        assert_eq!(String::from_utf8(dry_run.stdout).unwrap(), "3 records would be replayed\n");
+    }
+}
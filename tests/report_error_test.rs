@@ -0,0 +1,231 @@
+use pogr_log_rs::report::ErrorReport;
+use pogr_log_rs::{init_with_logger, report_error, LogConfig, LoggerConfig, POGRLogger};
+use log::{LevelFilter, Log};
+use reqwest::Client;
+use serde_json::json;
+use std::error::Error;
+use std::fmt;
+use std::time::Duration;
+
+
+#[derive(Debug)]
+struct ConfigError {
+    path: String,
+    source: std::num::ParseIntError,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid config at {}", self.path)
+    }
+}
+
+impl Error for ConfigError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+fn config_error(path: &str, value: &str) -> ConfigError {
+    ConfigError {
+        path: path.to_string(),
+        source: value.parse::<u32>().unwrap_err(),
+    }
+}
+
+
+#[derive(Debug)]
+struct TimeoutError(u64);
+
+impl fmt::Display for TimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "timed out after {}s", self.0)
+    }
+}
+
+impl Error for TimeoutError {}
+
+
+#[derive(Debug)]
+struct QuotaError {
+    limit: u32,
+}
+
+impl fmt::Display for QuotaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "quota of {} exceeded", self.limit)
+    }
+}
+
+impl Error for QuotaError {}
+
+
+/// An error whose source is only known as `dyn Error`.
+#[derive(Debug)]
+struct RequestFailed(Box<dyn Error>);
+
+impl fmt::Display for RequestFailed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "request failed")
+    }
+}
+
+impl Error for RequestFailed {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&*self.0)
+    }
+}
+
+
+/// An error whose `Debug` output does not start with its type name.
+struct Opaque;
+
+impl fmt::Debug for Opaque {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<opaque>")
+    }
+}
+
+impl fmt::Display for Opaque {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "opaque failure")
+    }
+}
+
+impl Error for Opaque {}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn logger(server: &mockito::Server) -> POGRLogger {
+        let logger_config = LoggerConfig {
+            service: "test_service".to_string(),
+            environment: "test_env".to_string(),
+            default_type: None,
+            tags: Default::default(),
+        };
+        POGRLogger::new(
+            Client::new(),
+            Some(format!("{}/v1/intake/logs", server.url())),
+            LogConfig::AccessKeys {
+                access_key: "test_access_key".to_string(),
+                secret_key: "test_secret_key".to_string(),
+                logger_config: logger_config.clone(),
+            },
+            logger_config,
+        )
+    }
+
+    #[test]
+    fn test_report_walks_the_source_chain() {
+        let report = ErrorReport::new(&config_error("/etc/game.toml", "abc"));
+
+        assert_eq!(report.message, "invalid config at /etc/game.toml");
+        assert_eq!(report.type_name, "report_error_test::ConfigError");
+        assert_eq!(report.chain.len(), 2);
+        assert_eq!(report.chain[1].message, "invalid digit found in string");
+        assert_eq!(report.chain[1].type_name, "core::num::error::ParseIntError");
+
+        let serialized = serde_json::to_value(&report).unwrap();
+        assert_eq!(serialized["chain"][0]["type"], "report_error_test::ConfigError");
+    }
+
+    #[test]
+    fn test_fingerprint_groups_by_kind_not_message() {
+        let first = ErrorReport::new(&config_error("/etc/a.toml", "abc"));
+        let second = ErrorReport::new(&config_error("/etc/b.toml", "xyz"));
+        assert_eq!(first.fingerprint, second.fingerprint);
+        assert_eq!(first.fingerprint.len(), 16);
+
+        let io = std::io::Error::other("disk on fire");
+        assert_ne!(ErrorReport::new(&io).fingerprint, first.fingerprint);
+    }
+
+    #[test]
+    fn test_fingerprint_tells_custom_errors_apart_by_static_type() {
+        let timeout = ErrorReport::new(&TimeoutError(30));
+        let quota = ErrorReport::new(&QuotaError { limit: 100 });
+        assert_ne!(timeout.fingerprint, quota.fingerprint);
+        assert_eq!(timeout.fingerprint, ErrorReport::new(&TimeoutError(5)).fingerprint);
+
+        // Sources only known as `dyn Error` are `unknown`, whatever their `Debug` output.
+        let wrapped = |source: Box<dyn Error>| ErrorReport::new(&RequestFailed(source));
+        let report = wrapped(Box::new(TimeoutError(30)));
+        assert_eq!(report.chain[1].type_name, "unknown");
+        assert_eq!(report.fingerprint, wrapped(Box::new(QuotaError { limit: 100 })).fingerprint);
+    }
+
+    #[test]
+    fn test_fingerprint_of_errors_boxed_from_strings() {
+        let boxed = |message: &str| -> Box<dyn Error> { message.to_string().into() };
+        let first = ErrorReport::new(&*boxed("player 17 not found"));
+        let second = ErrorReport::new(&*boxed("player 42 not found"));
+        assert_eq!(first.type_name, "unknown");
+        assert_eq!(first.fingerprint, second.fingerprint);
+        // Grouped with other errors of unknown type, not by their quoted `Debug` output.
+        let opaque: Box<dyn Error> = Box::new(Opaque);
+        assert_eq!(first.fingerprint, ErrorReport::new(&*opaque).fingerprint);
+        assert_ne!(first.fingerprint, ErrorReport::new(&std::io::Error::other("player 17 not found")).fingerprint);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_logger_sends_error_report() {
+        let mut server = mockito::Server::new_async().await;
+        let intake = server.mock("POST", "/v1/intake/logs")
+            .match_body(mockito::Matcher::PartialJson(json!({
+                "severity": "error",
+                "type": "error",
+                "log": "invalid config at /etc/game.toml",
+                "data": {
+                    "error": {"chain": [{"type": "report_error_test::ConfigError"}, {"type": "core::num::error::ParseIntError"}]},
+                    "context": {"attempt": 3},
+                },
+            })))
+            .with_status(200)
+            .expect(1)
+            .create_async().await;
+
+        let logger = logger(&server);
+
+        logger.report_error(&config_error("/etc/game.toml", "abc"), json!({"attempt": 3}));
+
+        assert!(logger.flush_timeout(Duration::from_secs(5)));
+        intake.assert_async().await;
+    }
+
+    #[test]
+    fn test_type_names_do_not_depend_on_debug() {
+        let report = ErrorReport::new(&Opaque);
+        assert_eq!(report.type_name, "report_error_test::Opaque");
+
+        // Behind a trait object, only common types can be named.
+        let boxed: Box<dyn Error + Send + Sync> = Box::new(Opaque);
+        assert_eq!(ErrorReport::new(&*boxed).type_name, "unknown");
+        let boxed: Box<dyn Error> = Box::new(std::io::Error::other("gone"));
+        assert_eq!(ErrorReport::new(&*boxed).type_name, "std::io::error::Error");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_macro_and_method_share_fingerprints() {
+        let fingerprint = ErrorReport::new(&config_error("/etc/game.toml", "abc")).fingerprint;
+        let mut server = mockito::Server::new_async().await;
+        let intake = server.mock("POST", "/v1/intake/logs")
+            .match_body(mockito::Matcher::PartialJson(json!({"data": {"error": {"fingerprint": fingerprint}}})))
+            .with_status(200)
+            .expect(2)
+            .create_async().await;
+
+        let direct = logger(&server);
+        direct.report_error(&config_error("/etc/a.toml", "x"), json!(null));
+        init_with_logger(logger(&server), LevelFilter::Info);
+        report_error!(config_error("/etc/b.toml", "y"));
+
+        tokio::task::spawn_blocking(move || {
+            direct.flush();
+            log::logger().flush();
+        }).await.unwrap();
+        intake.assert_async().await;
+    }
+}
@@ -0,0 +1,122 @@
+use pogr_log_rs::sampling::{Decision, Matcher, SampleInput, SampleKey, Sampler};
+use pogr_log_rs::{LogConfig, LoggerConfig, POGRLogger};
+use log::{Level, Log, Record};
+use reqwest::Client;
+use serde_json::json;
+use std::time::Duration;
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input<'a>(level: Level, target: &'a str, template: &'a str, log_type: Option<&'a str>) -> SampleInput<'a> {
+        SampleInput { level, target, template, log_type }
+    }
+
+    fn kept(decisions: &[Decision]) -> Vec<u64> {
+        decisions.iter().filter_map(|decision| match decision {
+            Decision::Keep { suppressed } => Some(*suppressed),
+            Decision::Drop => None,
+        }).collect()
+    }
+
+    #[test]
+    fn test_ratio_per_level() {
+        let sampler = Sampler::new().ratio(Matcher::Level(Level::Debug), 0.25);
+
+        let debug: Vec<_> = (0..8).map(|_| sampler.check(&input(Level::Debug, "game", "a.rs:1", None))).collect();
+        assert_eq!(kept(&debug), vec![0, 3]);
+
+        // Records no rule matches are always kept.
+        assert_eq!(sampler.check(&input(Level::Info, "game", "a.rs:1", None)), Decision::Keep { suppressed: 0 });
+    }
+
+    #[test]
+    fn test_ratio_keeps_the_first_record() {
+        let sampler = Sampler::new().ratio(Matcher::Type("rare".to_string()), 0.1);
+
+        assert_eq!(sampler.check(&input(Level::Error, "game", "a.rs:1", Some("rare"))), Decision::Keep { suppressed: 0 });
+        let rest: Vec<_> = (0..10).map(|_| sampler.check(&input(Level::Error, "game", "a.rs:1", Some("rare")))).collect();
+        assert_eq!(rest[..9], [Decision::Drop; 9]);
+        assert_eq!(rest[9], Decision::Keep { suppressed: 9 });
+    }
+
+    #[test]
+    fn test_first_then_every_per_template() {
+        let sampler = Sampler::new().first_then_every(Matcher::Type("frame".to_string()), SampleKey::Template, 2, 3);
+
+        let hot: Vec<_> = (0..8).map(|_| sampler.check(&input(Level::Warn, "game", "render.rs:10", Some("frame")))).collect();
+        assert_eq!(hot, vec![
+            Decision::Keep { suppressed: 0 },
+            Decision::Keep { suppressed: 0 },
+            Decision::Drop,
+            Decision::Drop,
+            Decision::Keep { suppressed: 2 },
+            Decision::Drop,
+            Decision::Drop,
+            Decision::Keep { suppressed: 2 },
+        ]);
+
+        // Another call site has its own counter.
+        assert_eq!(sampler.check(&input(Level::Warn, "game", "render.rs:20", Some("frame"))), Decision::Keep { suppressed: 0 });
+    }
+
+    #[test]
+    fn test_rate_limit_per_target() {
+        let sampler = Sampler::new().rate_limit(Matcher::Target("game::net".to_string()), SampleKey::Target, 0.001, 2);
+
+        let net: Vec<_> = (0..5).map(|_| sampler.check(&input(Level::Warn, "game::net::socket", "a.rs:1", None))).collect();
+        assert_eq!(kept(&net), vec![0, 0]);
+
+        let other: Vec<_> = (0..5).map(|_| sampler.check(&input(Level::Warn, "game::netcode", "a.rs:1", None))).collect();
+        assert_eq!(kept(&other).len(), 5);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_logger_attaches_suppressed_counts() {
+        let mut server = mockito::Server::new_async().await;
+        let summarized = server.mock("POST", "/v1/intake/logs")
+            .match_body(mockito::Matcher::PartialJson(json!({"log": "frame took too long", "_suppressed": 1})))
+            .with_status(200)
+            .expect(2)
+            .create_async().await;
+
+        let logger_config = LoggerConfig {
+            service: "test_service".to_string(),
+            environment: "test_env".to_string(),
+            default_type: None,
+            tags: Default::default(),
+        };
+        let mut logger = POGRLogger::new(
+            Client::new(),
+            Some(format!("{}/v1/intake/logs", server.url())),
+            LogConfig::AccessKeys {
+                access_key: "test_access_key".to_string(),
+                secret_key: "test_secret_key".to_string(),
+                logger_config: logger_config.clone(),
+            },
+            logger_config,
+        );
+        logger.set_sampler(Sampler::new().first_then_every(Matcher::Any, SampleKey::Template, 1, 2));
+
+        for _ in 0..5 {
+            logger.log(&Record::builder()
+                .args(format_args!("frame took too long"))
+                .level(Level::Warn)
+                .target("game::render")
+                .file(Some("render.rs"))
+                .line(Some(42))
+                .build());
+        }
+
+        // Records are sent from background tasks; give them a moment to arrive.
+        for _ in 0..50 {
+            if summarized.matched_async().await {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        summarized.assert_async().await;
+    }
+}
@@ -0,0 +1,156 @@
+use pogr_log_rs::credentials::CallbackCredentials;
+use pogr_log_rs::{Credentials, LogConfig, LoggerConfig, POGRLogger, SessionTokenConfig};
+use reqwest::Client;
+use log::Level;
+use serde_json::json;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client_build_logger(intake_url: String) -> POGRLogger {
+        client_build_logger_for(intake_url, "test_client")
+    }
+
+    fn client_build_logger_for(intake_url: String, client_id: &str) -> POGRLogger {
+        let logger_config = LoggerConfig {
+            service: "test_service".to_string(),
+            environment: "test_env".to_string(),
+            default_type: None,
+            tags: Default::default(),
+        };
+        POGRLogger::new(
+            Client::new(),
+            Some(intake_url),
+            LogConfig::ClientBuild {
+                client_id: client_id.to_string(),
+                build_id: "test_build".to_string(),
+                logger_config: logger_config.clone(),
+            },
+            logger_config,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_session_token_is_exchanged_once_and_reused() {
+        let mut server = mockito::Server::new_async().await;
+
+        let exchange = server.mock("POST", "/v1/auth/token")
+            .match_body(mockito::Matcher::Json(json!({"client_id": "test_client", "build_id": "test_build"})))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"access_token": "session_1", "expires_in": 3600}"#)
+            .expect(1)
+            .create_async().await;
+        let intake = server.mock("POST", "/v1/intake/logs")
+            .match_header("authorization", "Bearer session_1")
+            .match_header("POGR_CLIENT", mockito::Matcher::Missing)
+            .with_status(200)
+            .expect(2)
+            .create_async().await;
+
+        let mut logger = client_build_logger(format!("{}/v1/intake/logs", server.url()));
+        logger.enable_session_tokens(SessionTokenConfig::new(&format!("{}/v1/auth/token", server.url())));
+
+        logger.custom_log_delivered(Level::Info, "first", "test_log", json!({}), json!({})).await.unwrap();
+        logger.custom_log_delivered(Level::Info, "second", "test_log", json!({}), json!({})).await.unwrap();
+
+        exchange.assert_async().await;
+        intake.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_session_token_is_refreshed_before_expiry() {
+        let mut server = mockito::Server::new_async().await;
+
+        // A token that lives for less than the refresh margin is replaced on every request.
+        let exchange = server.mock("POST", "/v1/auth/token")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"access_token": "short_lived", "expires_in": 30}"#)
+            .expect(2)
+            .create_async().await;
+        let intake = server.mock("POST", "/v1/intake/logs")
+            .match_header("authorization", "Bearer short_lived")
+            .with_status(200)
+            .expect(2)
+            .create_async().await;
+
+        let mut logger = client_build_logger(format!("{}/v1/intake/logs", server.url()));
+        let mut config = SessionTokenConfig::new(&format!("{}/v1/auth/token", server.url()));
+        config.refresh_before = Duration::from_secs(60);
+        logger.enable_session_tokens(config);
+
+        logger.custom_log_delivered(Level::Info, "first", "test_log", json!({}), json!({})).await.unwrap();
+        logger.custom_log_delivered(Level::Info, "second", "test_log", json!({}), json!({})).await.unwrap();
+
+        exchange.assert_async().await;
+        intake.assert_async().await;
+    }
+
+    async fn token_for(server: &mut mockito::Server, client_id: &str, token: &str) -> (mockito::Mock, mockito::Mock) {
+        let exchange = server.mock("POST", "/v1/auth/token")
+            .match_body(mockito::Matcher::Json(json!({"client_id": client_id, "build_id": "test_build"})))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({"access_token": token, "expires_in": 3600}).to_string())
+            .expect(1)
+            .create_async().await;
+        let intake = server.mock("POST", "/v1/intake/logs")
+            .match_header("authorization", format!("Bearer {}", token).as_str())
+            .with_status(200)
+            .expect(1)
+            .create_async().await;
+        (exchange, intake)
+    }
+
+    #[tokio::test]
+    async fn test_loggers_with_different_ids_get_their_own_tokens() {
+        let mut server = mockito::Server::new_async().await;
+        let (exchange_a, intake_a) = token_for(&mut server, "client_a", "session_a").await;
+        let (exchange_b, intake_b) = token_for(&mut server, "client_b", "session_b").await;
+
+        let config = SessionTokenConfig::new(&format!("{}/v1/auth/token", server.url()));
+        let mut logger_a = client_build_logger_for(format!("{}/v1/intake/logs", server.url()), "client_a");
+        logger_a.enable_session_tokens(config.clone());
+        let mut logger_b = client_build_logger_for(format!("{}/v1/intake/logs", server.url()), "client_b");
+        logger_b.enable_session_tokens(config);
+
+        logger_a.custom_log_delivered(Level::Info, "from a", "test_log", json!({}), json!({})).await.unwrap();
+        logger_b.custom_log_delivered(Level::Info, "from b", "test_log", json!({}), json!({})).await.unwrap();
+
+        exchange_a.assert_async().await;
+        intake_a.assert_async().await;
+        exchange_b.assert_async().await;
+        intake_b.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_rotated_ids_are_exchanged_for_a_new_token() {
+        let mut server = mockito::Server::new_async().await;
+        let (exchange_a, intake_a) = token_for(&mut server, "client_a", "session_a").await;
+        let (exchange_b, intake_b) = token_for(&mut server, "client_b", "session_b").await;
+
+        let rotated = Arc::new(AtomicBool::new(false));
+        let mut logger = client_build_logger(format!("{}/v1/intake/logs", server.url()));
+        logger.enable_session_tokens(SessionTokenConfig::new(&format!("{}/v1/auth/token", server.url())));
+        let provider_rotated = rotated.clone();
+        logger.set_credential_provider(CallbackCredentials::new(move || {
+            let client_id = if provider_rotated.load(Ordering::SeqCst) { "client_b" } else { "client_a" };
+            Ok(Credentials::ClientBuild { client_id: client_id.to_string(), build_id: "test_build".to_string() })
+        }));
+
+        logger.custom_log_delivered(Level::Info, "before", "test_log", json!({}), json!({})).await.unwrap();
+        rotated.store(true, Ordering::SeqCst);
+        logger.custom_log_delivered(Level::Info, "after", "test_log", json!({}), json!({})).await.unwrap();
+
+        exchange_a.assert_async().await;
+        intake_a.assert_async().await;
+        exchange_b.assert_async().await;
+        intake_b.assert_async().await;
+    }
+}
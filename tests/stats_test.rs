@@ -0,0 +1,144 @@
+mod common;
+
+use pogr_log_rs::sampling::{Matcher, SampleKey, Sampler};
+use pogr_log_rs::{init_with_logger, POGRLogger, PayloadLimits};
+use log::{Level, LevelFilter};
+use std::time::Duration;
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn logger_for(server: &mockito::Server) -> POGRLogger {
+        let mut logger = common::logger_for(server);
+        // Keeps oversized-record reports off stderr.
+        logger.set_error_callback(|_| {});
+        logger
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_counts_accepted_filtered_and_sent() {
+        let mut server = mockito::Server::new_async().await;
+        server.mock("POST", "/v1/intake/logs")
+            .with_status(200)
+            .expect(2)
+            .create_async().await;
+
+        let logger = logger_for(&server);
+        common::log_message(&logger, Level::Info, "first");
+        common::log_message(&logger, Level::Warn, "second");
+        common::log_message(&logger, Level::Debug, "too verbose");
+        assert!(logger.flush_timeout(Duration::from_secs(5)));
+
+        let stats = logger.stats();
+        assert_eq!(stats.accepted, 2);
+        assert_eq!(stats.filtered, 1);
+        assert_eq!(stats.sent, 2);
+        assert_eq!(stats.failed, 0);
+        assert_eq!(stats.queue_depth, 0);
+        assert!(stats.bytes_sent > 0);
+        assert!(stats.send_latency_p50.is_some());
+        assert!(stats.send_latency_p50 <= stats.send_latency_p99);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_counts_retries_and_failures() {
+        let mut server = mockito::Server::new_async().await;
+        server.mock("POST", "/v1/intake/logs")
+            .with_status(401)
+            .expect(2)
+            .create_async().await;
+
+        let logger = logger_for(&server);
+        common::log_message(&logger, Level::Info, "rejected");
+        assert!(logger.flush_timeout(Duration::from_secs(5)));
+
+        let stats = logger.stats();
+        assert_eq!(stats.retried, 1);
+        assert_eq!(stats.failed, 1);
+        assert_eq!(stats.sent, 0);
+        assert_eq!(stats.bytes_sent, 0);
+        assert_eq!(stats.send_latency_p50, None);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_counts_drops_by_reason() {
+        let mut server = mockito::Server::new_async().await;
+        server.mock("POST", "/v1/intake/logs")
+            .with_status(200)
+            .create_async().await;
+
+        let mut logger = logger_for(&server);
+        logger.set_sampler(Sampler::new().first_then_every(Matcher::Level(Level::Warn), SampleKey::Rule, 1, 100));
+        logger.set_dedup_window(Duration::from_secs(60));
+        common::log_message(&logger, Level::Warn, "sampled 1");
+        common::log_message(&logger, Level::Warn, "sampled 2");
+        common::log_message(&logger, Level::Info, "repeated");
+        common::log_message(&logger, Level::Info, "repeated");
+
+        logger.set_payload_limits(PayloadLimits {
+            max_total_bytes: Some(10),
+            ..PayloadLimits::unlimited()
+        });
+        common::log_message(&logger, Level::Info, "too large");
+        assert!(logger.flush_timeout(Duration::from_secs(5)));
+
+        let stats = logger.stats();
+        assert_eq!(stats.accepted, 5);
+        assert_eq!(stats.sampled, 1);
+        assert_eq!(stats.dropped.deduplicated, 1);
+        assert_eq!(stats.dropped.oversized, 1);
+        assert_eq!(stats.dropped.total(), 2);
+        // The flush also sends the summary of the repeats.
+        assert_eq!(stats.sent, 3);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_prometheus_text() {
+        let mut server = mockito::Server::new_async().await;
+        server.mock("POST", "/v1/intake/logs")
+            .with_status(200)
+            .create_async().await;
+
+        let logger = logger_for(&server);
+        common::log_message(&logger, Level::Info, "exported");
+        assert!(logger.flush_timeout(Duration::from_secs(5)));
+
+        let text = logger.stats().prometheus_text();
+        assert!(text.contains("# TYPE pogr_log_records_sent_total counter\npogr_log_records_sent_total 1\n"));
+        assert!(text.contains("pogr_log_records_accepted_total 1\n"));
+        assert!(text.contains("pogr_log_records_dropped_total{reason=\"oversized\"} 0\n"));
+        assert!(text.contains("pogr_log_queue_depth 0\n"));
+        assert!(text.contains("# TYPE pogr_log_send_latency_p99_seconds gauge\npogr_log_send_latency_p99_seconds "));
+        assert!(!text.contains("quantile"));
+        assert!(text.contains(&format!("pogr_log_endpoint_healthy{{url=\"{}/v1/intake/logs\"}} 1\n", server.url())));
+        // No circuit breaker is set.
+        assert!(!text.contains("pogr_log_circuit_state"));
+        for line in text.lines().filter(|line| !line.starts_with('#')) {
+            let value = line.rsplit(' ').next().unwrap();
+            assert!(value.parse::<f64>().is_ok(), "bad sample line: {}", line);
+        }
+    }
+
+    // The only test in this file that installs the global logger.
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_global_stats_after_init_with_logger() {
+        assert!(pogr_log_rs::stats().is_none());
+        let mut server = mockito::Server::new_async().await;
+        server.mock("POST", "/v1/intake/logs")
+            .with_status(200)
+            .expect(1)
+            .create_async().await;
+
+        init_with_logger(logger_for(&server), LevelFilter::Warn);
+        log::error!(target: "stats", "through the facade");
+        log::info!(target: "stats", "below the filter");
+        tokio::task::spawn_blocking(|| log::logger().flush()).await.unwrap();
+
+        let stats = pogr_log_rs::stats().expect("no global logger");
+        assert_eq!(stats.accepted, 1);
+        assert_eq!(stats.sent, 1);
+        assert_eq!(stats.queue_depth, 0);
+    }
+}
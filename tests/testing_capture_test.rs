@@ -0,0 +1,128 @@
+mod common;
+
+use pogr_log_rs::redaction::{RedactAction, Redactor};
+use pogr_log_rs::testing::{self, Expectation};
+use pogr_log_rs::{assert_logged, pogr_error, pogr_info, pogr_warn, report_error};
+use log::{Level, Log, Record};
+use serde_json::json;
+use std::thread;
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capture_logger_stores_enriched_payloads() {
+        let (logger, capture) = testing::capture_logger(common::logger_config());
+        logger.log(&Record::builder()
+            .args(format_args!("Player joined"))
+            .level(Level::Info)
+            .build());
+
+        let records = capture.records();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0]["service"], "test_service");
+        assert_eq!(records[0]["environment"], "test_env");
+        assert_eq!(records[0]["severity"], "info");
+        assert_eq!(records[0]["log"], "Player joined");
+        assert_eq!(logger.stats().sent, 1);
+    }
+
+    #[test]
+    fn test_captured_payloads_are_redacted() {
+        let (mut logger, capture) = testing::capture_logger(common::logger_config());
+        logger.set_redactor(Redactor::new().key(&["password"], RedactAction::Mask));
+        logger.log(&Record::builder()
+            .args(format_args!("Login"))
+            .level(Level::Info)
+            .key_values(&[("pogr.data", log::kv::Value::from_serde(&json!({"password": "hunter2", "user": "ann"})))])
+            .build());
+
+        assert_logged!(capture, Level::Info, data contains {"user": "ann"});
+        assert_eq!(capture.records()[0]["data"]["password"], "[REDACTED]");
+    }
+
+    #[test]
+    fn test_filtered_records_are_not_captured() {
+        let (logger, capture) = testing::capture_logger(common::logger_config());
+        logger.log(&Record::builder()
+            .args(format_args!("too verbose"))
+            .level(Level::Debug)
+            .build());
+
+        assert!(capture.is_empty());
+    }
+
+    #[test]
+    fn test_scoped_capture_sees_only_its_thread() {
+        let capture = testing::capture();
+        pogr_warn!(type: "lobby", "on the test thread");
+        thread::spawn(|| {
+            let other = testing::capture();
+            pogr_warn!(type: "lobby", "on another thread");
+            assert_eq!(other.len(), 1);
+        }).join().unwrap();
+
+        assert_eq!(capture.len(), 1);
+        assert_logged!(capture, Level::Warn, "lobby", message: "on the test thread");
+        capture.assert_not_logged(&Expectation::new().message("on another thread"));
+    }
+
+    #[test]
+    fn test_nested_scopes() {
+        let outer = testing::capture();
+        {
+            let inner = testing::capture();
+            pogr_warn!("inner");
+            assert_eq!(inner.len(), 1);
+        }
+        pogr_warn!("outer");
+
+        assert_eq!(outer.len(), 1);
+        assert_logged!(outer, Level::Warn, message: "outer");
+    }
+
+    #[test]
+    fn test_global_functions_reach_the_capture() {
+        let capture = testing::capture();
+        pogr_log_rs::set_tag("build_id", "b-118");
+        pogr_warn!("tagged");
+        pogr_log_rs::remove_tag("build_id");
+        report_error!("x".parse::<u32>().unwrap_err(), {"input": "x"});
+        pogr_log_rs::install_panic_hook();
+        let _ = std::panic::catch_unwind(|| panic!("captured panic"));
+
+        assert_logged!(capture, Level::Warn, message: "tagged", tags contains {"build_id": "b-118"});
+        assert_logged!(capture, Level::Error, "error", data contains {"context": {"input": "x"}});
+        assert_logged!(capture, Level::Error, "panic");
+    }
+
+    #[test]
+    fn test_expectation_matching() {
+        let capture = testing::capture();
+        pogr_error!(type: "match", data: {"map": "harbor", "score": {"red": 3, "blue": 1}}, tags: {"region": "eu"}, "Match ended");
+
+        let matching = Expectation::new()
+            .level(Level::Error)
+            .log_type("match")
+            .data_contains(json!({"score": {"red": 3}}))
+            .tags_contains(json!({"region": "eu"}));
+        assert!(capture.find(&matching).is_some());
+        assert!(capture.find(&Expectation::new().level(Level::Warn)).is_none());
+        assert!(capture.find(&Expectation::new().data_contains(json!({"score": {"red": 4}}))).is_none());
+        assert!(capture.find(&Expectation::new().data_contains(json!({"mode": "ranked"}))).is_none());
+
+        capture.clear();
+        assert!(capture.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "no captured record matches")]
+    fn test_assert_logged_panics_without_match() {
+        let capture = testing::capture();
+        pogr_info!(type: "lobby", "Player joined");
+
+        assert_logged!(capture, Level::Info, "match");
+    }
+}
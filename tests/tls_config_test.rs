@@ -0,0 +1,187 @@
+#![cfg(feature = "rustls")]
+
+use pogr_log_rs::tls::{spki_sha256_pin, TlsConfig, TlsError};
+use pogr_log_rs::{LogConfig, LoggerConfig, POGRLogger};
+use log::Level;
+use rcgen::{BasicConstraints, Certificate, CertificateParams, IsCa};
+use reqwest::Client;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio_rustls::rustls;
+
+
+/// A private CA with a server certificate for `localhost` and a client certificate.
+struct Pki {
+    ca_pem: String,
+    server: Certificate,
+    server_pem: String,
+    client_pem: String,
+    client_key_pem: String,
+    ca_der: Vec<u8>,
+}
+
+fn pki() -> Pki {
+    let mut ca_params = CertificateParams::new(Vec::<String>::new());
+    ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+    let ca = Certificate::from_params(ca_params).unwrap();
+    let server = Certificate::from_params(CertificateParams::new(vec!["localhost".to_string()])).unwrap();
+    let client = Certificate::from_params(CertificateParams::new(vec!["game-client".to_string()])).unwrap();
+    Pki {
+        ca_pem: ca.serialize_pem().unwrap(),
+        ca_der: ca.serialize_der().unwrap(),
+        server_pem: server.serialize_pem_with_signer(&ca).unwrap(),
+        client_pem: client.serialize_pem_with_signer(&ca).unwrap(),
+        client_key_pem: client.serialize_private_key_pem(),
+        server,
+    }
+}
+
+/// Serves HTTPS on a local port, answering every request with `200` and counting them.
+/// With `require_client_cert`, only clients presenting a certificate issued by the CA get through.
+/// `extra_pem` certificates are sent after the server's own, as if they were intermediates.
+async fn serve(pki: &Pki, require_client_cert: bool, extra_pem: &[&str]) -> (String, Arc<AtomicUsize>) {
+    let mut chain = vec![rustls::Certificate(rustls_pemfile::certs(&mut pki.server_pem.as_bytes()).unwrap().remove(0))];
+    for pem in extra_pem {
+        chain.extend(rustls_pemfile::certs(&mut pem.as_bytes()).unwrap().into_iter().map(rustls::Certificate));
+    }
+    let key = rustls::PrivateKey(pki.server.serialize_private_key_der());
+    let builder = rustls::ServerConfig::builder().with_safe_defaults();
+    let config = if require_client_cert {
+        let mut roots = rustls::RootCertStore::empty();
+        roots.add(&rustls::Certificate(pki.ca_der.clone())).unwrap();
+        builder.with_client_cert_verifier(rustls::server::AllowAnyAuthenticatedClient::new(roots).boxed())
+    } else {
+        builder.with_no_client_auth()
+    };
+    let config = config.with_single_cert(chain, key).unwrap();
+    let acceptor = tokio_rustls::TlsAcceptor::from(Arc::new(config));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("https://localhost:{}/v1/intake/logs", listener.local_addr().unwrap().port());
+    let requests = Arc::new(AtomicUsize::new(0));
+    let counter = requests.clone();
+    tokio::spawn(async move {
+        loop {
+            let (stream, _) = listener.accept().await.unwrap();
+            let acceptor = acceptor.clone();
+            let counter = counter.clone();
+            tokio::spawn(async move {
+                let Ok(mut stream) = acceptor.accept(stream).await else { return };
+                let mut buf = vec![0; 16 * 1024];
+                if let Ok(n) = stream.read(&mut buf).await {
+                    if n > 0 {
+                        counter.fetch_add(1, Ordering::SeqCst);
+                        let _ = stream.write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\nconnection: close\r\n\r\n").await;
+                        let _ = stream.shutdown().await;
+                    }
+                }
+            });
+        }
+    });
+    (url, requests)
+}
+
+async fn post(client: &Client, url: &str) -> Result<reqwest::StatusCode, reqwest::Error> {
+    client.post(url).json(&json!({"log": "hello"})).send().await.map(|response| response.status())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_private_ca_is_trusted_through_the_logger() {
+        let pki = pki();
+        let (url, requests) = serve(&pki, false, &[]).await;
+
+        // The default roots do not know the private CA.
+        assert!(post(&Client::new(), &url).await.is_err());
+
+        let logger_config = LoggerConfig {
+            service: "test_service".to_string(),
+            environment: "test_env".to_string(),
+            default_type: None,
+            tags: Default::default(),
+        };
+        let mut logger = POGRLogger::new(
+            Client::new(),
+            Some(url),
+            LogConfig::AccessKeys {
+                access_key: "test_access_key".to_string(),
+                secret_key: "test_secret_key".to_string(),
+                logger_config: logger_config.clone(),
+            },
+            logger_config,
+        );
+        logger.set_tls_config(TlsConfig::new().add_root_certificate_pem(pki.ca_pem.as_bytes())).unwrap();
+        logger.custom_log_delivered(Level::Info, "hello", "test", json!({}), json!({})).await.unwrap();
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_client_certificate_for_mutual_tls() {
+        let pki = pki();
+        let (url, requests) = serve(&pki, true, &[]).await;
+        let trusting = TlsConfig::new().add_root_certificate_pem(pki.ca_pem.as_bytes()).only_added_roots();
+
+        assert!(post(&trusting.build_client().unwrap(), &url).await.is_err());
+        assert_eq!(requests.load(Ordering::SeqCst), 0);
+
+        let identified = trusting.client_identity_pem(pki.client_pem.as_bytes(), pki.client_key_pem.as_bytes());
+        assert_eq!(post(&identified.build_client().unwrap(), &url).await.unwrap(), 200);
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_spki_pinning() {
+        let pki = pki();
+        let (url, requests) = serve(&pki, false, &[]).await;
+
+        // The pin is the digest of the key the certificate carries.
+        let pin = spki_sha256_pin(pki.server_pem.as_bytes()).unwrap();
+        let expected = Sha256::digest(pki.server.get_key_pair().public_key_der());
+        assert_eq!(pin, base64_encode(&expected));
+
+        let trusting = TlsConfig::new().add_root_certificate_pem(pki.ca_pem.as_bytes());
+        let pinned = trusting.clone().pin_spki_sha256(&pin).unwrap();
+        assert_eq!(post(&pinned.build_client().unwrap(), &url).await.unwrap(), 200);
+
+        // A valid chain is not enough once a different key is pinned.
+        let other = spki_sha256_pin(pki.client_pem.as_bytes()).unwrap();
+        let mispinned = trusting.pin_spki_sha256(&other).unwrap();
+        assert!(post(&mispinned.build_client().unwrap(), &url).await.is_err());
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_pin_is_not_matched_against_extra_certificates() {
+        let pki = pki();
+        // A server with a valid chain that also sends the pinned certificate, unrelated to its own.
+        let (url, requests) = serve(&pki, false, &[&pki.client_pem]).await;
+
+        let pin = spki_sha256_pin(pki.client_pem.as_bytes()).unwrap();
+        let pinned = TlsConfig::new().add_root_certificate_pem(pki.ca_pem.as_bytes()).pin_spki_sha256(&pin).unwrap();
+        assert!(post(&pinned.build_client().unwrap(), &url).await.is_err());
+        assert_eq!(requests.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn test_invalid_settings_are_rejected() {
+        assert!(matches!(TlsConfig::new().pin_spki_sha256("not base64!"), Err(TlsError::InvalidPin(_))));
+        assert!(matches!(TlsConfig::new().pin_spki_sha256("AAAA"), Err(TlsError::InvalidPin(_))));
+        let garbage = TlsConfig::new().add_root_certificate_pem(b"not a certificate");
+        assert!(matches!(garbage.build_client(), Err(TlsError::InvalidCertificate(_))));
+        let keyless = TlsConfig::new().client_identity_pem(pki().client_pem.as_bytes(), b"");
+        assert!(matches!(keyless.build_client(), Err(TlsError::InvalidKey(_))));
+    }
+
+    fn base64_encode(bytes: &[u8]) -> String {
+        use base64::Engine;
+        base64::engine::general_purpose::STANDARD.encode(bytes)
+    }
+}
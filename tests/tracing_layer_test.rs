@@ -0,0 +1,78 @@
+#![cfg(feature = "tracing")]
+
+use pogr_log_rs::testing;
+use pogr_log_rs::tracing_layer::PogrLayer;
+use pogr_log_rs::{assert_logged, LoggerConfig};
+use log::Level;
+use tracing_subscriber::layer::SubscriberExt;
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_events_become_records() {
+        let (logger, capture) = testing::capture_logger(LoggerConfig::new("test_service", "test_env"));
+        let subscriber = tracing_subscriber::registry().with(PogrLayer::with_logger(logger));
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::warn!(pogr.r#type = "login", user_id = 123, retry = true, "User {} logged in", "ann");
+        });
+
+        assert_logged!(capture, Level::Warn, "login", message: "User ann logged in", data contains {"user_id": 123, "retry": true});
+        let record = &capture.records()[0];
+        assert_eq!(record["service"], "test_service");
+        assert!(record["data"].get("message").is_none());
+        assert!(record["data"].get("pogr.r#type").is_none());
+    }
+
+    #[test]
+    fn test_quoted_type_field() {
+        let (logger, capture) = testing::capture_logger(LoggerConfig::new("test_service", "test_env"));
+        let subscriber = tracing_subscriber::registry().with(PogrLayer::with_logger(logger));
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!(match_id = 7, "pogr.type" = "matchmaking", "Match found");
+        });
+
+        assert_logged!(capture, Level::Info, "matchmaking", data contains {"match_id": 7});
+        assert!(capture.records()[0]["data"].get("pogr.type").is_none());
+    }
+
+    #[test]
+    fn test_debug_fields_are_formatted() {
+        let (logger, capture) = testing::capture_logger(LoggerConfig::new("test_service", "test_env"));
+        let subscriber = tracing_subscriber::registry().with(PogrLayer::with_logger(logger));
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::error!(region = ?Some("eu-west"), "Match failed");
+        });
+
+        assert_logged!(capture, Level::Error, message: "Match failed", data contains {"region": "Some(\"eu-west\")"});
+    }
+
+    #[test]
+    fn test_events_below_the_logger_level_are_filtered() {
+        let (logger, capture) = testing::capture_logger(LoggerConfig::new("test_service", "test_env"));
+        let subscriber = tracing_subscriber::registry().with(PogrLayer::with_logger(logger));
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::debug!("too verbose");
+        });
+
+        assert!(capture.is_empty());
+    }
+
+    #[test]
+    fn test_layer_without_logger_uses_the_global_logger() {
+        let capture = testing::capture();
+        let subscriber = tracing_subscriber::registry().with(PogrLayer::new());
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::error!(attempt = 2, "Payment declined");
+        });
+
+        assert_logged!(capture, Level::Error, message: "Payment declined", data contains {"attempt": 2});
+    }
+}
@@ -0,0 +1,47 @@
+// Shared by the pass cases: a logger that keeps each record's target, message and key-values.
+
+use log::{Log, Metadata, Record};
+use serde_json::{Map, Value};
+use std::sync::Mutex;
+
+pub struct Captured {
+    pub target: String,
+    pub message: String,
+    pub fields: Map<String, Value>,
+}
+
+pub struct CaptureLogger(pub Mutex<Vec<Captured>>);
+
+struct FieldCollector(Map<String, Value>);
+
+impl<'kvs> log::kv::VisitSource<'kvs> for FieldCollector {
+    fn visit_pair(&mut self, key: log::kv::Key<'kvs>, value: log::kv::Value<'kvs>) -> Result<(), log::kv::Error> {
+        self.0.insert(key.to_string(), serde_json::to_value(value).unwrap());
+        Ok(())
+    }
+}
+
+impl Log for CaptureLogger {
+    fn enabled(&self, _metadata: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        let mut collector = FieldCollector(Map::new());
+        record.key_values().visit(&mut collector).unwrap();
+        self.0.lock().unwrap().push(Captured {
+            target: record.target().to_string(),
+            message: record.args().to_string(),
+            fields: collector.0,
+        });
+    }
+
+    fn flush(&self) {}
+}
+
+pub static LOGGER: CaptureLogger = CaptureLogger(Mutex::new(Vec::new()));
+
+pub fn install() {
+    log::set_logger(&LOGGER).unwrap();
+    log::set_max_level(log::LevelFilter::Trace);
+}
@@ -0,0 +1,6 @@
+use pogr_log_rs::pogr_info;
+
+fn main() {
+    // The named parts must be followed by a message.
+    pogr_info!(type: "login", data: {"user_id": 1});
+}
@@ -0,0 +1,9 @@
+use pogr_log_rs::structured_log;
+
+fn main() {
+    // The positional form takes a message, type, data and tags.
+    structured_log!(log::Level::Info, "User logged in", "login", {"user_id": 1});
+    structured_log!(log::Level::Info, "User logged in", {"user_id": 1});
+    // Formatting needs the named parts.
+    structured_log!(log::Level::Info, "User {} logged in", 1);
+}
@@ -0,0 +1,25 @@
+use pogr_log_rs::{pogr_debug, pogr_error, pogr_info, pogr_trace, pogr_warn};
+
+#[path = "../capture.rs"]
+mod capture;
+
+fn main() {
+    capture::install();
+    let user_id = 123;
+
+    pogr_error!(type: "db", "Query failed after {} retries", 3);
+    pogr_warn!(target: "auth", type: "login", data: {"user_id": user_id}, "Slow login");
+    pogr_info!(tags: {"env": "production"}, "User {user_id} logged in");
+    pogr_debug!("plain message");
+    pogr_trace!(target: "cache", "{} hits", 10);
+
+    let records = capture::LOGGER.0.lock().unwrap();
+    assert_eq!(records.len(), 5);
+    assert_eq!(records[0].message, "Query failed after 3 retries");
+    assert_eq!(records[0].fields["pogr.type"], "db");
+    assert_eq!(records[1].target, "auth");
+    assert_eq!(records[1].fields["pogr.data"]["user_id"], 123);
+    assert_eq!(records[2].fields["pogr.tags"]["env"], "production");
+    assert_eq!(records[3].target, module_path!());
+    assert_eq!(records[4].target, "cache");
+}
@@ -0,0 +1,33 @@
+use pogr_log_rs::{pogr_debug, structured_log};
+use serde_json::json;
+
+#[path = "../capture.rs"]
+mod capture;
+
+fn main() {
+    capture::install();
+    let user_id = 123;
+    let tags = json!({"env": "production"});
+
+    structured_log!(log::Level::Info, type: "login", data: {"user_id": user_id}, tags: {"env": "production"}, "User {} logged in", user_id);
+    structured_log!(log::Level::Info, tags: tags.clone(), type: "login", "User {user_id} logged in");
+    structured_log!(target: "auth", log::Level::Info, data: json!([1, 2]), "{} items", 2);
+    pogr_debug!("no structured parts, {} argument", 1);
+    // With a named part, three format arguments are not the positional type, data and tags.
+    structured_log!(log::Level::Info, type: "match_end", "{} scored {} on {}", "ana", 300, "dust");
+
+    let records = capture::LOGGER.0.lock().unwrap();
+    assert_eq!(records.len(), 5);
+    assert_eq!(records[0].message, "User 123 logged in");
+    assert_eq!(records[0].fields["pogr.data"], json!({"user_id": 123}));
+    assert_eq!(records[0].fields["pogr.tags"], tags);
+    assert_eq!(records[1].message, "User 123 logged in");
+    assert_eq!(records[1].fields["pogr.type"], "login");
+    assert!(records[1].fields.get("pogr.data").is_none());
+    assert_eq!(records[2].target, "auth");
+    assert_eq!(records[2].fields["pogr.data"], json!([1, 2]));
+    assert!(records[3].fields.is_empty());
+    assert_eq!(records[4].message, "ana scored 300 on dust");
+    assert_eq!(records[4].fields["pogr.type"], "match_end");
+    assert!(records[4].fields.get("pogr.data").is_none());
+}
@@ -0,0 +1,36 @@
+use pogr_log_rs::structured_log;
+use serde_json::json;
+
+#[path = "../capture.rs"]
+mod capture;
+
+fn main() {
+    capture::install();
+    let user_id = 123;
+
+    // Expressions, inline object literals, and any mix of the two.
+    structured_log!(log::Level::Info, "User logged in", "login", json!({"user_id": user_id}), json!({"env": "production"}));
+    structured_log!(log::Level::Info, "User logged in", "login", {"user_id": user_id}, {"env": "production"});
+    structured_log!(log::Level::Info, "User logged in", "login", {"user_id": user_id}, json!({"env": "production"}));
+    structured_log!(log::Level::Info, "User logged in", "login", json!({"user_id": user_id}), {"env": "production"},);
+    // The positional message is not a format string.
+    structured_log!(log::Level::Warn, "literal {braces}", String::from("login"), {}, {});
+    structured_log!(target: "auth", log::Level::Info, "User logged in", "login", {"user_id": user_id}, {});
+    // Three arguments after the message are always the type, data and tags.
+    structured_log!(log::Level::Info, "{} scored {} on {}", "match_end", json!({"score": 300}), json!({}));
+
+    let records = capture::LOGGER.0.lock().unwrap();
+    assert_eq!(records.len(), 7);
+    for record in &records[..4] {
+        assert_eq!(record.message, "User logged in");
+        assert_eq!(record.fields["pogr.type"], "login");
+        assert_eq!(record.fields["pogr.data"], json!({"user_id": 123}));
+        assert_eq!(record.fields["pogr.tags"], json!({"env": "production"}));
+        assert_eq!(record.target, module_path!());
+    }
+    assert_eq!(records[4].message, "literal {braces}");
+    assert_eq!(records[5].target, "auth");
+    assert_eq!(records[6].message, "{} scored {} on {}");
+    assert_eq!(records[6].fields["pogr.type"], "match_end");
+    assert_eq!(records[6].fields["pogr.data"], json!({"score": 300}));
+}